@@ -0,0 +1,146 @@
+//! Access control lists of RFC 3744.
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, Response};
+
+use crate::types::acl_cmd::{acl_body, parse_acl, Ace, Privileges};
+use crate::types::list_cmd::status_is_ok;
+use crate::types::propfind_cmd::PropfindRequest;
+use crate::types::{Dav2xx, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    /// The access control entries of a resource, in the order the server evaluates them
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn acl(&self, path: &str) -> Result<Vec<Ace>> {
+        let request = PropfindRequest::props([("DAV:", "acl")]);
+        let response = self
+            .list_with_raw(path, Depth::Number(0), &request)
+            .await?
+            .dav2xx()
+            .await?;
+        parse_acl(&response.text().await?)
+    }
+
+    pub async fn set_acl_raw(&self, path: &str, aces: &[Ace]) -> Result<Response> {
+        let builder = self
+            .start_request(Method::from_bytes(b"ACL")?, path)
+            .await?
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(acl_body(aces));
+        self.execute(builder).await
+    }
+
+    /// Replace the access control entries of a resource with the ACL method
+    ///
+    /// Protected and inherited entries are left out of the request, so the entries returned by
+    /// `acl` can be edited and sent back.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn set_acl(&self, path: &str, aces: Vec<Ace>) -> Result<()> {
+        self.set_acl_raw(path, &aces).await?.dav2xx().await?;
+        Ok(())
+    }
+
+    /// The privileges the current user has on a resource, `None` when the server does not
+    /// report them
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn privileges(&self, path: &str) -> Result<Option<Privileges>> {
+        let request = PropfindRequest::props([("DAV:", "current-user-privilege-set")]);
+        let response = self
+            .list_with_raw(path, Depth::Number(0), &request)
+            .await?
+            .dav2xx()
+            .await?;
+        let multi_status = self.parse_multi_status(&response.text().await?)?;
+        Ok(multi_status
+            .responses
+            .into_iter()
+            .flat_map(|response| response.prop_stat)
+            .filter(|prop_stat| prop_stat.status.is_empty() || status_is_ok(&prop_stat.status))
+            .find_map(|prop_stat| prop_stat.prop.privileges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::acl_cmd::{Ace, Principal, Privileges};
+    use crate::types::list_cmd::ListMultiStatus;
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn privileges_of_read_only_share() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/shared"))
+            .and(header("depth", "0"))
+            .and(body_string_contains("current-user-privilege-set"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?><d:multistatus xmlns:d="DAV:"><d:response><d:href>/dav/shared/</d:href><d:propstat><d:prop><d:current-user-privilege-set><d:privilege><d:read/></d:privilege><d:privilege><d:read-current-user-privilege-set/></d:privilege></d:current-user-privilege-set></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response></d:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let privileges = client.privileges("/shared").await.unwrap().unwrap();
+        assert!(privileges.contains(Privileges::READ));
+        assert!(!privileges.contains(Privileges::WRITE_CONTENT));
+    }
+
+    #[tokio::test]
+    async fn set_acl_sends_editable_entries() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("ACL"))
+            .and(path("/dav/shared"))
+            .and(body_string_contains(
+                "<D:ace><D:principal><D:authenticated/></D:principal><D:grant><D:privilege><D:read/></D:privilege></D:grant></D:ace></D:acl>",
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let protected = Ace {
+            protected: true,
+            ..Ace::grant(Principal::SelfPrincipal, Privileges::ALL)
+        };
+        client
+            .set_acl(
+                "/shared",
+                vec![
+                    protected,
+                    Ace::grant(Principal::Authenticated, Privileges::READ),
+                ],
+            )
+            .await
+            .unwrap();
+        mock_server.verify().await;
+    }
+
+    #[test]
+    fn parse_privileges_and_owner() {
+        let multi_status = ListMultiStatus::parse(
+            r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/</D:href><D:propstat><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:owner><D:href>/principals/alice/</D:href></D:owner><D:current-user-privilege-set><D:privilege><D:all/></D:privilege></D:current-user-privilege-set><D:resourcetype><D:collection/></D:resourcetype></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response></D:multistatus>"#,
+        )
+        .unwrap();
+        let prop = &multi_status.responses[0].prop_stat[0].prop;
+        assert_eq!(prop.owner.as_deref(), Some("/principals/alice/"));
+        assert_eq!(prop.privileges, Some(Privileges::ALL));
+        assert!(prop.other.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+//! Reporting destructive operations to the audit hook.
+
+use std::future::Future;
+
+use reqwest::Response;
+
+use crate::types::hooks::{AuditAction, AuditEvent, AuditStage};
+use crate::types::{Error, Result};
+use crate::Client;
+
+impl Client {
+    /// Run `request` between the before and after audit events of the operation.
+    pub(crate) async fn audited<F>(
+        &self,
+        action: AuditAction,
+        path: &str,
+        destination: Option<&str>,
+        request: F,
+    ) -> Result<Response>
+    where
+        F: Future<Output = Result<Response>>,
+    {
+        let Some(hook) = &self.hooks.on_audit else {
+            return request.await;
+        };
+        let mut event = AuditEvent {
+            action,
+            path: path.to_owned(),
+            destination: destination.map(|destination| destination.to_owned()),
+            stage: AuditStage::Before,
+        };
+        if !hook(&event) {
+            return Err(Error::Denied(event));
+        }
+        let result = request.await;
+        let status = result.as_ref().ok().map(|response| response.status());
+        event.stage = AuditStage::After {
+            success: status.map(|status| status.is_success()).unwrap_or(false),
+            status: status.map(|status| status.as_u16()),
+        };
+        hook(&event);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::types::hooks::{AuditAction, AuditStage};
+    use crate::{ClientBuilder, Error};
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn audit_hook_sees_and_can_refuse_deletes() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let seen = events.clone();
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .on_audit(move |event| {
+                seen.lock().unwrap().push(event.clone());
+                event.path != "protected"
+            })
+            .build()
+            .unwrap();
+        client.delete("scratch").await.unwrap();
+        assert!(matches!(
+            client.delete("protected").await,
+            Err(Error::Denied(event)) if event.action == AuditAction::Delete
+        ));
+        client.delete_if_match("tagged", "\"v1\"").await.unwrap();
+        assert!(matches!(
+            client.delete_if_match("protected", "\"v1\"").await,
+            Err(Error::Denied(event)) if event.action == AuditAction::Delete
+        ));
+
+        mock_server.verify().await;
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[3].path, "tagged");
+        assert_eq!(
+            events[4].stage,
+            AuditStage::After {
+                success: true,
+                status: Some(204)
+            }
+        );
+        assert_eq!(
+            events[1].stage,
+            AuditStage::After {
+                success: true,
+                status: Some(204)
+            }
+        );
+    }
+}
@@ -1,58 +1,169 @@
 //! Implements the authentication logic for the server.
 
-use crate::types::Error;
-use crate::{Auth, Client, DecodeError, StatusMismatchedError};
-use digest_auth::{AuthContext, HttpMethod};
+use crate::types::{AuthScheme, Result};
+use crate::{Auth, Client, DecodeError, Depth, Error};
+#[cfg(feature = "digest")]
+use crate::{RequestInfo, StatusMismatchedError};
+#[cfg(feature = "digest")]
+use digest_auth::{AlgorithmType, AuthContext, HttpMethod, WwwAuthenticateHeader};
 use http::Method;
-use reqwest::RequestBuilder;
+#[cfg(feature = "digest")]
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use reqwest::{Request, RequestBuilder, Response};
+#[cfg(feature = "digest")]
 use std::ops::Deref;
 use url::Url;
 
 impl Client {
     /// Apply the current auth method to the request.
+    #[cfg_attr(not(feature = "digest"), allow(unused_variables))]
     pub(crate) async fn apply_authentication(
         &self,
         mut builder: RequestBuilder,
         method: &Method,
         url: &Url,
-    ) -> Result<RequestBuilder, Error> {
+    ) -> Result<RequestBuilder> {
         match &self.auth {
             Auth::Anonymous => {}
             Auth::Basic(username, password) => {
                 builder = builder.basic_auth(username, Some(password));
             }
+            Auth::Bearer(token) => {
+                builder = builder.bearer_auth(token);
+            }
+            Auth::TokenProvider(provider) => {
+                builder = builder.bearer_auth(provider.get_token().await?);
+            }
+            #[cfg(feature = "digest")]
             Auth::Digest(username, password) => {
                 self.setup_digest_auth_if_not_initialized(method, url)
                     .await?;
-                let mut context = AuthContext::new(username, password, url.path());
-                context.method = HttpMethod::from(method.to_string());
-                let mut digest_state_lock = self.digest_auth.lock().await;
-                match digest_state_lock.as_mut() {
-                    // This should be unreachable unless a silent error occurs in the setup_digest_auth_if_not_initialized function.
-                    None => return Err(Error::MissingAuthContext),
-                    Some(state) => {
-                        let response = state.respond(&context)?;
-                        builder = builder.header("Authorization", response.to_header_string());
-                    }
-                }
+                let header = self.digest_header(username, password, method, url).await?;
+                builder = builder.header("Authorization", header);
             }
+            Auth::Auto { username, password } => match self.negotiate_auth().await? {
+                AuthScheme::Anonymous => {}
+                AuthScheme::Basic => {
+                    builder = builder.basic_auth(username, Some(password));
+                }
+                #[cfg(feature = "digest")]
+                AuthScheme::Digest => {
+                    let header = self.digest_header(username, password, method, url).await?;
+                    builder = builder.header("Authorization", header);
+                }
+            },
         };
         Ok(builder)
     }
 
+    /// The scheme of `Auth::Auto`, asking the server the first time with an unauthenticated
+    /// depth 0 PROPFIND on the host, which is safe to send whatever the real request is.
+    async fn negotiate_auth(&self) -> Result<AuthScheme> {
+        let mut negotiated = self.negotiated_auth.lock().await;
+        if let Some(scheme) = *negotiated {
+            return Ok(scheme);
+        }
+        let mut anonymous = self.clone();
+        anonymous.auth = Auth::Anonymous;
+        let response = Box::pin(anonymous.list_raw("", Depth::Number(0))).await?;
+        let scheme = if response.status().as_u16() != 401 {
+            AuthScheme::Anonymous
+        } else {
+            let headers = response.headers();
+            #[cfg(feature = "digest")]
+            let digest = strongest_digest_challenge(headers);
+            #[cfg(not(feature = "digest"))]
+            let digest: Option<()> = None;
+            let basic = headers
+                .get_all(reqwest::header::WWW_AUTHENTICATE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .any(|value| value.trim_start().to_ascii_lowercase().starts_with("basic"));
+            match digest {
+                #[cfg(feature = "digest")]
+                Some(challenge) => {
+                    *self.digest_auth.lock().await = Some(challenge);
+                    AuthScheme::Digest
+                }
+                _ if basic => AuthScheme::Basic,
+                _ => return Err(Error::Decode(DecodeError::NoAuthHeaderInResponse)),
+            }
+        };
+        *negotiated = Some(scheme);
+        Ok(scheme)
+    }
+
+    /// The `Authorization` header answering the current digest challenge.
+    #[cfg(feature = "digest")]
+    async fn digest_header(
+        &self,
+        username: &str,
+        password: &str,
+        method: &Method,
+        url: &Url,
+    ) -> Result<String> {
+        let mut context = AuthContext::new(username, password, url.path());
+        context.method = HttpMethod::from(method.to_string());
+        let mut digest_state_lock = self.digest_auth.lock().await;
+        match digest_state_lock.as_mut() {
+            // This should be unreachable unless a silent error occurs in the setup_digest_auth_if_not_initialized function.
+            None => Err(Error::MissingAuthContext),
+            Some(state) => Ok(state.respond(&context)?.to_header_string()),
+        }
+    }
+
+    /// Send a request, answering a new digest challenge once
+    ///
+    /// Servers reject requests made with an expired nonce with a 401 carrying a new challenge,
+    /// usually marked `stale=true`. The digest context is replaced by that challenge and the
+    /// request is sent again with a fresh `Authorization` header.
+    pub(crate) async fn send_authenticated(
+        &self,
+        request: Request,
+        request_id: Option<&str>,
+    ) -> Result<Response> {
+        #[cfg(feature = "digest")]
+        let credentials = match &self.auth {
+            Auth::Digest(username, password) => Some((username, password)),
+            Auth::Auto { username, password }
+                if *self.negotiated_auth.lock().await == Some(AuthScheme::Digest) =>
+            {
+                Some((username, password))
+            }
+            _ => None,
+        };
+        #[cfg(feature = "digest")]
+        if let (Some((username, password)), Some(mut retry)) = (credentials, request.try_clone()) {
+            let response = self.send_now(request, request_id).await?;
+            if response.status().as_u16() != 401 {
+                return Ok(response);
+            }
+            let Some(challenge) = strongest_digest_challenge(response.headers()) else {
+                return Ok(response);
+            };
+            *self.digest_auth.lock().await = Some(challenge);
+            let header = self
+                .digest_header(username, password, retry.method(), retry.url())
+                .await?;
+            retry
+                .headers_mut()
+                .insert(AUTHORIZATION, HeaderValue::from_str(&header)?);
+            return self.send_now(retry, request_id).await;
+        }
+        self.send_now(request, request_id).await
+    }
+
     /// Get the setup status of the digest auth context.
     ///
     /// Self contained in a function to make the lock bounds limited and clear.
+    #[cfg(feature = "digest")]
     async fn is_digest_auth_initialized(&self) -> bool {
         self.digest_auth.lock().await.deref().is_some()
     }
 
     /// Setup the digest auth context if it is not already setup.
-    async fn setup_digest_auth_if_not_initialized(
-        &self,
-        method: &Method,
-        url: &Url,
-    ) -> Result<(), Error> {
+    #[cfg(feature = "digest")]
+    async fn setup_digest_auth_if_not_initialized(&self, method: &Method, url: &Url) -> Result<()> {
         if !self.is_digest_auth_initialized().await {
             self.probe_server_for_digest_auth(method, url).await?;
         }
@@ -60,7 +171,8 @@ impl Client {
     }
 
     /// Make a request with the intention of getting a 401 error and updating the authorisation.
-    async fn probe_server_for_digest_auth(&self, method: &Method, url: &Url) -> Result<(), Error> {
+    #[cfg(feature = "digest")]
+    async fn probe_server_for_digest_auth(&self, method: &Method, url: &Url) -> Result<()> {
         let response = self
             .agent
             .request(method.clone(), url.as_str())
@@ -69,6 +181,10 @@ impl Client {
         let code = response.status().as_u16();
         if code == 401 {
             let headers = response.headers();
+            if let Some(challenge) = strongest_digest_challenge(headers) {
+                *self.digest_auth.lock().await = Some(challenge);
+                return Ok(());
+            }
             let www_auth = headers
                 .get("www-authenticate")
                 .ok_or(Error::Decode(DecodeError::NoAuthHeaderInResponse))?
@@ -80,6 +196,12 @@ impl Client {
                 StatusMismatchedError {
                     response_code: code,
                     expected_code: 401,
+                    request_id: None,
+                    request: Some(Box::new(RequestInfo {
+                        method: Some(method.to_string()),
+                        url: url.to_string(),
+                    })),
+                    retry_after: None,
                 },
             )))
         }
@@ -87,7 +209,8 @@ impl Client {
 
     /// Update the authentication context which right now is just
     /// for digest authentication.
-    async fn update_auth_context(&self, auth_header: &str) -> Result<(), Error> {
+    #[cfg(feature = "digest")]
+    async fn update_auth_context(&self, auth_header: &str) -> Result<()> {
         let auth_context = digest_auth::parse(auth_header)?;
         let mut session_auth = self.digest_auth.lock().await;
         *session_auth = Some(auth_context);
@@ -95,11 +218,37 @@ impl Client {
     }
 }
 
-#[cfg(test)]
+/// The digest challenge with the strongest algorithm among the `WWW-Authenticate` headers
+///
+/// RFC 7616 servers may offer one challenge per algorithm, SHA-512-256 is preferred over
+/// SHA-256, which is preferred over MD5. The algorithm and `userhash` of the challenge are
+/// then used when answering it.
+#[cfg(feature = "digest")]
+fn strongest_digest_challenge(headers: &HeaderMap) -> Option<WwwAuthenticateHeader> {
+    let strength = |challenge: &WwwAuthenticateHeader| match challenge.algorithm.algo {
+        AlgorithmType::MD5 => 0,
+        AlgorithmType::SHA2_256 => 1,
+        AlgorithmType::SHA2_512_256 => 2,
+    };
+    headers
+        .get_all(WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter(|value| {
+            value
+                .trim_start()
+                .to_ascii_lowercase()
+                .starts_with("digest")
+        })
+        .filter_map(|value| digest_auth::parse(value).ok())
+        .max_by_key(strength)
+}
+
+#[cfg(all(test, feature = "digest"))]
 mod tests {
     use crate::{Auth, Client, ClientBuilder, Depth};
     use std::time::Duration;
-    use wiremock::matchers::{basic_auth, header_exists, method};
+    use wiremock::matchers::{basic_auth, header, header_exists, header_regex, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn setup_digest_client(host: String) -> Client {
@@ -127,6 +276,125 @@ mod tests {
         assert_eq!(auth_context.as_ref().unwrap().realm, "example.com");
     }
 
+    #[tokio::test]
+    async fn stale_nonce_is_renewed_and_request_retried() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header_regex("authorization", r#"nonce="new""#))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(401).append_header(
+                "WWW-Authenticate",
+                "Digest realm=\"example.com\", qop=\"auth\", nonce=\"new\", stale=true",
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = setup_digest_client(mock_server.uri());
+        client
+            .update_auth_context("Digest realm=\"example.com\", qop=\"auth\", nonce=\"old\"")
+            .await
+            .unwrap();
+        let response = client.get_raw("/").await.unwrap();
+        assert_eq!(response.status(), 200);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn strongest_digest_algorithm_is_chosen() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header_regex("authorization", "algorithm=SHA-256"))
+            .and(header_regex("authorization", "userhash=true"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(401)
+                    .append_header(
+                        "WWW-Authenticate",
+                        "Digest realm=\"example.com\", qop=\"auth\", nonce=\"n1\", algorithm=MD5",
+                    )
+                    .append_header(
+                        "WWW-Authenticate",
+                        "Digest realm=\"example.com\", qop=\"auth\", nonce=\"n1\", algorithm=SHA-256, userhash=true",
+                    ),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = setup_digest_client(mock_server.uri());
+        let response = client.get_raw("/").await.unwrap();
+        assert_eq!(response.status(), 200);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn auto_auth_negotiates_basic_once() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(basic_auth("user", "password"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/"))
+            .and(header("depth", "0"))
+            .respond_with(
+                ResponseTemplate::new(401).append_header("WWW-Authenticate", "Basic realm=\"dav\""),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .set_auth(Auth::Auto {
+                username: "user".to_owned(),
+                password: "password".to_owned(),
+            })
+            .build()
+            .unwrap();
+        assert_eq!(client.get_raw("/a").await.unwrap().status(), 200);
+        assert_eq!(client.get_raw("/b").await.unwrap().status(), 200);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn auto_auth_probe_does_not_repeat_the_request() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .respond_with(ResponseTemplate::new(207))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/a.txt"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .set_auth(Auth::Auto {
+                username: "user".to_owned(),
+                password: "password".to_owned(),
+            })
+            .build()
+            .unwrap();
+        client.delete("/a.txt").await.unwrap();
+        mock_server.verify().await;
+    }
+
     #[tokio::test]
     async fn can_updated_existing_auth_context() {
         let client = setup_digest_client("http://example.com".to_owned());
@@ -271,4 +539,42 @@ mod tests {
         let response = client.get_raw("/").await.unwrap();
         assert_eq!(response.status().as_u16(), 200);
     }
+
+    #[tokio::test]
+    async fn token_provider_is_asked_per_request() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Default)]
+        struct Counter(AtomicUsize);
+
+        #[async_trait::async_trait]
+        impl crate::TokenProvider for Counter {
+            async fn get_token(&self) -> crate::Result<String> {
+                Ok(format!("token-{}", self.0.fetch_add(1, Ordering::SeqCst)))
+            }
+        }
+
+        let mock_server = MockServer::start().await;
+        for token in ["token-0", "token-1"] {
+            Mock::given(method("GET"))
+                .and(header(
+                    "authorization",
+                    format!("Bearer {}", token).as_str(),
+                ))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .set_auth(Auth::TokenProvider(Arc::new(Counter::default())))
+            .build()
+            .unwrap();
+        client.get("/a").await.unwrap();
+        client.get("/b").await.unwrap();
+        mock_server.verify().await;
+    }
 }
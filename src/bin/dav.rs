@@ -0,0 +1,146 @@
+//! A small webdav command line client built on reqwest_dav.
+//!
+//! The server is read from `DAV_HOST`, credentials from `DAV_USER` and `DAV_PASSWORD`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use reqwest_dav::list_cmd::ListEntity;
+use reqwest_dav::{Auth, Client, ClientBuilder, Depth};
+
+const USAGE: &str = "usage: dav <command> [args]
+
+commands:
+    ls <path>                 list a folder
+    get <path> [file]         download a file, to stdout without <file>
+    put <file> <path>         upload a file
+    rm <path>                 delete a file or folder
+    mkdir <path>              create a folder
+    mv <from> <to>            move a file or folder
+    sync <dir> <path>         upload new and changed files of a local folder
+
+environment:
+    DAV_HOST                  url of the webdav server
+    DAV_USER, DAV_PASSWORD    credentials for basic authentication";
+
+type CliResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, args)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    match run(command, args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            let mut message = err.to_string();
+            let mut source = err.source();
+            while let Some(cause) = source {
+                message = format!("{message}: {cause}");
+                source = cause.source();
+            }
+            eprintln!("dav {command}: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn client() -> CliResult<Client> {
+    let host = std::env::var("DAV_HOST").map_err(|_| "DAV_HOST is not set")?;
+    let auth = match (std::env::var("DAV_USER"), std::env::var("DAV_PASSWORD")) {
+        (Ok(user), Ok(password)) => Auth::Basic(user, password),
+        _ => Auth::Anonymous,
+    };
+    Ok(ClientBuilder::new().set_host(host).set_auth(auth).build()?)
+}
+
+async fn run(command: &str, args: &[String]) -> CliResult {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match (command, args.as_slice()) {
+        ("ls", [path]) => ls(&client()?, path).await,
+        ("get", [path]) => get(&client()?, path, None).await,
+        ("get", [path, file]) => get(&client()?, path, Some(Path::new(file))).await,
+        ("put", [file, path]) => {
+            let data = tokio::fs::read(file).await?;
+            client()?.put(path, data).await?;
+            Ok(())
+        }
+        ("rm", [path]) => Ok(client()?.delete(path).await?),
+        ("mkdir", [path]) => Ok(client()?.mkcol(path).await?),
+        ("mv", [from, to]) => Ok(client()?.mv(from, to).await?),
+        ("sync", [dir, path]) => sync(&client()?, Path::new(dir), path).await,
+        _ => Err(USAGE.into()),
+    }
+}
+
+async fn ls(client: &Client, path: &str) -> CliResult {
+    for entity in client.list(path, Depth::Number(1)).await? {
+        match entity {
+            ListEntity::File(file) => {
+                println!("{:>12}  {}", file.content_length, file.href)
+            }
+            ListEntity::Folder(folder) => println!("{:>12}  {}", "-", folder.href),
+        }
+    }
+    Ok(())
+}
+
+async fn get(client: &Client, path: &str, file: Option<&Path>) -> CliResult {
+    let data = client.get(path).await?.bytes().await?;
+    match file {
+        Some(file) => tokio::fs::write(file, data).await?,
+        None => {
+            use std::io::Write;
+            std::io::stdout().write_all(&data)?
+        }
+    }
+    Ok(())
+}
+
+/// Upload the files of `dir` which are missing remotely or differ in size.
+async fn sync(client: &Client, dir: &Path, path: &str) -> CliResult {
+    let remote = format!("/{}", path.trim_matches('/'));
+    // remote paths, with the size of files
+    let mut existing = HashMap::new();
+    match client.list(&remote, Depth::Infinity).await {
+        Ok(entities) => {
+            for entity in entities {
+                let size = match &entity {
+                    ListEntity::File(file) => Some(file.content_length),
+                    ListEntity::Folder(_) => None,
+                };
+                existing.insert(entity.rel_path(client), size);
+            }
+        }
+        Err(_) => client.mkcol(&remote).await?,
+    }
+    let lookup = |target: &str| existing.get(target).copied();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(dir.join(&relative)).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let relative = relative.join(entry.file_name());
+            let target = format!(
+                "{}/{}",
+                remote.trim_end_matches('/'),
+                relative.to_string_lossy()
+            );
+            let metadata = entry.metadata().await?;
+            if metadata.is_dir() {
+                if lookup(&target).is_none() {
+                    client.mkcol(&target).await?;
+                }
+                pending.push(relative);
+            } else if lookup(&target) != Some(Some(metadata.len() as i64)) {
+                println!("{target}");
+                client
+                    .put(&target, tokio::fs::read(entry.path()).await?)
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,155 @@
+//! A blocking client, for callers which do not run an async runtime.
+//!
+//! Enabled with the `blocking` feature. The client drives the async `Client` on its own
+//! current-thread runtime, so authentication, quirks and hooks behave the same. Like
+//! `reqwest::blocking` it must not be used from within an async runtime.
+
+use std::sync::Arc;
+
+use tokio::runtime::Runtime;
+
+use crate::types::copy_cmd::{CopyOptions, MoveOptions};
+use crate::types::list_cmd::ListEntity;
+use crate::types::put_cmd::PutResponse;
+use crate::types::Result;
+use crate::Depth;
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    pub fn new(inner: crate::Client) -> Result<Client> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Client {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The async client doing the work.
+    pub fn inner(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    /// Get the content of a file from Webdav server
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn get(&self, path: &str) -> Result<Vec<u8>> {
+        self.runtime.block_on(async {
+            let response = self.inner.get(path).await?;
+            Ok(response.bytes().await?.to_vec())
+        })
+    }
+
+    /// Upload a file/zip on Webdav server
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn put(&self, path: &str, body: impl Into<Vec<u8>>) -> Result<PutResponse> {
+        self.runtime.block_on(self.inner.put(path, body.into()))
+    }
+
+    /// Deletes the collection, file, folder or zip archive at the given path on Webdav server
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn delete(&self, path: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.delete(path))
+    }
+
+    /// Creates a directory on Webdav server
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn mkcol(&self, path: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.mkcol(path))
+    }
+
+    /// Creates a directory and its missing parents on Webdav server
+    ///
+    /// Use absolute path to the webdav server folder location
+    pub fn mkcol_recursive(&self, path: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.mkcol_recursive(path))
+    }
+
+    /// Unzips the .zip archieve on Webdav server
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn unzip(&self, path: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.unzip(path))
+    }
+
+    /// Rename or move a collection, file, folder on Webdav server
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn mv(&self, from: &str, to: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.mv(from, to))
+    }
+
+    /// Rename or move a collection, file, folder on Webdav server with the given options
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn mv_with(&self, from: &str, to: &str, options: MoveOptions) -> Result<()> {
+        self.runtime.block_on(self.inner.mv_with(from, to, options))
+    }
+
+    /// Copy a collection, file, folder on Webdav server
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn cp(&self, from: &str, to: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.cp(from, to))
+    }
+
+    /// Copy a collection, file, folder on Webdav server with the given options
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn cp_with(&self, from: &str, to: &str, options: CopyOptions) -> Result<()> {
+        self.runtime.block_on(self.inner.cp_with(from, to, options))
+    }
+
+    /// List files and folders at the given path on Webdav server
+    ///
+    /// Use absolute path to the webdav server folder location
+    pub fn list(&self, path: &str, depth: Depth) -> Result<Vec<ListEntity>> {
+        self.runtime.block_on(self.inner.list(path, depth))
+    }
+}
+
+impl crate::ClientBuilder {
+    /// Build a blocking client, see `blocking::Client`
+    pub fn build_blocking(self) -> Result<Client> {
+        Client::new(self.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ClientBuilder;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn blocking_get_outside_of_a_runtime() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mock_server = runtime.block_on(async {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/a.txt"))
+                .respond_with(ResponseTemplate::new(200).set_body_string("content"))
+                .mount(&mock_server)
+                .await;
+            mock_server
+        });
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build_blocking()
+            .unwrap();
+        assert_eq!(client.get("a.txt").unwrap(), b"content");
+    }
+}
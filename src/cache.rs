@@ -0,0 +1,161 @@
+//! GET requests revalidated against a cache of bodies, see `ClientBuilder::set_cache`.
+
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use reqwest::{Method, Response, StatusCode};
+
+use crate::types::cache_cmd::{CachedBody, GetCache};
+use crate::types::{Dav2xx, Result};
+use crate::Client;
+
+impl Client {
+    /// GET `path`, sending the tag of the cached body in `If-None-Match`
+    ///
+    /// A `304 Not Modified` is answered with the cached body, any other successful response
+    /// carrying an `ETag` replaces the cached one. Bodies over the size limit of the cache, or of
+    /// unknown length, are streamed and evict the cached one.
+    pub(crate) async fn get_cached(&self, cache: &GetCache, path: &str) -> Result<Response> {
+        let key = self.url_for(path)?.to_string();
+        let cached = cache.store.load(&key).await;
+        let mut builder = self.start_request(Method::GET, path).await?;
+        if let Some(cached) = &cached {
+            builder = builder.header(IF_NONE_MATCH, cached.etag.as_str());
+        }
+        let response = self.execute(builder).await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                return Ok(cached_response(&response, cached));
+            }
+        }
+        let response = response.dav2xx().await?;
+        let etag = match response.headers().get(ETAG).map(|etag| etag.to_str()) {
+            Some(Ok(etag)) => etag.to_owned(),
+            _ => {
+                cache.store.remove(&key).await;
+                return Ok(response);
+            }
+        };
+        let fits =
+            matches!(response.content_length(), Some(length) if length <= cache.max_body_size);
+        if !fits {
+            cache.store.remove(&key).await;
+            return Ok(response);
+        }
+        let mut rebuilt = http::Response::new(Vec::new());
+        *rebuilt.status_mut() = response.status();
+        *rebuilt.headers_mut() = response.headers().clone();
+        *rebuilt.extensions_mut() = response.extensions().clone();
+        let body = response.bytes().await?.to_vec();
+        let content_type = rebuilt
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        cache
+            .store
+            .store(
+                &key,
+                CachedBody {
+                    etag,
+                    content_type,
+                    body: body.clone(),
+                },
+            )
+            .await;
+        *rebuilt.body_mut() = body;
+        Ok(Response::from(rebuilt))
+    }
+}
+
+/// A `200 OK` carrying the cached body, with the headers of the `304` response.
+fn cached_response(not_modified: &Response, cached: CachedBody) -> Response {
+    let mut response = http::Response::new(cached.body);
+    *response.headers_mut() = not_modified.headers().clone();
+    *response.extensions_mut() = not_modified.extensions().clone();
+    if let Some(content_type) = cached
+        .content_type
+        .and_then(|content_type| content_type.parse().ok())
+    {
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+    }
+    Response::from(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::cache_cmd::MemoryStore;
+    use crate::ClientBuilder;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn second_get_is_revalidated() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dav/config.json"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304).insert_header("etag", "\"v1\""))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/config.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_raw("{\"a\":1}", "application/json"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .set_cache(MemoryStore::new())
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            let response = client.get("/config.json").await.unwrap();
+            assert_eq!(response.status(), 200);
+            assert_eq!(
+                response.headers()["content-type"].to_str().unwrap(),
+                "application/json"
+            );
+            assert_eq!(response.text().await.unwrap(), "{\"a\":1}");
+        }
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn large_bodies_are_not_cached() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dav/big.bin"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/big.bin"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_string("0123456789"),
+            )
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .set_cache_max_body_size(4)
+            .set_cache(MemoryStore::new())
+            .build()
+            .unwrap();
+        for _ in 0..2 {
+            let response = client.get("/big.bin").await.unwrap();
+            assert_eq!(response.text().await.unwrap(), "0123456789");
+        }
+        mock_server.verify().await;
+    }
+}
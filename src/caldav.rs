@@ -0,0 +1,70 @@
+//! CalDAV (RFC 4791) REPORTs.
+
+use reqwest::Response;
+
+use crate::types::caldav_cmd::{CalendarObject, CalendarQuery};
+use crate::types::{Dav2xx, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    pub async fn calendar_query_raw(&self, path: &str, query: &CalendarQuery) -> Result<Response> {
+        self.report_raw(path, Depth::Number(1), &query.to_xml())
+            .await
+    }
+
+    /// The calendar objects of a calendar matching `query`
+    ///
+    /// Use absolute path to the webdav server calendar location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn calendar_query(
+        &self,
+        path: &str,
+        query: CalendarQuery,
+    ) -> Result<Vec<CalendarObject>> {
+        let response = self
+            .calendar_query_raw(path, &query)
+            .await?
+            .dav2xx()
+            .await?;
+        Ok(self.parse_multi_status(&response.text().await?)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::caldav_cmd::CalendarQuery;
+    use crate::ClientBuilder;
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn calendar_query_returns_calendar_data() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("REPORT"))
+            .and(path("/dav/cal"))
+            .and(header("depth", "1"))
+            .and(body_string_contains("<C:comp-filter name=\"VTODO\">"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8" ?><D:multistatus xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav"><D:response><D:href>/dav/cal/todo.ics</D:href><D:propstat><D:prop><D:getetag>"1"</D:getetag><C:calendar-data><![CDATA[BEGIN:VCALENDAR
+END:VCALENDAR
+]]></C:calendar-data></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let objects = client
+            .calendar_query("/cal", CalendarQuery::todos())
+            .await
+            .unwrap();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(
+            objects[0].calendar_data.as_deref(),
+            Some("BEGIN:VCALENDAR\nEND:VCALENDAR\n")
+        );
+    }
+}
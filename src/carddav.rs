@@ -0,0 +1,100 @@
+//! CardDAV (RFC 6352) REPORTs.
+
+use reqwest::Response;
+
+use crate::types::carddav_cmd::{
+    address_data, addressbook_multiget_body, addressbook_query_body, PropFilter,
+};
+use crate::types::{Dav2xx, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    async fn addressbook_report(&self, path: &str, body: String) -> Result<Response> {
+        self.report_raw(path, Depth::Number(1), &body).await
+    }
+
+    /// The href and vCard of the cards of an address book matching any of `filters`
+    ///
+    /// All cards are returned when `filters` is empty.
+    ///
+    /// Use absolute path to the webdav server address book location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn addressbook_query(
+        &self,
+        path: &str,
+        filters: &[PropFilter],
+    ) -> Result<Vec<(String, String)>> {
+        let response = self
+            .addressbook_report(path, addressbook_query_body(filters))
+            .await?
+            .dav2xx()
+            .await?;
+        Ok(address_data(
+            self.parse_multi_status(&response.text().await?)?,
+        ))
+    }
+
+    /// The href and vCard of the given cards of an address book, in one request
+    ///
+    /// Cards which do not exist are left out.
+    ///
+    /// Use absolute path to the webdav server address book location
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, hrefs))
+    )]
+    pub async fn addressbook_multiget<S: AsRef<str>>(
+        &self,
+        path: &str,
+        hrefs: &[S],
+    ) -> Result<Vec<(String, String)>> {
+        let response = self
+            .addressbook_report(path, addressbook_multiget_body(hrefs))
+            .await?
+            .dav2xx()
+            .await?;
+        Ok(address_data(
+            self.parse_multi_status(&response.text().await?)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ClientBuilder;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn multiget_returns_cards_by_href() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("REPORT"))
+            .and(path("/dav/contacts"))
+            .and(body_string_contains("<D:href>/dav/contacts/a.vcf</D:href>"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8" ?><D:multistatus xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav"><D:response><D:href>/dav/contacts/a.vcf</D:href><D:propstat><D:prop><D:getetag>"1"</D:getetag><C:address-data>BEGIN:VCARD
+FN:Alice
+END:VCARD
+</C:address-data></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response><D:response><D:href>/dav/contacts/b.vcf</D:href><D:status>HTTP/1.1 404 Not Found</D:status></D:response></D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let cards = client
+            .addressbook_multiget("/contacts", &["/dav/contacts/a.vcf", "/dav/contacts/b.vcf"])
+            .await
+            .unwrap();
+        assert_eq!(
+            cards,
+            vec![(
+                "/dav/contacts/a.vcf".to_owned(),
+                "BEGIN:VCARD\nFN:Alice\nEND:VCARD\n".to_owned()
+            )]
+        );
+    }
+}
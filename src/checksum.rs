@@ -0,0 +1,156 @@
+//! Checksums sent with uploads and verified on downloads.
+
+use bytes::Bytes;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Body;
+
+use crate::types::checksum_cmd::{Checksum, ChecksumAlgorithm};
+use crate::types::{ChecksumMismatchError, DecodeError, Error, FieldError, Result};
+use crate::Client;
+
+/// `OC-Checksum`, and `Content-MD5` for MD5, of a body held in memory.
+pub(crate) fn checksum_headers(body: &Body, algorithm: ChecksumAlgorithm) -> Result<HeaderMap> {
+    let data = body.as_bytes().ok_or_else(|| {
+        Error::Decode(DecodeError::FieldNotSupported(FieldError {
+            field: "checksum of a streamed body".to_owned(),
+        }))
+    })?;
+    let checksum = algorithm.compute(data);
+    let mut headers = HeaderMap::new();
+    headers.insert("oc-checksum", HeaderValue::from_str(&checksum.to_string())?);
+    if let Some(content_md5) = checksum.to_content_md5() {
+        headers.insert("content-md5", HeaderValue::from_str(&content_md5)?);
+    }
+    Ok(headers)
+}
+
+impl Client {
+    /// Download a file and check it against the checksums the server has for it
+    ///
+    /// The checksums come from the `OC-Checksum` or `Content-MD5` response headers, or else from
+    /// the `oc:checksums` property of Nextcloud and ownCloud. Fails with
+    /// `Error::ChecksumMismatch` when the content does not match one of them, and with
+    /// `FieldNotFound` when the server has no checksum in a known algorithm.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_verified(&self, path: &str) -> Result<Bytes> {
+        let response = self.get(path).await?;
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        let mut checksums = header("oc-checksum")
+            .map(|value| Checksum::parse_list(&value))
+            .unwrap_or_default();
+        checksums
+            .extend(header("content-md5").and_then(|value| Checksum::from_content_md5(&value)));
+        let data = response.bytes().await?;
+
+        if checksums.is_empty() {
+            let properties = self
+                .propfind_raw_props(path, &[("http://owncloud.org/ns", "checksums")])
+                .await?;
+            checksums = properties
+                .values()
+                .flat_map(|value| Checksum::parse_list(value))
+                .collect();
+        }
+        if checksums.is_empty() {
+            return Err(Error::Decode(DecodeError::FieldNotFound(FieldError {
+                field: "checksum".to_owned(),
+            })));
+        }
+        for expected in checksums {
+            let actual = expected.algorithm.compute(&data);
+            if actual.value != expected.value {
+                return Err(Error::ChecksumMismatch(ChecksumMismatchError {
+                    path: path.to_owned(),
+                    algorithm: expected.algorithm.name().to_owned(),
+                    expected: expected.value,
+                    actual: actual.value,
+                }));
+            }
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::checksum_cmd::ChecksumAlgorithm;
+    use crate::types::put_cmd::PutOptions;
+    use crate::types::Error;
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn upload_sends_and_download_verifies_checksums() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/a.txt"))
+            .and(header(
+                "oc-checksum",
+                "MD5:900150983cd24fb0d6963f7d28e17f72",
+            ))
+            .and(header("content-md5", "kAFQmDzST7DWlj99KOF/cg=="))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/a.txt"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .append_header(
+                        "oc-checksum",
+                        "SHA1:a9993e364706816aba3e25717850c26c9cd0d89d",
+                    )
+                    .set_body_string("abc"),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/b.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("abd"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/b.txt"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns"><d:response><d:href>/b.txt</d:href><d:propstat><d:prop><oc:checksums><oc:checksum>SHA1:a9993e364706816aba3e25717850c26c9cd0d89d</oc:checksum></oc:checksums></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response></d:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        client
+            .put_with(
+                "/a.txt",
+                "abc",
+                PutOptions {
+                    checksum: Some(ChecksumAlgorithm::Md5),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(&client.get_verified("/a.txt").await.unwrap()[..], b"abc");
+        match client.get_verified("/b.txt").await.unwrap_err() {
+            Error::ChecksumMismatch(error) => {
+                assert_eq!(error.algorithm, "SHA1");
+                assert_eq!(error.expected, "a9993e364706816aba3e25717850c26c9cd0d89d");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        mock_server.verify().await;
+    }
+}
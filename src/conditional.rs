@@ -0,0 +1,166 @@
+//! Requests guarded by `If-Match` and `If-None-Match`.
+
+use reqwest::header::{IF_MATCH, IF_NONE_MATCH};
+use reqwest::{Body, Method, Response, StatusCode};
+
+use crate::types::conditional_cmd::ConditionalGet;
+use crate::types::etag::ETag;
+use crate::types::hooks::AuditAction;
+use crate::types::put_cmd::PutResponse;
+use crate::types::{check_multi_status, Dav2xx, Error, PreconditionFailedError, Result};
+use crate::Client;
+
+impl Client {
+    /// Get a file unless it still has the tag `etag`
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_if_none_match(&self, path: &str, etag: &str) -> Result<ConditionalGet> {
+        let builder = self
+            .start_request(Method::GET, path)
+            .await?
+            .header(IF_NONE_MATCH, ETag::parse(etag).to_header(false));
+        let response = self.execute(builder).await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(ConditionalGet::NotModified);
+        }
+        Ok(ConditionalGet::Modified(response.dav2xx().await?))
+    }
+
+    /// Upload a file only if the remote one still has the tag `etag`
+    ///
+    /// Fails with `Error::PreconditionFailed` when the file was changed in the meantime.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, body))
+    )]
+    pub async fn put_if_match<B: Into<Body>>(
+        &self,
+        path: &str,
+        body: B,
+        etag: &str,
+    ) -> Result<PutResponse> {
+        let etag = ETag::parse(etag).to_header(self.quirks.weak_etags);
+        self.put_with_condition(path, body, IF_MATCH, &etag).await
+    }
+
+    /// Upload a file only if it does not exist yet
+    ///
+    /// Fails with `Error::PreconditionFailed` when the file exists.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, body))
+    )]
+    pub async fn put_if_none_match_star<B: Into<Body>>(
+        &self,
+        path: &str,
+        body: B,
+    ) -> Result<PutResponse> {
+        self.put_with_condition(path, body, IF_NONE_MATCH, "*")
+            .await
+    }
+
+    /// Delete a resource only if it still has the tag `etag`
+    ///
+    /// Fails with `Error::PreconditionFailed` when the resource was changed in the meantime.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn delete_if_match(&self, path: &str, etag: &str) -> Result<()> {
+        let response = self
+            .audited(AuditAction::Delete, path, None, async {
+                let builder = self.start_request(Method::DELETE, path).await?.header(
+                    IF_MATCH,
+                    ETag::parse(etag).to_header(self.quirks.weak_etags),
+                );
+                self.execute(builder).await
+            })
+            .await?;
+        check_multi_status(precondition(path, response).await?).await
+    }
+
+    async fn put_with_condition<B: Into<Body>>(
+        &self,
+        path: &str,
+        body: B,
+        condition: reqwest::header::HeaderName,
+        value: &str,
+    ) -> Result<PutResponse> {
+        let builder = self
+            .start_request(Method::PUT, path)
+            .await?
+            .header("content-type", "application/octet-stream")
+            .header(condition, value)
+            .body(body);
+        let response = precondition(path, self.execute(builder).await?).await?;
+        Ok(PutResponse::from_response(&response))
+    }
+}
+
+/// Map 412 Precondition Failed to `Error::PreconditionFailed`.
+pub(crate) async fn precondition(path: &str, response: Response) -> Result<Response> {
+    if response.status() == StatusCode::PRECONDITION_FAILED {
+        return Err(Error::PreconditionFailed(PreconditionFailedError {
+            path: path.to_owned(),
+            request_id: crate::types::request_id(&response),
+            request: Some(crate::types::RequestInfo::new(&response)),
+        }));
+    }
+    response.dav2xx().await
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::conditional_cmd::ConditionalGet;
+    use crate::types::Error;
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn guarded_requests_map_status_codes() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dav/a.txt"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/dav/a.txt"))
+            .and(header("if-match", "\"v0\""))
+            .respond_with(ResponseTemplate::new(412))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/dav/new.txt"))
+            .and(header("if-none-match", "*"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.get_if_none_match("/a.txt", "\"v1\"").await.unwrap(),
+            ConditionalGet::NotModified
+        ));
+        assert!(matches!(
+            client.put_if_match("/a.txt", "data", "v0").await,
+            Err(Error::PreconditionFailed(_))
+        ));
+        client
+            .put_if_none_match_star("/new.txt", "data")
+            .await
+            .unwrap();
+        mock_server.verify().await;
+    }
+}
@@ -0,0 +1,79 @@
+//! `DavClient` implemented by `Client`.
+
+use reqwest::{Body, Response};
+
+use crate::types::list_cmd::{ListEntity, ListResponse};
+use crate::types::propfind_cmd::PropfindRequest;
+use crate::types::put_cmd::PutResponse;
+use crate::types::{DavClient, Result};
+use crate::{Client, Depth};
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl DavClient for Client {
+    async fn get(&self, path: &str) -> Result<Response> {
+        Client::get(self, path).await
+    }
+
+    async fn put(&self, path: &str, body: Body) -> Result<PutResponse> {
+        Client::put(self, path, body).await
+    }
+
+    async fn list(&self, path: &str, depth: Depth) -> Result<Vec<ListEntity>> {
+        Client::list(self, path, depth).await
+    }
+
+    async fn propfind(
+        &self,
+        path: &str,
+        depth: Depth,
+        request: PropfindRequest,
+    ) -> Result<Vec<ListResponse>> {
+        Client::list_with(self, path, depth, request).await
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        Client::delete(self, path).await
+    }
+
+    async fn mkcol(&self, path: &str) -> Result<()> {
+        Client::mkcol(self, path).await
+    }
+
+    async fn mv(&self, from: &str, to: &str) -> Result<()> {
+        Client::mv(self, from, to).await
+    }
+
+    async fn cp(&self, from: &str, to: &str) -> Result<()> {
+        Client::cp(self, from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::DavClient;
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn client_is_usable_as_trait_object() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("MKCOL"))
+            .and(path("/dav/docs"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client: Box<dyn DavClient> = Box::new(
+            ClientBuilder::new()
+                .set_host(format!("{}/dav", mock_server.uri()))
+                .build()
+                .unwrap(),
+        );
+        client.mkcol("/docs").await.unwrap();
+        mock_server.verify().await;
+    }
+}
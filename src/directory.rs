@@ -0,0 +1,536 @@
+//! Transfers of whole folders between the server and the local file system.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use futures_util::StreamExt;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
+use tokio::task;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+use crate::types::list_cmd::ListEntity;
+use crate::types::put_cmd::PutOptions;
+use crate::types::timestamp::{DateTime, DavDateTime};
+use crate::types::transfer_cmd::{
+    DirEntryResult, DirTransferReport, DownloadOptions, EntryOutcome, UploadOptions,
+};
+use crate::types::{DecodeError, Error, Result, ServerError};
+use crate::{Client, Depth};
+
+impl Client {
+    /// Download a folder and all of its descendants into `local_dir`
+    ///
+    /// Folders are recreated locally, files are downloaded `options.concurrency` at a time.
+    /// A failing file does not stop the others, the report tells what became of each one.
+    /// Once `options.cancellation` is cancelled, files being downloaded are removed again and the
+    /// transfer fails with `Error::TransferCancelled`.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn download_dir(
+        &self,
+        remote_path: &str,
+        local_dir: &Path,
+        options: DownloadOptions,
+    ) -> Result<DirTransferReport> {
+        let client = self.for_transfer(options.cancellation.clone());
+        let mut report = DirTransferReport::default();
+        // boxed, the inlined request futures below would not fit the stack of a plain thread
+        let result =
+            Box::pin(client.download_dir_into(&mut report, remote_path, local_dir, &options)).await;
+        client.finish_transfer(result, report)
+    }
+
+    async fn download_dir_into(
+        &self,
+        report: &mut DirTransferReport,
+        remote_path: &str,
+        local_dir: &Path,
+        options: &DownloadOptions,
+    ) -> Result<()> {
+        let root = format!("/{}", remote_path.trim_matches('/'));
+        let entities = self.list(&root, Depth::Infinity).await?;
+        tokio::fs::create_dir_all(local_dir).await?;
+
+        let mut files = vec![];
+        for entity in entities {
+            let remote = entity.rel_path(self);
+            let Some(local) = local_target(&root, &remote, local_dir) else {
+                continue;
+            };
+            match entity {
+                ListEntity::Folder(_) => tokio::fs::create_dir_all(&local).await?,
+                ListEntity::File(_) => files.push((remote, local)),
+            }
+        }
+
+        let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+        let mut running = JoinSet::new();
+        let mut targets = HashMap::new();
+        for (remote, local) in files {
+            if !options.overwrite && tokio::fs::try_exists(&local).await.unwrap_or(false) {
+                report.entries.push(DirEntryResult {
+                    remote,
+                    local,
+                    outcome: EntryOutcome::Skipped,
+                });
+                continue;
+            }
+            let permit = semaphore.clone().acquire_owned().await;
+            if self.is_cancelled() {
+                report.entries.push(DirEntryResult {
+                    remote,
+                    local,
+                    outcome: EntryOutcome::Cancelled,
+                });
+                continue;
+            }
+            let client = self.clone();
+            let (task_remote, task_local) = (remote.clone(), local.clone());
+            let task = running.spawn(async move {
+                let _permit = permit;
+                let downloading = Box::pin(client.download_file(&task_remote, &task_local));
+                match client.until_cancelled(downloading).await {
+                    Ok(bytes) => EntryOutcome::Transferred { bytes },
+                    Err(Error::Cancelled) => {
+                        let _ = tokio::fs::remove_file(&task_local).await;
+                        EntryOutcome::Cancelled
+                    }
+                    Err(error) => EntryOutcome::Failed(error),
+                }
+            });
+            targets.insert(task.id(), (remote, local));
+        }
+        join_entries(report, running, targets).await;
+        Ok(())
+    }
+
+    /// Upload `local_dir` and all of its descendants to `remote_path`
+    ///
+    /// Missing collections are created parents first, then files are uploaded
+    /// `options.concurrency` at a time. With `options.skip_unchanged` files whose remote copy has
+    /// the same size and is not older are left alone. A failing file does not stop the others,
+    /// the report tells what became of each one. Once `options.cancellation` is cancelled the
+    /// transfer fails with `Error::TransferCancelled`.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn upload_dir(
+        &self,
+        local_dir: &Path,
+        remote_path: &str,
+        options: UploadOptions,
+    ) -> Result<DirTransferReport> {
+        let client = self.for_transfer(options.cancellation.clone());
+        let mut report = DirTransferReport::default();
+        // boxed, the inlined request futures below would not fit the stack of a plain thread
+        let result =
+            Box::pin(client.upload_dir_into(&mut report, local_dir, remote_path, &options)).await;
+        client.finish_transfer(result, report)
+    }
+
+    async fn upload_dir_into(
+        &self,
+        report: &mut DirTransferReport,
+        local_dir: &Path,
+        remote_path: &str,
+        options: &UploadOptions,
+    ) -> Result<()> {
+        let root = format!("/{}", remote_path.trim_matches('/'));
+        // remote paths, with the size and modification time of files
+        let mut existing = HashMap::new();
+        match self.list(&root, Depth::Infinity).await {
+            Ok(entities) => {
+                for entity in entities {
+                    let file = match &entity {
+                        ListEntity::File(file) => {
+                            Some((file.content_length, file.last_modified.epoch_seconds()))
+                        }
+                        ListEntity::Folder(_) => None,
+                    };
+                    existing.insert(entity.rel_path(self), file);
+                }
+            }
+            Err(Error::Decode(DecodeError::Server(ServerError {
+                response_code: 404, ..
+            }))) => self.mkcol(&root).await?,
+            Err(e) => return Err(e),
+        }
+
+        let mut files = vec![];
+        let mut pending = vec![PathBuf::new()];
+        while let Some(relative) = pending.pop() {
+            let mut entries = tokio::fs::read_dir(local_dir.join(&relative)).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let relative = relative.join(entry.file_name());
+                let remote = format!(
+                    "{}/{}",
+                    root.trim_end_matches('/'),
+                    relative.to_string_lossy().replace('\\', "/")
+                );
+                let metadata = entry.metadata().await?;
+                if metadata.is_dir() {
+                    if !existing.contains_key(&remote) {
+                        self.mkcol(&remote).await?;
+                    }
+                    pending.push(relative);
+                    continue;
+                }
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |duration| duration.as_secs() as i64);
+                let unchanged = matches!(
+                    existing.get(&remote),
+                    Some(Some((size, remote_modified)))
+                        if *size == metadata.len() as i64 && *remote_modified >= modified
+                );
+                if options.skip_unchanged && unchanged {
+                    report.entries.push(DirEntryResult {
+                        remote,
+                        local: entry.path(),
+                        outcome: EntryOutcome::Skipped,
+                    });
+                } else {
+                    files.push((remote, entry.path()));
+                }
+            }
+        }
+
+        let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+        let mut running = JoinSet::new();
+        let mut targets = HashMap::new();
+        for (remote, local) in files {
+            let permit = semaphore.clone().acquire_owned().await;
+            if self.is_cancelled() {
+                report.entries.push(DirEntryResult {
+                    remote,
+                    local,
+                    outcome: EntryOutcome::Cancelled,
+                });
+                continue;
+            }
+            let client = self.clone();
+            let preserve_mtime = options.preserve_mtime;
+            let (task_remote, task_local) = (remote.clone(), local.clone());
+            let task = running.spawn(async move {
+                let _permit = permit;
+                let uploading =
+                    Box::pin(client.upload_file(&task_local, &task_remote, preserve_mtime));
+                match client.until_cancelled(uploading).await {
+                    Ok(bytes) => EntryOutcome::Transferred { bytes },
+                    Err(Error::Cancelled) => EntryOutcome::Cancelled,
+                    Err(error) => EntryOutcome::Failed(error),
+                }
+            });
+            targets.insert(task.id(), (remote, local));
+        }
+        join_entries(report, running, targets).await;
+        Ok(())
+    }
+
+    /// The client running a directory transfer, cancelled by `cancellation` if given.
+    fn for_transfer(&self, cancellation: Option<CancellationToken>) -> Client {
+        match cancellation {
+            Some(token) => self.with_cancellation(token),
+            None => self.clone(),
+        }
+    }
+
+    /// The report of a directory transfer, or `Error::TransferCancelled` holding it.
+    fn finish_transfer(
+        &self,
+        result: Result<()>,
+        report: DirTransferReport,
+    ) -> Result<DirTransferReport> {
+        match result {
+            Ok(()) | Err(Error::Cancelled) if self.is_cancelled() => {
+                Err(Error::TransferCancelled(Box::new(report)))
+            }
+            Ok(()) => Ok(report),
+            Err(error) => Err(error),
+        }
+    }
+
+    async fn upload_file(&self, local: &Path, remote: &str, preserve_mtime: bool) -> Result<u64> {
+        let file = tokio::fs::File::open(local).await?;
+        let metadata = file.metadata().await?;
+        let body = reqwest::Body::wrap_stream(tokio_util::io::ReaderStream::new(file));
+        let options = PutOptions {
+            mtime: match preserve_mtime {
+                true => metadata.modified().ok().map(DateTime::from_system_time),
+                false => None,
+            },
+            ..Default::default()
+        };
+        self.put_with(remote, body, options).await?;
+        Ok(metadata.len())
+    }
+
+    async fn download_file(&self, remote: &str, local: &Path) -> Result<u64> {
+        let mut stream = Box::pin(self.get_stream(remote).await?);
+        let mut file = tokio::fs::File::create(local).await?;
+        let mut bytes = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk).await?;
+            bytes += chunk.len() as u64;
+        }
+        file.flush().await?;
+        Ok(bytes)
+    }
+}
+
+/// Add the outcome of every transfer task to `report`, a task which panicked or was aborted
+/// counting as failed.
+async fn join_entries(
+    report: &mut DirTransferReport,
+    mut running: JoinSet<EntryOutcome>,
+    mut targets: HashMap<task::Id, (String, PathBuf)>,
+) {
+    while let Some(joined) = running.join_next_with_id().await {
+        let (id, outcome) = match joined {
+            Ok((id, outcome)) => (id, outcome),
+            Err(error) => (
+                error.id(),
+                EntryOutcome::Failed(Error::Io(std::io::Error::other(error))),
+            ),
+        };
+        if let Some((remote, local)) = targets.remove(&id) {
+            report.entries.push(DirEntryResult {
+                remote,
+                local,
+                outcome,
+            });
+        }
+    }
+}
+
+/// Where `remote`, found below `root`, goes inside `local_dir`.
+///
+/// `None` for the root itself and for paths which would escape `local_dir`.
+fn local_target(root: &str, remote: &str, local_dir: &Path) -> Option<PathBuf> {
+    let relative = remote
+        .strip_prefix(root.trim_end_matches('/'))?
+        .trim_start_matches('/');
+    if relative.is_empty() {
+        return None;
+    }
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+    Some(local_dir.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::transfer_cmd::{DownloadOptions, EntryOutcome, UploadOptions};
+    use crate::types::Error;
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn download_dir_mirrors_the_tree() {
+        let mock_server = MockServer::start().await;
+        let body = r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">
+<D:response><D:href>/dav/docs/</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat></D:response>
+<D:response><D:href>/dav/docs/sub/</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat></D:response>
+<D:response><D:href>/dav/docs/sub/a%20b.txt</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:getcontentlength>5</D:getcontentlength><D:resourcetype/></D:prop></D:propstat></D:response>
+<D:response><D:href>/dav/docs/missing.txt</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:getcontentlength>1</D:getcontentlength><D:resourcetype/></D:prop></D:propstat></D:response>
+<D:response><D:href>/dav/docs/panic.txt</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:getcontentlength>1</D:getcontentlength><D:resourcetype/></D:prop></D:propstat></D:response>
+</D:multistatus>"#;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/docs"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(body))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/docs/sub/a%20b.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/docs/missing.txt"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .on_request(|request| assert!(!request.url.ends_with("panic.txt")))
+            .build()
+            .unwrap();
+        let local_dir = std::env::temp_dir().join(format!("reqwest_dav-{}", uuid::Uuid::new_v4()));
+        let report = client
+            .download_dir("/docs", &local_dir, DownloadOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(local_dir.join("sub/a b.txt")).unwrap(),
+            "hello"
+        );
+        assert!(!report.is_success());
+        let mut failures: Vec<&str> = report.failures().map(|e| e.remote.as_str()).collect();
+        failures.sort();
+        assert_eq!(failures, vec!["/docs/missing.txt", "/docs/panic.txt"]);
+        assert!(report
+            .entries
+            .iter()
+            .any(|entry| entry.remote == "/docs/sub/a b.txt"
+                && matches!(entry.outcome, EntryOutcome::Transferred { bytes: 5 })));
+        std::fs::remove_dir_all(local_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn cancelled_download_reports_progress() {
+        let mock_server = MockServer::start().await;
+        let mut body =
+            r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#.to_owned();
+        for name in ["", "a.txt", "b.txt", "c.txt"] {
+            let resource_type = match name {
+                "" => "<D:collection/>",
+                _ => "",
+            };
+            body.push_str(&format!(
+                r#"<D:response><D:href>/dav/docs/{}</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:getcontentlength>1</D:getcontentlength><D:resourcetype>{}</D:resourcetype></D:prop></D:propstat></D:response>"#,
+                name, resource_type
+            ));
+        }
+        body.push_str("</D:multistatus>");
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/docs"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(body))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/docs/a.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("a"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/docs/c.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("c"))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let token = tokio_util::sync::CancellationToken::new();
+        let cancel = token.clone();
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .on_request(move |request| {
+                if request.url.ends_with("/b.txt") {
+                    cancel.cancel();
+                }
+            })
+            .build()
+            .unwrap();
+        let local_dir = std::env::temp_dir().join(format!("reqwest_dav-{}", uuid::Uuid::new_v4()));
+        let options = DownloadOptions {
+            concurrency: 1,
+            cancellation: Some(token),
+            ..Default::default()
+        };
+        let report = match client.download_dir("/docs", &local_dir, options).await {
+            Err(Error::TransferCancelled(report)) => report,
+            result => panic!("unexpected result {:?}", result),
+        };
+
+        let mut outcomes: Vec<(&str, bool)> = report
+            .entries
+            .iter()
+            .map(|entry| {
+                let transferred = matches!(entry.outcome, EntryOutcome::Transferred { bytes: 1 });
+                assert!(transferred || matches!(entry.outcome, EntryOutcome::Cancelled));
+                (entry.remote.as_str(), transferred)
+            })
+            .collect();
+        outcomes.sort();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("/docs/a.txt", true),
+                ("/docs/b.txt", false),
+                ("/docs/c.txt", false)
+            ]
+        );
+        assert!(local_dir.join("a.txt").exists());
+        assert!(!local_dir.join("b.txt").exists());
+        std::fs::remove_dir_all(local_dir).unwrap();
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn upload_dir_creates_collections_and_skips_unchanged() {
+        let mock_server = MockServer::start().await;
+        let body = r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">
+<D:response><D:href>/dav/docs/</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat></D:response>
+<D:response><D:href>/dav/docs/same.txt</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Fri, 01 Jan 2100 00:00:00 GMT</D:getlastmodified><D:getcontentlength>4</D:getcontentlength><D:resourcetype/></D:prop></D:propstat></D:response>
+</D:multistatus>"#;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/docs"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(body))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("MKCOL"))
+            .and(path("/dav/docs/sub"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/dav/docs/sub/new.txt"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let local_dir = std::env::temp_dir().join(format!("reqwest_dav-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(local_dir.join("sub")).unwrap();
+        std::fs::write(local_dir.join("same.txt"), "same").unwrap();
+        std::fs::write(local_dir.join("sub/new.txt"), "new").unwrap();
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let report = client
+            .upload_dir(
+                &local_dir,
+                "/docs",
+                UploadOptions {
+                    skip_unchanged: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(report.is_success());
+        assert!(report
+            .entries
+            .iter()
+            .any(|entry| entry.remote == "/docs/same.txt"
+                && matches!(entry.outcome, EntryOutcome::Skipped)));
+        assert!(report
+            .entries
+            .iter()
+            .any(|entry| entry.remote == "/docs/sub/new.txt"
+                && matches!(entry.outcome, EntryOutcome::Transferred { bytes: 3 })));
+        mock_server.verify().await;
+        std::fs::remove_dir_all(local_dir).unwrap();
+    }
+}
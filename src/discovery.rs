@@ -0,0 +1,180 @@
+//! Bootstrapping of CalDAV and CardDAV clients (RFC 6764).
+
+use reqwest::header::LOCATION;
+use reqwest::Method;
+use url::Url;
+
+use crate::types::discovery_cmd::{property_hrefs, DavHome};
+use crate::types::list_cmd::{CALDAV_NAMESPACE, CARDDAV_NAMESPACE};
+use crate::types::propfind_cmd::PropfindRequest;
+use crate::types::{Dav2xx, DecodeError, Error, FieldError, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    /// Find the calendar home of the user
+    ///
+    /// Follows `/.well-known/caldav` on the server of the host, falling back to the host itself,
+    /// then asks for the `current-user-principal` and its `calendar-home-set`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn discover_caldav(&self) -> Result<DavHome> {
+        self.discover("caldav", CALDAV_NAMESPACE, "calendar-home-set")
+            .await
+    }
+
+    /// Find the address book home of the user
+    ///
+    /// Works like `discover_caldav` with `/.well-known/carddav` and `addressbook-home-set`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn discover_carddav(&self) -> Result<DavHome> {
+        self.discover("carddav", CARDDAV_NAMESPACE, "addressbook-home-set")
+            .await
+    }
+
+    async fn discover(&self, service: &str, namespace: &str, home_set: &str) -> Result<DavHome> {
+        let context = self.service_context(service).await?;
+        let principal = self
+            .property_urls(&context, "DAV:", "current-user-principal")
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| not_found("current-user-principal"))?;
+        let home_sets = self.property_urls(&principal, namespace, home_set).await?;
+        if home_sets.is_empty() {
+            return Err(not_found(home_set));
+        }
+        Ok(DavHome {
+            context_url: context.to_string(),
+            principal_url: principal.to_string(),
+            home_set_urls: home_sets.iter().map(|url| url.to_string()).collect(),
+        })
+    }
+
+    /// The context path the well-known URI of `service` leads to, or the host.
+    async fn service_context(&self, service: &str) -> Result<Url> {
+        let host = Url::parse(&self.host)?;
+        let origin = self.with_host(host.origin().ascii_serialization());
+        let builder = origin
+            .start_request(Method::GET, &format!("/.well-known/{}", service))
+            .await?;
+        let response = origin.execute(builder).await?;
+        if response.status().is_redirection() {
+            if let Some(location) = response.headers().get(LOCATION) {
+                return Ok(response.url().join(location.to_str()?)?);
+            }
+        }
+        if response.status().is_success() && response.url().path() != "/" {
+            return Ok(response.url().clone());
+        }
+        Ok(host)
+    }
+
+    /// A client at the origin of `url`, and the path to give it for `url`.
+    pub(crate) fn at_url(&self, url: &Url) -> (Client, String) {
+        let mut client = self.with_host(url.origin().ascii_serialization());
+        // the url holds a path returned by the server, already encoded
+        client.raw_paths = true;
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_owned(),
+        };
+        (client, path)
+    }
+
+    /// The hrefs of a property of the resource at `url`, resolved against it.
+    pub(crate) async fn property_urls(
+        &self,
+        url: &Url,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Vec<Url>> {
+        let (client, path) = self.at_url(url);
+        let request = PropfindRequest::props([(namespace, name)]);
+        let response = client
+            .list_with_raw(&path, Depth::Number(0), &request)
+            .await?
+            .dav2xx()
+            .await?;
+        let base = response.url().clone();
+        property_hrefs(&response.text().await?, namespace, name)?
+            .iter()
+            .map(|href| Ok(base.join(href)?))
+            .collect()
+    }
+}
+
+fn not_found(field: &str) -> Error {
+    Error::Decode(DecodeError::FieldNotFound(FieldError {
+        field: field.to_owned(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ClientBuilder;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn property(href: &str, prop: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response></d:multistatus>"#,
+            href, prop
+        )
+    }
+
+    #[tokio::test]
+    async fn caldav_home_is_discovered_through_well_known() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/.well-known/caldav"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/remote.php/dav/"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/remote.php/dav/"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/remote.php/dav/"))
+            .and(body_string_contains("current-user-principal"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(property(
+                "/remote.php/dav/",
+                "<d:current-user-principal><d:href>/remote.php/dav/principals/users/alice/</d:href></d:current-user-principal>",
+            )))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/remote.php/dav/principals/users/alice/"))
+            .and(body_string_contains("calendar-home-set"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(property(
+                "/remote.php/dav/principals/users/alice/",
+                "<c:calendar-home-set><d:href>/remote.php/dav/calendars/alice/</d:href></c:calendar-home-set>",
+            )))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/files/alice", mock_server.uri()))
+            .build()
+            .unwrap();
+        let home = client.discover_caldav().await.unwrap();
+        assert_eq!(
+            home.context_url,
+            format!("{}/remote.php/dav/", mock_server.uri())
+        );
+        assert_eq!(
+            home.principal_url,
+            format!(
+                "{}/remote.php/dav/principals/users/alice/",
+                mock_server.uri()
+            )
+        );
+        assert_eq!(
+            home.home_set_urls,
+            vec![format!(
+                "{}/remote.php/dav/calendars/alice/",
+                mock_server.uri()
+            )]
+        );
+    }
+}
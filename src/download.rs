@@ -0,0 +1,435 @@
+//! Streamed, ranged and resumable downloads.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::SeekFrom;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+#[cfg(not(target_arch = "wasm32"))]
+use futures_util::future::try_join_all;
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use reqwest::header::RANGE;
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE};
+use reqwest::{Method, Response, StatusCode};
+use tokio::io::AsyncRead;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio_util::io::StreamReader;
+
+use crate::types::download_cmd::{ByteRange, DownloadProgress};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::etag::ETag;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::list_cmd::ListEntity;
+use crate::types::{Dav2xx, DecodeError, Error, Result, StatusMismatchedError};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::{FieldError, PreconditionFailedError, RequestInfo};
+use crate::Client;
+
+impl Client {
+    /// Get a file from Webdav server as a stream of chunks
+    ///
+    /// Unlike `get` the body is never buffered and the GET cache is not used.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_stream(&self, path: &str) -> Result<impl Stream<Item = Result<Bytes>>> {
+        self.get_stream_with_progress(path, |_| {}).await
+    }
+
+    /// Get a file as a stream of chunks, calling `progress` after each one
+    ///
+    /// Use absolute path to the webdav server file location
+    pub async fn get_stream_with_progress<F>(
+        &self,
+        path: &str,
+        mut progress: F,
+    ) -> Result<impl Stream<Item = Result<Bytes>>>
+    where
+        F: FnMut(DownloadProgress),
+    {
+        let response = self.get_raw(path).await?.dav2xx().await?;
+        let total = response.content_length();
+        let mut downloaded = 0;
+        Ok(response.bytes_stream().map(move |chunk| {
+            let chunk = chunk?;
+            downloaded += chunk.len() as u64;
+            progress(DownloadProgress { downloaded, total });
+            Ok(chunk)
+        }))
+    }
+
+    /// Get a file from Webdav server as an `AsyncRead`
+    ///
+    /// Errors of the underlying request surface as `std::io::Error` wrapping the crate `Error`.
+    ///
+    /// Use absolute path to the webdav server file location
+    pub async fn get_reader(&self, path: &str) -> Result<impl AsyncRead> {
+        let stream = self
+            .get_stream(path)
+            .await?
+            .map_err(|error: Error| match error {
+                Error::Io(error) => error,
+                error => std::io::Error::other(error),
+            });
+        Ok(StreamReader::new(Box::pin(stream)))
+    }
+
+    /// Get the bytes of a file within `range`
+    ///
+    /// Fails unless the server answers with 206 Partial Content, servers ignoring the `Range`
+    /// header would send the whole file instead.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_range(&self, path: &str, range: ByteRange) -> Result<Response> {
+        let builder = self
+            .start_request(Method::GET, path)
+            .await?
+            .header(RANGE, range.to_header());
+        let response = self.execute(builder).await?.dav2xx().await?;
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(Error::Decode(DecodeError::StatusMismatched(
+                StatusMismatchedError::new(&response, 206),
+            )));
+        }
+        Ok(response)
+    }
+
+    /// Download a file to `local_path`, resuming a previous partial download
+    ///
+    /// The tag of the file is kept next to the partial download in `<local_path>.etag`. The
+    /// download resumes from the end of the local file if the remote file still has that tag,
+    /// and starts over otherwise. Returns the size of the downloaded file.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn download_resumable(&self, path: &str, local_path: &Path) -> Result<u64> {
+        let tag_path = {
+            let mut name = local_path.as_os_str().to_owned();
+            name.push(".etag");
+            PathBuf::from(name)
+        };
+        let offset = match tokio::fs::metadata(local_path).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+        let stored_tag = tokio::fs::read_to_string(&tag_path).await.ok();
+        let mut response = match stored_tag.as_deref().filter(|_| offset > 0) {
+            Some(tag) => {
+                let builder = self
+                    .start_request(Method::GET, path)
+                    .await?
+                    .header(RANGE, ByteRange::From(offset).to_header())
+                    .header(IF_RANGE, tag);
+                self.execute(builder).await?
+            }
+            None => self.get_raw(path).await?,
+        };
+        if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            // nothing left to get if the local file has the full size
+            let complete = response
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("bytes */"))
+                .and_then(|size| size.parse::<u64>().ok())
+                == Some(offset);
+            if complete {
+                let _ = tokio::fs::remove_file(&tag_path).await;
+                return Ok(offset);
+            }
+            response = self.get_raw(path).await?;
+        }
+        let response = response.dav2xx().await?;
+
+        let (mut file, mut size) = if response.status() == StatusCode::PARTIAL_CONTENT {
+            let file = tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(local_path)
+                .await?;
+            (file, offset)
+        } else {
+            match response
+                .headers()
+                .get(ETAG)
+                .and_then(|tag| tag.to_str().ok())
+            {
+                Some(tag) => tokio::fs::write(&tag_path, tag).await?,
+                None => {
+                    let _ = tokio::fs::remove_file(&tag_path).await;
+                }
+            }
+            (tokio::fs::File::create(local_path).await?, 0)
+        };
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk).await?;
+            size += chunk.len() as u64;
+        }
+        file.flush().await?;
+        let _ = tokio::fs::remove_file(&tag_path).await;
+        Ok(size)
+    }
+
+    /// Download a file to `local_path` with `segments` concurrent `Range` requests
+    ///
+    /// The size and tag of the file are read with a depth 0 PROPFIND, then every segment is
+    /// written at its offset as it arrives. Fails unless the server answers with 206 Partial
+    /// Content, and with `Error::PreconditionFailed` when the file changes during the download.
+    /// The local file is removed on failure. Returns the size of the downloaded file.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_parallel(
+        &self,
+        path: &str,
+        local_path: &Path,
+        segments: usize,
+    ) -> Result<u64> {
+        let client = self.operation();
+        let file = match client.metadata(path).await? {
+            ListEntity::File(file) => file,
+            ListEntity::Folder(_) => {
+                return Err(Error::Decode(DecodeError::FieldNotFound(FieldError {
+                    field: "getcontentlength".to_owned(),
+                })))
+            }
+        };
+        let size = file.content_length.max(0) as u64;
+        tokio::fs::File::create(local_path)
+            .await?
+            .set_len(size)
+            .await?;
+        let segment_len = size.div_ceil(segments.max(1) as u64).max(1);
+        let downloads = (0..size).step_by(segment_len as usize).map(|start| {
+            let end = (start + segment_len).min(size) - 1;
+            client.download_segment(path, local_path, start..=end, size, file.tag.as_deref())
+        });
+        match client.until_cancelled(try_join_all(downloads)).await {
+            Ok(_) => Ok(size),
+            Err(error) => {
+                let _ = tokio::fs::remove_file(local_path).await;
+                Err(error)
+            }
+        }
+    }
+
+    /// Write the bytes of `range` into `local_path`, checking they belong to the file of `size`
+    /// bytes and tag `etag`.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn download_segment(
+        &self,
+        path: &str,
+        local_path: &Path,
+        range: std::ops::RangeInclusive<u64>,
+        size: u64,
+        etag: Option<&str>,
+    ) -> Result<()> {
+        let (start, end) = (*range.start(), *range.end());
+        let response = self.range_response(path, range.into(), etag).await?;
+        let total = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit_once('/'))
+            .and_then(|(_, total)| total.parse::<u64>().ok());
+        let tag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|tag| tag.to_str().ok())
+            .map(ETag::parse);
+        let tag_changed = match (tag, etag) {
+            (Some(tag), Some(etag)) => !tag.weak_eq(&ETag::parse(etag)),
+            _ => false,
+        };
+        if total != Some(size) || tag_changed {
+            return Err(Error::PreconditionFailed(PreconditionFailedError {
+                path: path.to_owned(),
+                request_id: crate::types::request_id(&response),
+                request: Some(RequestInfo::new(&response)),
+            }));
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(local_path)
+            .await?;
+        file.seek(SeekFrom::Start(start)).await?;
+        let mut written = 0;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        file.flush().await?;
+        if written != end - start + 1 {
+            return Err(Error::Io(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures_util::TryStreamExt;
+    use tokio::io::AsyncReadExt;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::download_cmd::{ByteRange, DownloadProgress};
+    use crate::types::Error;
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn streamed_download_reports_progress() {
+        let mock_server = MockServer::start().await;
+        let body = vec![7u8; 100_000];
+        Mock::given(method("GET"))
+            .and(path("/dav/big.bin"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(body.clone()))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let reports = Arc::new(Mutex::new(vec![]));
+        let sink = reports.clone();
+        let chunks: Vec<_> = client
+            .get_stream_with_progress("/big.bin", move |progress| {
+                sink.lock().unwrap().push(progress)
+            })
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), body);
+        assert_eq!(
+            reports.lock().unwrap().last(),
+            Some(&DownloadProgress {
+                downloaded: 100_000,
+                total: Some(100_000)
+            })
+        );
+
+        let mut read = vec![];
+        let mut reader = Box::pin(client.get_reader("/big.bin").await.unwrap());
+        reader.read_to_end(&mut read).await.unwrap();
+        assert_eq!(read, body);
+    }
+
+    #[tokio::test]
+    async fn resumable_download_appends_missing_bytes() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dav/a.txt"))
+            .and(header("range", "bytes=3-"))
+            .and(header("if-range", "\"v1\""))
+            .respond_with(ResponseTemplate::new(206).set_body_string("lo"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let local = std::env::temp_dir().join(format!("reqwest_dav-{}", uuid::Uuid::new_v4()));
+        let tag = local.with_extension("etag");
+        std::fs::write(&local, "hel").unwrap();
+        std::fs::write(&tag, "\"v1\"").unwrap();
+
+        let size = client.download_resumable("/a.txt", &local).await.unwrap();
+        assert_eq!(size, 5);
+        assert_eq!(std::fs::read_to_string(&local).unwrap(), "hello");
+        assert!(!tag.exists());
+        std::fs::remove_file(local).unwrap();
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn range_ignored_by_server_is_an_error() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dav/a.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        assert!(client
+            .get_range("/a.txt", ByteRange::From(3))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn parallel_download_assembles_segments() {
+        let mock_server = MockServer::start().await;
+        for name in ["a.bin", "b.bin"] {
+            Mock::given(method("PROPFIND"))
+                .and(path(format!("/dav/{}", name)))
+                .respond_with(ResponseTemplate::new(207).set_body_string(format!(
+                    r#"<?xml version="1.0"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/{}</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:getcontentlength>10</D:getcontentlength><D:getetag>"v1"</D:getetag><D:resourcetype/></D:prop></D:propstat></D:response></D:multistatus>"#,
+                    name
+                )))
+                .mount(&mock_server)
+                .await;
+        }
+        for (range, body) in [("0-3", "abcd"), ("4-7", "efgh"), ("8-9", "ij")] {
+            Mock::given(method("GET"))
+                .and(path("/dav/a.bin"))
+                .and(header("range", format!("bytes={}", range).as_str()))
+                .and(header("if-match", "\"v1\""))
+                .respond_with(
+                    ResponseTemplate::new(206)
+                        .insert_header("content-range", format!("bytes {}/10", range).as_str())
+                        .insert_header("etag", "\"v1\"")
+                        .set_body_string(body),
+                )
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        // the file grew after the PROPFIND
+        Mock::given(method("GET"))
+            .and(path("/dav/b.bin"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-range", "bytes 0-4/12")
+                    .set_body_string("abcde"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let local = std::env::temp_dir().join(format!("reqwest_dav-{}", uuid::Uuid::new_v4()));
+        let size = client.get_parallel("/a.bin", &local, 3).await.unwrap();
+        assert_eq!(size, 10);
+        assert_eq!(std::fs::read_to_string(&local).unwrap(), "abcdefghij");
+        std::fs::remove_file(&local).unwrap();
+
+        let err = client.get_parallel("/b.bin", &local, 2).await.unwrap_err();
+        assert!(matches!(err, Error::PreconditionFailed(_)));
+        assert!(!local.exists());
+        mock_server.verify().await;
+    }
+}
@@ -0,0 +1,348 @@
+//! Client side emulation of COPY and MOVE for servers lacking them.
+
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Body, Method, Response};
+
+use crate::types::check_multi_status;
+use crate::types::copy_cmd::TransferMode;
+use crate::types::list_cmd::ListEntity;
+use crate::types::{Dav2xx, DecodeError, Error, FieldError, Result};
+use crate::Client;
+
+fn is_unsupported(code: u16) -> bool {
+    code == 405 || code == 501
+}
+
+/// MOVE failures which a copy followed by a delete may get around: unsupported verb,
+/// destination on another server (502) or across a quota boundary (507).
+fn is_move_recoverable(code: u16) -> bool {
+    is_unsupported(code) || code == 502 || code == 507
+}
+
+fn same_shape(source: &ListEntity, destination: &ListEntity) -> bool {
+    match (source, destination) {
+        (ListEntity::File(source), ListEntity::File(destination)) => {
+            source.content_length == destination.content_length
+        }
+        (ListEntity::Folder(_), ListEntity::Folder(_)) => true,
+        _ => false,
+    }
+}
+
+impl Client {
+    /// Whether the `Allow` header of an OPTIONS request lists `method`, `None` when unknown.
+    pub(crate) async fn allows_method(&self, path: &str, method: &str) -> Result<Option<bool>> {
+        let builder = self.start_request(Method::OPTIONS, path).await?;
+        let response = self.execute(builder).await?;
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+        Ok(response
+            .headers()
+            .get("allow")
+            .and_then(|allow| allow.to_str().ok())
+            .map(|allow| {
+                allow
+                    .split(',')
+                    .any(|allowed| allowed.trim().eq_ignore_ascii_case(method))
+            }))
+    }
+
+    /// Stream a file to another location through the client, keeping its content type.
+    pub(crate) async fn copy_through_client(&self, from: &str, to: &str) -> Result<()> {
+        let source = self.get(from).await?;
+        let content_type = source
+            .headers()
+            .get("content-type")
+            .cloned()
+            .unwrap_or(HeaderValue::from_static("application/octet-stream"));
+        #[cfg(not(target_arch = "wasm32"))]
+        let body = Body::wrap_stream(source.bytes_stream());
+        #[cfg(target_arch = "wasm32")]
+        let body = Body::from(source.bytes().await?);
+        self.put_with_content_type_raw(to, body, content_type)
+            .await?
+            .dav2xx()
+            .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn put_with_content_type_raw(
+        &self,
+        path: &str,
+        body: Body,
+        content_type: HeaderValue,
+    ) -> Result<Response> {
+        let builder = self
+            .start_request(Method::PUT, path)
+            .await?
+            .headers({
+                let mut map = HeaderMap::new();
+                map.insert("content-type", content_type);
+                map
+            })
+            .body(body);
+        self.execute(builder).await
+    }
+
+    /// Copy a file, falling back to GET + PUT when the server does not support COPY
+    ///
+    /// The fallback is used when OPTIONS does not list COPY in `Allow`, or when COPY is answered
+    /// with 405 or 501. It only works for files, collections are not walked and fail instead.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn cp_or_fallback(&self, from: &str, to: &str) -> Result<TransferMode> {
+        let client = self.operation();
+        if client.allows_method(from, "COPY").await? != Some(false) {
+            let response = client.cp_raw(from, to).await?;
+            if !is_unsupported(response.status().as_u16()) {
+                check_multi_status(response.dav2xx().await?).await?;
+                return Ok(TransferMode::Native);
+            }
+        }
+        // a GET of a collection returns an index page, which must not be written as a file
+        if let ListEntity::Folder(_) = client.metadata(from).await? {
+            return Err(Error::Decode(DecodeError::FieldNotSupported(FieldError {
+                field: "collections cannot be copied through the client".to_owned(),
+            })));
+        }
+        client.copy_through_client(from, to).await?;
+        Ok(TransferMode::GetPut)
+    }
+
+    /// Move a resource, falling back to copy + delete when the server cannot MOVE it
+    ///
+    /// The fallback is used when MOVE is answered with 405, 501, 502 or 507. The resource is
+    /// copied with `cp_or_fallback`, and the source is only deleted once the destination exists
+    /// with the same type and size.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn mv_or_fallback(&self, from: &str, to: &str) -> Result<TransferMode> {
+        let client = self.operation();
+        let response = client.mv_raw(from, to).await?;
+        if !is_move_recoverable(response.status().as_u16()) {
+            check_multi_status(response.dav2xx().await?).await?;
+            return Ok(TransferMode::Native);
+        }
+        let source = client.metadata(from).await?;
+        let mode = match client.cp_or_fallback(from, to).await? {
+            TransferMode::Native => TransferMode::CopyDelete,
+            _ => TransferMode::GetPutDelete,
+        };
+        let destination = client.metadata(to).await?;
+        if !same_shape(&source, &destination) {
+            return Err(Error::Decode(DecodeError::FieldNotSupported(FieldError {
+                field: "copied destination does not match source".to_owned(),
+            })));
+        }
+        client.delete(from).await?;
+        Ok(mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::copy_cmd::TransferMode;
+    use crate::types::{DecodeError, Error};
+    use crate::ClientBuilder;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn copy_falls_back_to_get_put_on_405() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("OPTIONS"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("COPY"))
+            .respond_with(ResponseTemplate::new(405))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/a.txt"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(file_status("/a.txt")))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/a.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("hello", "text/plain"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/b.txt"))
+            .and(header("content-type", "text/plain"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        let mode = client.cp_or_fallback("a.txt", "b.txt").await.unwrap();
+        assert_eq!(mode, TransferMode::GetPut);
+        mock_server.verify().await;
+    }
+
+    fn file_status(href: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+            <D:multistatus xmlns:D="DAV:">
+                <D:response>
+                    <D:href>{}</D:href>
+                    <D:propstat>
+                        <D:status>HTTP/1.1 200 OK</D:status>
+                        <D:prop>
+                            <D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified>
+                            <D:resourcetype/>
+                            <D:getcontentlength>5</D:getcontentlength>
+                        </D:prop>
+                    </D:propstat>
+                </D:response>
+            </D:multistatus>"#,
+            href
+        )
+    }
+
+    #[tokio::test]
+    async fn move_falls_back_to_copy_delete_on_502() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("MOVE"))
+            .respond_with(ResponseTemplate::new(502))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("OPTIONS"))
+            .respond_with(ResponseTemplate::new(200).append_header("allow", "GET, PUT, COPY"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("COPY"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        for file in ["/a.txt", "/b.txt"] {
+            Mock::given(method("PROPFIND"))
+                .and(path(file))
+                .respond_with(ResponseTemplate::new(207).set_body_string(file_status(file)))
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("DELETE"))
+            .and(path("/a.txt"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        let mode = client.mv_or_fallback("a.txt", "b.txt").await.unwrap();
+        assert_eq!(mode, TransferMode::CopyDelete);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn move_falls_back_to_get_put_delete_on_405() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("MOVE"))
+            .respond_with(ResponseTemplate::new(405))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("OPTIONS"))
+            .respond_with(ResponseTemplate::new(200).append_header("allow", "GET, PUT"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("COPY"))
+            .respond_with(ResponseTemplate::new(405))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        for file in ["/a.txt", "/b.txt"] {
+            Mock::given(method("PROPFIND"))
+                .and(path(file))
+                .respond_with(ResponseTemplate::new(207).set_body_string(file_status(file)))
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/a.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("hello", "text/plain"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/b.txt"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/a.txt"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        let mode = client.mv_or_fallback("a.txt", "b.txt").await.unwrap();
+        assert_eq!(mode, TransferMode::GetPutDelete);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn folders_are_not_copied_through_the_client() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("OPTIONS"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("COPY"))
+            .respond_with(ResponseTemplate::new(501))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                file_status("/docs/").replace(
+                    "<D:resourcetype/>",
+                    "<D:resourcetype><D:collection/></D:resourcetype>",
+                ),
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("<html/>", "text/html"))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.cp_or_fallback("docs", "copy").await,
+            Err(Error::Decode(DecodeError::FieldNotSupported(_)))
+        ));
+        mock_server.verify().await;
+    }
+}
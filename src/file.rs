@@ -0,0 +1,366 @@
+//! Remote file handles, read with `Range` requests and written with a single PUT.
+
+use std::io;
+use std::io::SeekFrom;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+use reqwest::header::{IF_MATCH, RANGE};
+use reqwest::{Method, Response, StatusCode};
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+use crate::conditional::precondition;
+use crate::types::download_cmd::ByteRange;
+use crate::types::etag::ETag;
+use crate::types::file_cmd::{DavFile, DavFileWriter, PendingRead, DEFAULT_READ_AHEAD};
+use crate::types::list_cmd::ListEntity;
+use crate::types::{DecodeError, Error, FieldError, Result, StatusMismatchedError};
+use crate::Client;
+
+impl Client {
+    /// Open a file for random access reads, see `DavFile`
+    ///
+    /// The size and tag of the file are read with a depth 0 PROPFIND. Reads fail with
+    /// `Error::PreconditionFailed` once the file has another strong tag.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn open(&self, path: &str) -> Result<DavFile> {
+        let file = match self.metadata(path).await? {
+            ListEntity::File(file) => file,
+            ListEntity::Folder(_) => {
+                return Err(Error::Decode(DecodeError::FieldNotFound(FieldError {
+                    field: "getcontentlength".to_owned(),
+                })))
+            }
+        };
+        Ok(DavFile {
+            client: self.operation(),
+            path: path.to_owned(),
+            len: file.content_length.max(0) as u64,
+            etag: file.tag,
+            position: 0,
+            buffer: Bytes::new(),
+            buffer_start: 0,
+            read_ahead: DEFAULT_READ_AHEAD,
+            pending: None,
+        })
+    }
+
+    /// Create or replace a file through an `AsyncWrite`, see `DavFileWriter`
+    ///
+    /// Nothing is sent before the writer is shut down.
+    ///
+    /// Use absolute path to the webdav server file location
+    pub fn create(&self, path: &str) -> DavFileWriter {
+        self.file_writer(path, vec![])
+    }
+
+    /// Append to a file through an `AsyncWrite`, see `DavFileWriter`
+    ///
+    /// WebDAV has no partial writes, the current content is read into memory and uploaded
+    /// again with the appended bytes. A missing file is created.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn append(&self, path: &str) -> Result<DavFileWriter> {
+        let content = match self.get(path).await {
+            Ok(response) => response.bytes().await?.to_vec(),
+            Err(e) if e.is_not_found() => vec![],
+            Err(e) => return Err(e),
+        };
+        Ok(self.file_writer(path, content))
+    }
+
+    fn file_writer(&self, path: &str, buffer: Vec<u8>) -> DavFileWriter {
+        DavFileWriter {
+            client: self.operation(),
+            path: path.to_owned(),
+            buffer,
+            #[cfg(feature = "nextcloud")]
+            chunk_size: None,
+            upload: None,
+            response: None,
+        }
+    }
+
+    /// Get the bytes of `range`, guarded by `etag` when it is a strong tag.
+    async fn read_range(&self, path: &str, range: ByteRange, etag: Option<&str>) -> Result<Bytes> {
+        Ok(self
+            .range_response(path, range, etag)
+            .await?
+            .bytes()
+            .await?)
+    }
+
+    /// Request `range`, guarded by `etag` when it is a strong tag, failing unless the answer is
+    /// 206 Partial Content.
+    pub(crate) async fn range_response(
+        &self,
+        path: &str,
+        range: ByteRange,
+        etag: Option<&str>,
+    ) -> Result<Response> {
+        let mut builder = self
+            .start_request(Method::GET, path)
+            .await?
+            .header(RANGE, range.to_header());
+        let etag = etag
+            .map(ETag::parse)
+            .filter(|etag| !etag.weak || self.quirks.weak_etags);
+        if let Some(etag) = etag {
+            builder = builder.header(IF_MATCH, etag.to_header(self.quirks.weak_etags));
+        }
+        let response = precondition(path, self.execute(builder).await?).await?;
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(Error::Decode(DecodeError::StatusMismatched(
+                StatusMismatchedError::new(&response, 206),
+            )));
+        }
+        Ok(response)
+    }
+}
+
+impl DavFile {
+    /// The buffered bytes from the current position on, if any.
+    fn buffered(&self) -> Option<&[u8]> {
+        let offset = self.position.checked_sub(self.buffer_start)?;
+        match usize::try_from(offset) {
+            Ok(offset) if offset < self.buffer.len() => Some(&self.buffer[offset..]),
+            _ => None,
+        }
+    }
+
+    /// Start the request for the bytes at the current position.
+    fn fetch(&self, wanted: usize) -> PendingRead {
+        let start = self.position;
+        let end = (start + wanted.max(self.read_ahead) as u64).min(self.len) - 1;
+        let client = self.client.clone();
+        let path = self.path.clone();
+        let etag = self.etag.clone();
+        PendingRead {
+            start,
+            future: Box::pin(async move {
+                client
+                    .read_range(&path, ByteRange::Bounded { start, end }, etag.as_deref())
+                    .await
+            }),
+        }
+    }
+}
+
+impl AsyncRead for DavFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.position >= this.len || buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            if let Some(buffered) = this.buffered() {
+                let count = buffered.len().min(buf.remaining());
+                buf.put_slice(&buffered[..count]);
+                this.position += count as u64;
+                return Poll::Ready(Ok(()));
+            }
+            // a read left behind by a seek is dropped, which aborts its request
+            if this.pending.as_ref().map(|pending| pending.start) != Some(this.position) {
+                this.pending = Some(this.fetch(buf.remaining()));
+            }
+            let pending = this.pending.as_mut().unwrap();
+            let result = ready!(pending.future.as_mut().poll(cx));
+            let start = pending.start;
+            this.pending = None;
+            let bytes = result.map_err(|error| match error {
+                Error::Io(error) => error,
+                error => io::Error::other(error),
+            })?;
+            if bytes.is_empty() {
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+            this.buffer = bytes;
+            this.buffer_start = start;
+        }
+    }
+}
+
+impl AsyncSeek for DavFile {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+        let target = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => this.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => this.position.checked_add_signed(offset),
+        };
+        match target {
+            Some(target) => {
+                this.position = target;
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+impl DavFileWriter {
+    /// Start uploading the buffered bytes.
+    fn start_upload(&mut self) {
+        let client = self.client.clone();
+        let path = self.path.clone();
+        let buffer = std::mem::take(&mut self.buffer);
+        #[cfg(feature = "nextcloud")]
+        if let Some(chunk_size) = self.chunk_size {
+            if buffer.len() > chunk_size && client.nextcloud_root().is_ok() {
+                let options = crate::types::nextcloud_cmd::ChunkOptions {
+                    chunk_size,
+                    upload_id: None,
+                };
+                self.upload = Some(Box::pin(async move {
+                    client
+                        .put_chunked(&path, io::Cursor::new(buffer), options)
+                        .await
+                }));
+                return;
+            }
+        }
+        self.upload = Some(Box::pin(async move { client.put(&path, buffer).await }));
+    }
+}
+
+impl AsyncWrite for DavFileWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.upload.is_some() || this.response.is_some() {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        this.buffer.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.response.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+        if this.upload.is_none() {
+            this.start_upload();
+        }
+        let result = ready!(this.upload.as_mut().unwrap().as_mut().poll(cx));
+        this.upload = None;
+        match result {
+            Ok(response) => {
+                this.response = Some(response);
+                Poll::Ready(Ok(()))
+            }
+            Err(Error::Io(error)) => Poll::Ready(Err(error)),
+            Err(error) => Poll::Ready(Err(io::Error::other(error))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::SeekFrom;
+
+    use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+    use wiremock::matchers::{body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn reads_and_seeks_with_range_requests() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/a.bin"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/a.bin</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:getcontentlength>10</D:getcontentlength><D:getetag>"v1"</D:getetag><D:resourcetype/></D:prop></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/a.bin"))
+            .and(header("range", "bytes=0-3"))
+            .and(header("if-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(206).set_body_string("abcd"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/a.bin"))
+            .and(header("range", "bytes=8-9"))
+            .respond_with(ResponseTemplate::new(206).set_body_string("ij"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let mut file = client.open("/a.bin").await.unwrap().with_read_ahead(4);
+        assert_eq!(file.len(), 10);
+
+        let mut read = [0; 3];
+        file.read_exact(&mut read).await.unwrap();
+        assert_eq!(&read, b"abc");
+        let mut read = [0; 1];
+        file.read_exact(&mut read).await.unwrap();
+        assert_eq!(&read, b"d");
+
+        assert_eq!(file.seek(SeekFrom::End(-2)).await.unwrap(), 8);
+        let mut rest = vec![];
+        file.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(rest, b"ij");
+        assert!(file.seek(SeekFrom::Current(-11)).await.is_err());
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn writer_uploads_on_shutdown() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dav/log.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("one\n"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/dav/log.txt"))
+            .and(body_string("one\ntwo\n"))
+            .respond_with(ResponseTemplate::new(204).insert_header("etag", "\"v2\""))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let mut writer = client.append("/log.txt").await.unwrap();
+        writer.write_all(b"two\n").await.unwrap();
+        writer.flush().await.unwrap();
+        writer.shutdown().await.unwrap();
+        assert_eq!(writer.response().unwrap().etag.as_deref(), Some("\"v2\""));
+        assert!(writer.write_all(b"three\n").await.is_err());
+        mock_server.verify().await;
+    }
+}
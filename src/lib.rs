@@ -1,27 +1,133 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Duration;
 
-use digest_auth::{WwwAuthenticateHeader};
-use reqwest::header::{HeaderMap, HeaderValue};
+#[cfg(feature = "digest")]
+use digest_auth::WwwAuthenticateHeader;
+use futures_util::StreamExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::{Body, Method, RequestBuilder, Response};
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 use url::Url;
+use web_time::Instant;
 
-use crate::types::list_cmd::{ListEntity, ListMultiStatus, ListResponse};
+#[cfg(feature = "checksum")]
+use crate::checksum::checksum_headers;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::cache_cmd::{CacheStore, GetCache, DEFAULT_MAX_CACHED_BODY};
+use crate::types::check_multi_status;
+use crate::types::copy_cmd::{CopyOptions, MoveOptions};
+use crate::types::hooks::{AuditAction, AuditEvent, Hooks, RequestSummary, ResponseSummary};
+use crate::types::list_cmd::{status_is_ok, ListCache, ListEntity, ListMultiStatus, ListResponse};
+use crate::types::lock_cmd::LockToken;
+use crate::types::propfind_cmd::PropfindRequest;
+use crate::types::put_cmd::{PutOptions, PutResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::record_cmd::Recorder;
+use crate::types::request_cmd::RequestOptions;
+use crate::types::retry_cmd::RetryPolicy;
+use crate::types::server_cmd::ServerQuirks;
+use crate::types::stats::{StatsCounters, TransferStats};
+use crate::types::timestamp::DavDateTime;
+#[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+use crate::types::tls_cmd::TlsSettings;
 pub use crate::types::*;
 
 pub mod types;
 
+mod acl;
+mod audit;
 mod authentication;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(not(target_arch = "wasm32"))]
+mod cache;
+mod caldav;
+mod carddav;
+#[cfg(feature = "checksum")]
+mod checksum;
+mod conditional;
+mod dav_client;
+#[cfg(not(target_arch = "wasm32"))]
+mod directory;
+mod discovery;
+mod download;
+mod fallback;
+#[cfg(not(target_arch = "wasm32"))]
+mod file;
+mod list_stream;
+mod lock;
+mod mkcol;
+#[cfg(feature = "nextcloud")]
+mod nextcloud;
+#[cfg(feature = "ocis")]
+mod ocis;
+mod pipeline;
+pub mod prelude;
+mod principals;
+mod proppatch;
+#[cfg(feature = "re-exports")]
 pub mod re_exports;
+#[cfg(not(target_arch = "wasm32"))]
+mod record;
+mod recursive;
+#[cfg(not(target_arch = "wasm32"))]
+mod redact;
+mod report;
+#[cfg(not(target_arch = "wasm32"))]
+mod retry;
+mod search;
+mod server;
+mod sync;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(not(target_arch = "wasm32"))]
+mod transfer;
+mod version;
+#[cfg(feature = "yandex")]
+mod yandex;
 
 #[derive(Debug, Clone)]
 pub struct Client {
     pub agent: reqwest::Client,
     pub host: String,
     pub auth: Auth,
+    #[cfg(feature = "digest")]
     pub digest_auth: Arc<Mutex<Option<WwwAuthenticateHeader>>>,
+    pub quirks: ServerQuirks,
+    /// Paths are sent as given instead of being percent-encoded, see `ClientBuilder::set_raw_paths`
+    pub raw_paths: bool,
+    /// Header carrying the id of the logical operation a request belongs to, if enabled.
+    pub request_id_header: Option<HeaderName>,
+    /// Id sent with every request, instead of one generated per operation.
+    pub request_id: Option<String>,
+    /// Records the traffic of the client, or answers it from a recording.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub recorder: Option<Recorder>,
+    /// Bodies revalidated by `get`, see `ClientBuilder::set_cache`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub get_cache: Option<GetCache>,
+    pub hooks: Hooks,
+    /// Recent depth 0 and 1 listings, see `ClientBuilder::set_list_cache_ttl`
+    pub list_cache: Option<ListCache>,
+    /// Requests still running after this long are reported, see `ClientBuilder::set_slow_request_threshold`
+    pub slow_request_threshold: Option<Duration>,
+    /// Failed requests sent again, see `ClientBuilder::set_retry_policy`
+    pub retry_policy: Option<RetryPolicy>,
+    /// Sent in the `If` header of writes, see `Client::with_lock_token`
+    pub lock_token: Option<LockToken>,
+    /// Requests fail with `Error::Cancelled` once this is cancelled.
+    pub cancellation: Option<CancellationToken>,
+    /// Requests fail with `Error::DeadlineExceeded` once this passes.
+    pub deadline: Option<Instant>,
+    #[cfg(feature = "fault-injection")]
+    pub fault_injector: Option<types::fault_cmd::FaultInjector>,
+    /// Shared by all clients derived from this one.
+    pub(crate) stats: Arc<StatsCounters>,
+    /// The scheme negotiated for `Auth::Auto`, shared by all clients derived from this one.
+    pub(crate) negotiated_auth: Arc<Mutex<Option<AuthScheme>>>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,35 +135,257 @@ pub struct ClientBuilder {
     agent: Option<reqwest::Client>,
     host: Option<String>,
     auth: Option<Auth>,
+    quirks: Option<ServerQuirks>,
+    raw_paths: bool,
+    request_id_header: Option<HeaderName>,
+    #[cfg(not(target_arch = "wasm32"))]
+    recorder: Option<Recorder>,
+    #[cfg(not(target_arch = "wasm32"))]
+    get_cache: Option<GetCache>,
+    #[cfg(not(target_arch = "wasm32"))]
+    cache_max_body_size: u64,
+    hooks: Hooks,
+    list_cache_ttl: Option<Duration>,
+    slow_request_threshold: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    #[cfg(feature = "fault-injection")]
+    fault_injector: Option<types::fault_cmd::FaultInjector>,
+    #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+    tls: TlsSettings,
 }
 
 impl Client {
+    /// Create a client from an already configured reqwest client
+    ///
+    /// Equivalent to a `ClientBuilder` with only the agent, host and auth set.
+    pub fn from_parts(agent: reqwest::Client, host: String, auth: Auth) -> Result<Self> {
+        ClientBuilder::new()
+            .set_agent(agent)
+            .set_host(host)
+            .set_auth(auth)
+            .build()
+    }
+
     /// Main function that creates the RequestBuilder, sets the method, url and the basic_auth
-    pub async fn start_request(&self, method: Method, path: &str) -> Result<RequestBuilder, Error> {
-        let url = Url::parse(&format!(
-            "{}/{}",
-            self.host.trim_end_matches("/"),
-            path.trim_start_matches("/")
-        ))?;
+    pub async fn start_request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        self.start_request_with(method, path, &RequestOptions::default())
+            .await
+    }
+
+    /// `start_request` with extra headers, query parameters and a timeout
+    pub async fn start_request_with(
+        &self,
+        method: Method,
+        path: &str,
+        options: &RequestOptions,
+    ) -> Result<RequestBuilder> {
+        let mut url = self.url_for(path)?;
+        options.apply_to_url(&mut url);
         let mut builder = self.agent.request(method.clone(), url.as_str());
+        if self.quirks.translate_f && (method == Method::GET || method == Method::PUT) {
+            builder = builder.header("translate", "f");
+        }
+        if let Some(header) = &self.request_id_header {
+            let id = match &self.request_id {
+                Some(id) => id.clone(),
+                None => uuid::Uuid::new_v4().to_string(),
+            };
+            builder = builder.header(header.clone(), id);
+        }
+        if let Some(token) = &self.lock_token {
+            if !matches!(
+                method.as_str(),
+                "GET" | "HEAD" | "OPTIONS" | "PROPFIND" | "REPORT" | "SEARCH" | "UNLOCK"
+            ) {
+                builder = builder.header("if", token.to_if_header());
+            }
+        }
+        builder = options.apply(builder);
         builder = self.apply_authentication(builder, &method, &url).await?;
         Ok(builder)
     }
 
-    pub async fn get_raw(&self, path: &str) -> Result<Response, Error> {
-        Ok(self.start_request(Method::GET, path).await?.send().await?)
+    /// Resolve a path against the host
+    ///
+    /// The segments of the path are percent-encoded unless `raw_paths` is set. A query string in
+    /// `path` (`file.txt?token=abc`) is kept as such and merged with any query string of the host.
+    pub(crate) fn url_for(&self, path: &str) -> Result<Url> {
+        self.url_for_with(path, |path| self.encode(path))
+    }
+
+    /// Percent-encode `path` as sent in requests.
+    fn encode(&self, path: &str) -> String {
+        match self.raw_paths {
+            true => path.to_owned(),
+            false => encode_segments(path, PATH_SEGMENT),
+        }
+    }
+
+    fn url_for_with(&self, path: &str, encode: impl Fn(&str) -> String) -> Result<Url> {
+        let mut url = Url::parse(&self.host)?;
+        let (path, query) = match path.split_once('?') {
+            Some((path, query)) => (encode(path), Some(query)),
+            None => (encode(path), None),
+        };
+        let joined = format!(
+            "{}/{}",
+            url.path().trim_end_matches("/"),
+            path.trim_start_matches("/")
+        );
+        url.set_path(&joined);
+        let query = match (url.query(), query) {
+            (Some(host_query), Some(query)) => Some(format!("{}&{}", host_query, query)),
+            (Some(host_query), None) => Some(host_query.to_owned()),
+            (None, query) => query.map(|query| query.to_owned()),
+        };
+        url.set_query(query.as_deref());
+        Ok(url)
+    }
+
+    /// The path of an href returned by the server, relative to the host and percent-decoded
+    ///
+    /// The result can be passed back to `get`, `list` and the other operations:
+    /// `/remote.php/dav/files/alice/My%20Docs/` becomes `/My Docs` for a client rooted at
+    /// `https://cloud.example.com/remote.php/dav/files/alice`.
+    ///
+    /// With `raw_paths` the path is left percent-encoded, as the client expects it then.
+    pub fn path_of(&self, href: &str) -> String {
+        let path = self.href_to_path(href);
+        if self.raw_paths {
+            return format!("/{}", path);
+        }
+        format!(
+            "/{}",
+            percent_encoding::percent_decode_str(&path).decode_utf8_lossy()
+        )
+    }
+
+    /// A client sharing the agent and authentication state of this one, rooted at `host`
+    pub fn with_host(&self, host: String) -> Client {
+        Client {
+            host,
+            ..self.clone()
+        }
+    }
+
+    /// A client rooted at `path` below the host of this one, sharing its agent and authentication
+    /// state
+    ///
+    /// `client.join("addressbooks/users/stefan")?` gives a client whose paths, and the paths
+    /// returned by `path_of`, are relative to that folder.
+    ///
+    /// Use absolute path to the webdav server folder location
+    pub fn join(&self, path: &str) -> Result<Client> {
+        Ok(self.with_host(self.url_for(path)?.to_string()))
+    }
+
+    /// A client sending `id` in the request id header with every request
+    ///
+    /// Use it to correlate a group of calls with the server logs. Has no effect unless the
+    /// header was enabled with `ClientBuilder::set_request_id_header`.
+    pub fn with_request_id(&self, id: String) -> Client {
+        Client {
+            request_id: Some(id),
+            ..self.clone()
+        }
+    }
+
+    /// A client whose requests stop once `token` is cancelled
+    ///
+    /// Requests not sent yet fail with `Error::Cancelled`, requests in flight are aborted.
+    /// Recursive helpers such as `list_cancellable` return what they got so far instead.
+    pub fn with_cancellation(&self, token: CancellationToken) -> Client {
+        Client {
+            cancellation: Some(token),
+            ..self.clone()
+        }
+    }
+
+    /// A client whose requests have to finish before `deadline`
+    ///
+    /// Each request gets the remaining time as timeout, requests started after the deadline
+    /// fail with `Error::DeadlineExceeded`. An earlier deadline of this client is kept.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_deadline(&self, deadline: Instant) -> Client {
+        Client {
+            deadline: Some(match self.deadline {
+                Some(current) => current.min(deadline),
+                None => deadline,
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// The client to issue the requests of one logical operation with, so they share one id.
+    pub(crate) fn operation(&self) -> Client {
+        match (&self.request_id_header, &self.request_id) {
+            (Some(_), None) => self.with_request_id(uuid::Uuid::new_v4().to_string()),
+            _ => self.clone(),
+        }
+    }
+
+    /// Counters of the requests made by this client and the clients derived from it
+    pub fn stats(&self) -> TransferStats {
+        self.stats.snapshot()
+    }
+
+    /// Start counting from zero again
+    pub fn reset_stats(&self) {
+        self.stats.reset()
+    }
+
+    pub async fn get_raw(&self, path: &str) -> Result<Response> {
+        let builder = self.start_request(Method::GET, path).await?;
+        self.execute(builder).await
+    }
+
+    pub async fn get_with_raw(&self, path: &str, options: &RequestOptions) -> Result<Response> {
+        let builder = self.start_request_with(Method::GET, path, options).await?;
+        self.execute(builder).await
     }
 
     /// Get a file from Webdav server
     ///
     /// Use absolute path to the webdav server file location
-    pub async fn get(&self, path: &str) -> Result<Response, Error> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get(&self, path: &str) -> Result<Response> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(cache) = &self.get_cache {
+            return self.get_cached(cache, path).await;
+        }
         self.get_raw(path).await?.dav2xx().await
     }
 
-    pub async fn put_raw<B: Into<Body>>(&self, path: &str, body: B) -> Result<Response, Error> {
-        Ok(self
-            .start_request(Method::PUT, path)
+    /// Get a file from Webdav server with extra headers, query parameters or a timeout
+    ///
+    /// Bypasses the cache set with `ClientBuilder::set_cache`.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_with(&self, path: &str, options: RequestOptions) -> Result<Response> {
+        self.get_with_raw(path, &options).await?.dav2xx().await
+    }
+
+    pub async fn put_raw<B: Into<Body>>(&self, path: &str, body: B) -> Result<Response> {
+        self.put_with_headers_raw(
+            path,
+            body.into(),
+            HeaderMap::new(),
+            &RequestOptions::default(),
+        )
+        .await
+    }
+
+    /// PUT with headers on top of the `Content-Type`, and those of `options` on top of them.
+    async fn put_with_headers_raw(
+        &self,
+        path: &str,
+        body: Body,
+        headers: HeaderMap,
+        options: &RequestOptions,
+    ) -> Result<Response> {
+        let builder = self
+            .start_request_with(Method::PUT, path, options)
             .await?
             .headers({
                 let mut map = HeaderMap::new();
@@ -65,11 +393,11 @@ impl Client {
                     "content-type",
                     HeaderValue::from_str("application/octet-stream")?,
                 );
+                map.extend(headers);
                 map
             })
-            .body(body)
-            .send()
-            .await?)
+            .body(body);
+        self.execute(builder).await
     }
 
     /// Upload a file/zip on Webdav server
@@ -78,101 +406,285 @@ impl Client {
     /// This can be achieved with **std::fs::File** or **zip-rs** for sending zip files.
     ///
     /// Use absolute path to the webdav server folder location
-    pub async fn put<B: Into<Body>>(&self, path: &str, body: B) -> Result<(), Error> {
-        self.put_raw(path, body).await?.dav2xx().await?;
-        Ok(())
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, body))
+    )]
+    pub async fn put<B: Into<Body>>(&self, path: &str, body: B) -> Result<PutResponse> {
+        let response = self.put_raw(path, body).await?.dav2xx().await?;
+        Ok(PutResponse::from_response(&response))
     }
 
-    pub async fn delete_raw(&self, path: &str) -> Result<Response, Error> {
-        Ok(self
-            .start_request(Method::DELETE, path)
+    /// Upload a file on Webdav server, see `PutOptions` for the settings
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, body))
+    )]
+    pub async fn put_with<B: Into<Body>>(
+        &self,
+        path: &str,
+        body: B,
+        options: PutOptions,
+    ) -> Result<PutResponse> {
+        if options.ensure_parent {
+            if let Some((parent, _)) = path.trim_matches('/').rsplit_once('/') {
+                self.mkcol_recursive(parent).await?;
+            }
+        }
+        let body = body.into();
+        let mut headers = HeaderMap::new();
+        #[cfg(feature = "checksum")]
+        if let Some(algorithm) = options.checksum {
+            headers.extend(checksum_headers(&body, algorithm)?);
+        }
+        if let Some(mtime) = &options.mtime {
+            headers.insert("x-oc-mtime", HeaderValue::from(mtime.epoch_seconds()));
+        }
+        let response = self
+            .put_with_headers_raw(path, body, headers, &options.request)
             .await?
-            .send()
-            .await?)
+            .dav2xx()
+            .await?;
+        let mut put_response = PutResponse::from_response(&response);
+        if let (Some(mtime), false) = (options.mtime, put_response.mtime_set) {
+            put_response.mtime_set = self.set_mtime(path, mtime).await?;
+        }
+        Ok(put_response)
+    }
+
+    pub async fn delete_raw(&self, path: &str) -> Result<Response> {
+        self.audited(AuditAction::Delete, path, None, async {
+            let builder = self.start_request(Method::DELETE, path).await?;
+            self.execute(builder).await
+        })
+        .await
     }
 
     /// Deletes the collection, file, folder or zip archive at the given path on Webdav server
     ///
     /// Use absolute path to the webdav server file location
-    pub async fn delete(&self, path: &str) -> Result<(), Error> {
-        self.delete_raw(path).await?.dav2xx().await?;
-        Ok(())
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn delete(&self, path: &str) -> Result<()> {
+        check_multi_status(self.delete_raw(path).await?.dav2xx().await?).await
+    }
+
+    /// Delete many resources, `concurrency` at a time
+    ///
+    /// A failing delete does not stop the others, the result of each path is returned in the
+    /// order the paths were given. Collections failing partially report the members the server
+    /// could not delete, see `delete`.
+    ///
+    /// Use absolute paths to the webdav server file locations
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, paths))
+    )]
+    pub async fn delete_many<P: Into<String>>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+        concurrency: usize,
+    ) -> Vec<(String, Result<()>)> {
+        let client = self.operation();
+        futures_util::stream::iter(paths.into_iter().map(Into::into))
+            .map(|path: String| {
+                let client = &client;
+                async move {
+                    let result = client.delete(&path).await;
+                    (path, result)
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
     }
 
-    pub async fn mkcol_raw(&self, path: &str) -> Result<Response, Error> {
-        Ok(self
+    pub async fn mkcol_raw(&self, path: &str) -> Result<Response> {
+        let builder = self
             .start_request(Method::from_bytes(b"MKCOL").unwrap(), path)
-            .await?
-            .send()
-            .await?)
+            .await?;
+        self.execute(builder).await
     }
 
     /// Creates a directory on Webdav server
     ///
     /// Use absolute path to the webdav server file location
-    pub async fn mkcol(&self, path: &str) -> Result<(), Error> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn mkcol(&self, path: &str) -> Result<()> {
         self.mkcol_raw(path).await?.dav2xx().await?;
         Ok(())
     }
 
-    pub async fn unzip_raw(&self, path: &str) -> Result<Response, Error> {
-        Ok(self
-            .start_request(Method::POST, path)
-            .await?
-            .form(&{
-                let mut params = HashMap::new();
-                params.insert("method", "UNZIP");
-                params
-            })
-            .send()
-            .await?)
+    /// Creates a directory and its missing parents on Webdav server, like `mkdir -p`
+    ///
+    /// Collections which already exist are left alone.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn mkcol_recursive(&self, path: &str) -> Result<()> {
+        let mut current = String::new();
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            current.push('/');
+            current.push_str(segment);
+            let response = self.mkcol_raw(&current).await?;
+            // 405 is the answer for an existing resource
+            if response.status() != reqwest::StatusCode::METHOD_NOT_ALLOWED {
+                response.dav2xx().await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn unzip_raw(&self, path: &str) -> Result<Response> {
+        let builder = self.start_request(Method::POST, path).await?.form(&{
+            let mut params = HashMap::new();
+            params.insert("method", "UNZIP");
+            params
+        });
+        self.execute(builder).await
     }
 
     /// Unzips the .zip archieve on Webdav server
     ///
     /// Use absolute path to the webdav server file location
-    pub async fn unzip(&self, path: &str) -> Result<(), Error> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn unzip(&self, path: &str) -> Result<()> {
         self.unzip_raw(path).await?.dav2xx().await?;
         Ok(())
     }
 
-    pub async fn mv_raw(&self, from: &str, to: &str) -> Result<Response, Error> {
-        let base = Url::parse(&self.host)?;
-        let mv_to = format!(
-            "{}/{}",
-            base.path().trim_end_matches("/"),
-            to.trim_start_matches("/")
-        );
-        Ok(self
-            .start_request(Method::from_bytes(b"MOVE")?, from)
+    /// The `Destination` header value for MOVE and COPY, an absolute URI as RFC 4918 expects.
+    fn destination(&self, to: &str) -> Result<String> {
+        let url = match self.quirks.encode_destination {
+            true => self.url_for_with(to, |path| encode_path(&self.encode(path)))?,
+            false => self.url_for(to)?,
+        };
+        Ok(match self.quirks.path_destination {
+            true => url.path().to_owned(),
+            false => url.to_string(),
+        })
+    }
+
+    pub async fn mv_raw(&self, from: &str, to: &str) -> Result<Response> {
+        self.mv_with_raw(from, to, &MoveOptions::default()).await
+    }
+
+    pub async fn mv_with_raw(
+        &self,
+        from: &str,
+        to: &str,
+        options: &MoveOptions,
+    ) -> Result<Response> {
+        self.audited(AuditAction::Move, from, Some(to), async {
+            let mv_to = self.destination(to)?;
+            let builder = self
+                .start_request(Method::from_bytes(b"MOVE")?, from)
+                .await?
+                .headers({
+                    let mut map = HeaderMap::new();
+                    map.insert("destination", HeaderValue::from_str(&mv_to)?);
+                    map.insert("overwrite", overwrite_header(options.overwrite));
+                    map
+                });
+            self.execute(builder).await
+        })
+        .await
+    }
+
+    /// Rename or move a collection, file, folder on Webdav server
+    ///
+    /// If the file location changes it will move the file, if only the file name changes it will rename it.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn mv(&self, from: &str, to: &str) -> Result<()> {
+        self.mv_with(from, to, MoveOptions::default()).await
+    }
+
+    /// Rename or move a collection, file, folder on Webdav server with the given options
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn mv_with(&self, from: &str, to: &str, options: MoveOptions) -> Result<()> {
+        check_multi_status(self.mv_with_raw(from, to, &options).await?.dav2xx().await?).await
+    }
+
+    pub async fn cp_raw(&self, from: &str, to: &str) -> Result<Response> {
+        self.cp_with_raw(from, to, &CopyOptions::default()).await
+    }
+
+    pub async fn cp_with_raw(
+        &self,
+        from: &str,
+        to: &str,
+        options: &CopyOptions,
+    ) -> Result<Response> {
+        let cp_to = self.destination(to)?;
+        let builder = self
+            .start_request(Method::from_bytes(b"COPY")?, from)
             .await?
             .headers({
                 let mut map = HeaderMap::new();
-                map.insert("destination", HeaderValue::from_str(&mv_to)?);
+                map.insert("destination", HeaderValue::from_str(&cp_to)?);
+                map.insert("overwrite", overwrite_header(options.overwrite));
+                map.insert(
+                    "depth",
+                    HeaderValue::from_str(&match options.depth {
+                        Depth::Number(value) => format!("{}", value),
+                        Depth::Infinity => "infinity".to_owned(),
+                    })?,
+                );
                 map
-            })
-            .send()
-            .await?)
+            });
+        self.execute(builder).await
     }
 
-    /// Rename or move a collection, file, folder on Webdav server
+    /// Copy a collection, file, folder on Webdav server
     ///
-    /// If the file location changes it will move the file, if only the file name changes it will rename it.
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn cp(&self, from: &str, to: &str) -> Result<()> {
+        self.cp_with(from, to, CopyOptions::default()).await
+    }
+
+    /// Copy a collection, file, folder on Webdav server with the given options
     ///
     /// Use absolute path to the webdav server file location
-    pub async fn mv(&self, from: &str, to: &str) -> Result<(), Error> {
-        self.mv_raw(from, to).await?.dav2xx().await?;
-        Ok(())
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn cp_with(&self, from: &str, to: &str, options: CopyOptions) -> Result<()> {
+        check_multi_status(self.cp_with_raw(from, to, &options).await?.dav2xx().await?).await
+    }
+
+    pub async fn list_raw(&self, path: &str, depth: Depth) -> Result<Response> {
+        self.list_with_raw(path, depth, &PropfindRequest::AllProp)
+            .await
+    }
+
+    pub async fn list_with_raw(
+        &self,
+        path: &str,
+        depth: Depth,
+        request: &PropfindRequest,
+    ) -> Result<Response> {
+        self.propfind_with_raw(path, depth, request, &RequestOptions::default())
+            .await
     }
 
-    pub async fn list_raw(&self, path: &str, depth: Depth) -> Result<Response, Error> {
-        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
-            <D:propfind xmlns:D="DAV:">
-                <D:allprop/>
-            </D:propfind>
-        "#;
-        Ok(self
-            .start_request(Method::from_bytes(b"PROPFIND").unwrap(), path)
+    async fn propfind_with_raw(
+        &self,
+        path: &str,
+        depth: Depth,
+        request: &PropfindRequest,
+        options: &RequestOptions,
+    ) -> Result<Response> {
+        // RFC 4918 treats a PROPFIND without a body as allprop.
+        let body = match request {
+            PropfindRequest::AllProp if self.quirks.empty_propfind_body => String::new(),
+            request => request.to_xml(),
+        };
+        let builder = self
+            .start_request_with(Method::from_bytes(b"PROPFIND").unwrap(), path, options)
             .await?
             .headers({
                 let mut map = HeaderMap::new();
@@ -185,44 +697,234 @@ impl Client {
                 );
                 map
             })
-            .body(body)
-            .send()
-            .await?)
+            .body(body);
+        self.execute(builder).await
+    }
+
+    pub async fn list_rsp(&self, path: &str, depth: Depth) -> Result<Vec<ListResponse>> {
+        self.list_with(path, depth, PropfindRequest::AllProp).await
     }
 
-    pub async fn list_rsp(&self, path: &str, depth: Depth) -> Result<Vec<ListResponse>, Error> {
-        let reqwest_response = self.list_raw(path, depth).await?;
+    /// List the resources at the given path with the properties selected by `request`
+    ///
+    /// The responses are returned as parsed, properties the server did not return are `None`.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list_with(
+        &self,
+        path: &str,
+        depth: Depth,
+        request: PropfindRequest,
+    ) -> Result<Vec<ListResponse>> {
+        let reqwest_response = self.list_with_raw(path, depth, &request).await?;
+        self.list_responses(reqwest_response).await
+    }
+
+    /// Parse the multistatus of a PROPFIND response.
+    pub(crate) async fn list_responses(
+        &self,
+        reqwest_response: Response,
+    ) -> Result<Vec<ListResponse>> {
         let code = reqwest_response.status();
         if code.is_success() {
             let response = reqwest_response.text().await?;
-            let result: Result<ListMultiStatus, serde_xml_rs::Error> =
-                serde_xml_rs::from_str(&response);
-            match result {
+            match self.parse_multi_status(&response) {
                 Ok(mul) => Ok(mul.responses),
                 Err(e) => {
-                    println!("Error: {}", e);
-                    Err(e.into())
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(error = %e, "failed to parse multistatus response");
+                    Err(e)
                 }
             }
         } else {
             Err(Error::Decode(DecodeError::StatusMismatched(
-                StatusMismatchedError {
-                    response_code: code.as_u16(),
-                    expected_code: 207,
-                },
+                StatusMismatchedError::new(&reqwest_response, 207),
             )))
         }
     }
 
+    /// Parse a multistatus body, leniently when the server needs it.
+    pub(crate) fn parse_multi_status(&self, xml: &str) -> Result<ListMultiStatus> {
+        match self.quirks.lenient_parsing {
+            true => ListMultiStatus::parse_lenient(xml),
+            false => ListMultiStatus::parse(xml),
+        }
+    }
+
+    /// Get the given `(namespace, name)` properties of a resource as text
+    ///
+    /// Properties the server did not return are missing from the map.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn propfind_raw_props(
+        &self,
+        path: &str,
+        props: &[(&str, &str)],
+    ) -> Result<HashMap<(String, String), String>> {
+        let request = PropfindRequest::props(props.iter().copied());
+        let response = self
+            .list_with_raw(path, Depth::Number(0), &request)
+            .await?
+            .dav2xx()
+            .await?;
+        let multi_status = ListMultiStatus::parse_raw(&response.text().await?)?;
+        let mut properties = HashMap::new();
+        for response in multi_status.responses {
+            for prop_stat in response.prop_stat {
+                if prop_stat.status.is_empty() || status_is_ok(&prop_stat.status) {
+                    properties.extend(prop_stat.prop.other);
+                }
+            }
+        }
+        Ok(properties)
+    }
+
     /// List files and folders at the given path on Webdav server
     ///
     /// Depth of "0" applies only to the resource, "1" to the resource and it's children, "infinity" to the resource and all it's children recursively
     /// The result will contain an xml list with the remote folder contents.
     ///
     /// Use absolute path to the webdav server folder location
-    pub async fn list(&self, path: &str, depth: Depth) -> Result<Vec<ListEntity>, Error> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list(&self, path: &str, depth: Depth) -> Result<Vec<ListEntity>> {
+        if matches!(depth, Depth::Infinity) {
+            return self.list_recursive(path).await;
+        }
+        let cached = match (&self.list_cache, &depth) {
+            (Some(cache), Depth::Number(depth @ 0..=1)) => {
+                let mut url = self.url_for(path)?;
+                url.set_query(None);
+                Some((cache, url.to_string(), *depth))
+            }
+            _ => None,
+        };
+        if let Some((cache, url, depth)) = &cached {
+            if let Some(entities) = cache.get(url, *depth) {
+                return Ok(entities);
+            }
+        }
         let responses = self.list_rsp(path, depth).await?;
-        responses.into_iter().map(ListEntity::try_from).collect()
+        let entities: Vec<ListEntity> = responses
+            .into_iter()
+            .map(ListEntity::try_from)
+            .collect::<Result<_>>()?;
+        if let Some((cache, url, depth)) = cached {
+            cache.insert(&url, depth, entities.clone());
+        }
+        Ok(entities)
+    }
+
+    /// List files and folders at the given path with extra headers, query parameters or a timeout
+    ///
+    /// Unlike `list`, infinite depth is sent to the server as such and the listing cache is
+    /// bypassed.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list_with_opts(
+        &self,
+        path: &str,
+        depth: Depth,
+        options: RequestOptions,
+    ) -> Result<Vec<ListEntity>> {
+        let response = self
+            .propfind_with_raw(path, depth, &PropfindRequest::AllProp, &options)
+            .await?;
+        self.list_responses(response)
+            .await?
+            .into_iter()
+            .map(ListEntity::try_from)
+            .collect()
+    }
+
+    /// Get the file or folder at the given path, with a depth 0 PROPFIND
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn metadata(&self, path: &str) -> Result<ListEntity> {
+        self.list(path, Depth::Number(0))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(Error::Decode(DecodeError::FieldNotFound(FieldError {
+                field: "response".to_owned(),
+            })))
+    }
+
+    /// Whether a file or folder exists at the given path
+    ///
+    /// Asks with HEAD, and with a depth 0 PROPFIND when the server does not answer HEAD.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn exists(&self, path: &str) -> Result<bool> {
+        let builder = self.start_request(Method::HEAD, path).await?;
+        let mut response = self.execute(builder).await?;
+        if matches!(response.status().as_u16(), 405 | 501) {
+            response = self.list_raw(path, Depth::Number(0)).await?;
+        }
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        response.dav2xx().await?;
+        Ok(true)
+    }
+
+    /// Forget all listings cached by `ClientBuilder::set_list_cache_ttl`
+    pub fn clear_list_cache(&self) {
+        if let Some(cache) = &self.list_cache {
+            cache.clear();
+        }
+    }
+}
+
+fn overwrite_header(overwrite: bool) -> HeaderValue {
+    HeaderValue::from_static(if overwrite { "T" } else { "F" })
+}
+
+/// Characters escaped in the path segments of requests, non-ASCII ones are always escaped.
+const PATH_SEGMENT: &percent_encoding::AsciiSet = &percent_encoding::CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Percent-encode every segment of `path` as a literal name.
+fn encode_segments(path: &str, set: &'static percent_encoding::AsciiSet) -> String {
+    path.split('/')
+        .map(|segment| percent_encoding::utf8_percent_encode(segment, set).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Percent-encode every reserved character of every segment of `path`, leaving existing escapes
+/// intact.
+pub(crate) fn encode_path(path: &str) -> String {
+    const SEGMENT: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+        .remove(b'-')
+        .remove(b'.')
+        .remove(b'_')
+        .remove(b'~');
+    path.split('/')
+        .map(|segment| {
+            let decoded = percent_encoding::percent_decode_str(segment).decode_utf8_lossy();
+            percent_encoding::utf8_percent_encode(&decoded, SEGMENT).to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -232,6 +934,23 @@ impl ClientBuilder {
             agent: None,
             host: None,
             auth: None,
+            quirks: None,
+            raw_paths: false,
+            request_id_header: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            recorder: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            get_cache: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            cache_max_body_size: DEFAULT_MAX_CACHED_BODY,
+            hooks: Hooks::default(),
+            list_cache_ttl: None,
+            slow_request_threshold: None,
+            retry_policy: None,
+            #[cfg(feature = "fault-injection")]
+            fault_injector: None,
+            #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+            tls: TlsSettings::default(),
         }
     }
 
@@ -250,24 +969,917 @@ impl ClientBuilder {
         self
     }
 
-    pub fn build(self) -> Result<Client, Error> {
+    /// Enable workarounds for a non-conforming server, see `ServerQuirks::for_server`
+    pub fn set_quirks(mut self, quirks: ServerQuirks) -> Self {
+        self.quirks = Some(quirks);
+        self
+    }
+
+    /// Keep listing when a property is malformed, see `ServerQuirks::lenient_parsing`
+    ///
+    /// Unparseable dates are recorded as `None` in `ListProp` instead of failing the whole call,
+    /// entities left without a date are listed as modified at the Unix epoch. Applies on top of
+    /// the quirks given to `set_quirks` before, a later `set_quirks` replaces it.
+    pub fn set_lenient_parsing(mut self, lenient: bool) -> Self {
+        self.quirks.get_or_insert_default().lenient_parsing = lenient;
+        self
+    }
+
+    /// Send paths as given, for callers which percent-encode them already
+    ///
+    /// By default every path segment is percent-encoded, so `a b%.txt` is requested as
+    /// `a%20b%25.txt`.
+    pub fn set_raw_paths(mut self, raw_paths: bool) -> Self {
+        self.raw_paths = raw_paths;
+        self
+    }
+
+    /// Send an id with every request in the given header, e.g. `X-Request-Id`
+    ///
+    /// A UUID is generated per operation, requests made by helpers issuing several requests
+    /// share the same id. The id is also reported in errors and traces.
+    pub fn set_request_id_header(mut self, header: HeaderName) -> Self {
+        self.request_id_header = Some(header);
+        self
+    }
+
+    /// Record the traffic of the client to fixtures, or replay it offline, see `Recorder`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Answer depth 0 and 1 listings from the results of the last `ttl`
+    ///
+    /// Writes made through the client, or any client derived from it, invalidate the listings of
+    /// the written resource, its parent and its descendants.
+    pub fn set_list_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.list_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Cache the bodies returned by `get`, revalidating them with `If-None-Match`
+    ///
+    /// Only responses with an `ETag` are cached. Cached GETs are read into memory instead
+    /// of being streamed, up to the size set with `set_cache_max_body_size`. See `MemoryStore`
+    /// and `DirectoryStore`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_cache<S: CacheStore + 'static>(mut self, store: S) -> Self {
+        self.get_cache = Some(GetCache {
+            store: Arc::new(store),
+            max_body_size: DEFAULT_MAX_CACHED_BODY,
+        });
+        self
+    }
+
+    /// Stream bodies larger than `size` bytes past the cache, 8 MiB by default
+    ///
+    /// Bodies of unknown length are never cached.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_cache_max_body_size(mut self, size: u64) -> Self {
+        self.cache_max_body_size = size;
+        self
+    }
+
+    /// Call `hook` before every request is sent
+    pub fn on_request<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&RequestSummary) + Send + Sync + 'static,
+    {
+        self.hooks.on_request = Some(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` once every request finished, including failed ones
+    pub fn on_response<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&ResponseSummary) + Send + Sync + 'static,
+    {
+        self.hooks.on_response = Some(Arc::new(hook));
+        self
+    }
+
+    /// Report requests which have not finished after `threshold`
+    ///
+    /// The request is passed to the `on_slow_request` hook and, with the `tracing` feature,
+    /// logged as a warning. The request itself keeps running.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// Send requests failing with a network error or a 429, 502, 503 or 504 again
+    ///
+    /// See `RetryPolicy` for the requests which are retried.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Call `hook` when a request exceeds the slow request threshold
+    pub fn on_slow_request<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&RequestSummary, Duration) + Send + Sync + 'static,
+    {
+        self.hooks.on_slow_request = Some(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` before and after every DELETE and MOVE
+    ///
+    /// Returning `false` for the `AuditStage::Before` event cancels the operation with
+    /// `Error::Denied`, the return value is ignored afterwards.
+    pub fn on_audit<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&AuditEvent) -> bool + Send + Sync + 'static,
+    {
+        self.hooks.on_audit = Some(Arc::new(hook));
+        self
+    }
+
+    /// Inject delays, statuses and dropped connections into the requests of the client
+    #[cfg(feature = "fault-injection")]
+    pub fn set_fault_injector(mut self, injector: types::fault_cmd::FaultInjector) -> Self {
+        self.fault_injector = Some(injector);
+        self
+    }
+
+    /// Accept any server certificate, expired, self-signed or for another host
+    ///
+    /// Only for test servers, anyone on the network can then read and alter the traffic.
+    #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.tls.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    /// Trust the certificates of a PEM file content, for servers with a private CA
+    #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+    pub fn add_root_certificate_pem(mut self, pem: &[u8]) -> Result<Self> {
+        self.tls
+            .root_certificates
+            .extend(reqwest::Certificate::from_pem_bundle(pem)?);
+        Ok(self)
+    }
+
+    /// Trust a DER encoded certificate
+    #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+    pub fn add_root_certificate_der(mut self, der: &[u8]) -> Result<Self> {
+        self.tls
+            .root_certificates
+            .push(reqwest::Certificate::from_der(der)?);
+        Ok(self)
+    }
+
+    /// Trust the certificates of a PEM or DER file, failing when it cannot be read or parsed
+    #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+    pub fn add_root_certificate_file(self, path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let content = std::fs::read(path)?;
+        match content.starts_with(b"-----BEGIN") {
+            true => self.add_root_certificate_pem(&content),
+            false => self.add_root_certificate_der(&content),
+        }
+    }
+
+    /// Present a client certificate to servers asking for mutual TLS
+    ///
+    /// Needs the `native-tls` feature or one of the `rustls-tls` ones.
+    #[cfg(all(feature = "__identity", not(target_arch = "wasm32")))]
+    pub fn set_identity(mut self, identity: reqwest::Identity) -> Self {
+        self.tls.identity = Some(identity);
+        self
+    }
+
+    /// Present the client certificate and key of a PKCS #12 archive, a `.p12` or `.pfx` file
+    #[cfg(all(feature = "native-tls", not(target_arch = "wasm32")))]
+    pub fn set_client_cert_pkcs12(self, der: &[u8], password: &str) -> Result<Self> {
+        Ok(self.set_identity(reqwest::Identity::from_pkcs12_der(der, password)?))
+    }
+
+    /// Build the client, failing with `Error::InvalidHost` if the host is not a http(s) URL
+    ///
+    /// The TLS settings apply to the agent built here, they cannot be combined with `set_agent`.
+    pub fn build(self) -> Result<Client> {
+        let host = self
+            .host
+            .ok_or(Error::Decode(DecodeError::FieldNotFound(FieldError {
+                field: "host".to_owned(),
+            })))?;
+        validate_host(&host)?;
+        #[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+        let agent = match (self.agent, self.tls.is_customized()) {
+            (Some(_), true) => {
+                return Err(Error::Decode(DecodeError::FieldNotSupported(FieldError {
+                    field: "tls settings with set_agent".to_owned(),
+                })))
+            }
+            (Some(agent), false) => agent,
+            (None, _) => self.tls.apply(reqwest::Client::builder()).build()?,
+        };
+        #[cfg(not(all(feature = "__tls", not(target_arch = "wasm32"))))]
+        let agent = self.agent.unwrap_or_default();
         Ok(Client {
-            agent: if let Some(agent) = self.agent {
-                agent
-            } else {
-                reqwest::Client::new()
-            },
-            host: self
-                .host
-                .ok_or(Error::Decode(DecodeError::FieldNotFound(FieldError {
-                    field: "host".to_owned(),
-                })))?,
+            agent,
+            host,
             auth: if let Some(auth) = self.auth {
                 auth
             } else {
                 Auth::Anonymous
             },
+            #[cfg(feature = "digest")]
             digest_auth: Arc::new(Default::default()),
+            quirks: self.quirks.unwrap_or_default(),
+            raw_paths: self.raw_paths,
+            request_id_header: self.request_id_header,
+            request_id: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            recorder: self.recorder,
+            #[cfg(not(target_arch = "wasm32"))]
+            get_cache: self.get_cache.map(|cache| GetCache {
+                max_body_size: self.cache_max_body_size,
+                ..cache
+            }),
+            hooks: self.hooks,
+            list_cache: self.list_cache_ttl.map(ListCache::new),
+            slow_request_threshold: self.slow_request_threshold,
+            retry_policy: self.retry_policy,
+            lock_token: None,
+            cancellation: None,
+            deadline: None,
+            #[cfg(feature = "fault-injection")]
+            fault_injector: self.fault_injector,
+            stats: Arc::new(Default::default()),
+            negotiated_auth: Arc::new(Default::default()),
         })
     }
 }
+
+fn validate_host(host: &str) -> Result<()> {
+    let invalid = |reason: String| {
+        Err(Error::InvalidHost(InvalidHostError {
+            host: host.to_owned(),
+            reason,
+        }))
+    };
+    let url = match Url::parse(host) {
+        Ok(url) => url,
+        Err(err) => return invalid(err.to_string()),
+    };
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return invalid(format!("unsupported scheme {}", url.scheme()));
+    }
+    if url.host_str().is_none() {
+        return invalid("missing host".to_owned());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::copy_cmd::CopyOptions;
+    use crate::types::propfind_cmd::PropfindRequest;
+    use crate::types::request_cmd::RequestOptions;
+    use crate::types::timestamp::{DateTime, DavDateTime as _};
+    use crate::types::{DecodeError, Error};
+    use crate::{ClientBuilder, Depth};
+    use wiremock::matchers::{body_string_contains, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn put_with_ensure_parent_creates_missing_collections() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("MKCOL"))
+            .and(path("/dav/a"))
+            .respond_with(ResponseTemplate::new(405))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("MKCOL"))
+            .and(path("/dav/a/b"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/dav/a/b/file.txt"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let options = crate::types::put_cmd::PutOptions {
+            ensure_parent: true,
+            ..Default::default()
+        };
+        client
+            .put_with("/a/b/file.txt", "data", options)
+            .await
+            .unwrap();
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn get_with_sends_request_options() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dav/a.txt"))
+            .and(query_param("token", "abc"))
+            .and(query_param("version", "3"))
+            .and(header("x-trace", "42"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("data"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/slow.txt"))
+            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(5)))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav?token=abc", mock_server.uri()))
+            .build()
+            .unwrap();
+        let options = RequestOptions::new()
+            .header("x-trace".parse().unwrap(), "42".parse().unwrap())
+            .query("version", "3");
+        let response = client.get_with("/a.txt", options).await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "data");
+
+        let options = RequestOptions::new().timeout(std::time::Duration::from_millis(50));
+        match client.get_with("/slow.txt", options).await.unwrap_err() {
+            Error::Reqwest(error) => assert!(error.is_timeout()),
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[tokio::test]
+    async fn put_with_mtime_falls_back_to_proppatch() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/nextcloud.txt"))
+            .and(header("x-oc-mtime", "1554904800"))
+            .respond_with(ResponseTemplate::new(201).append_header("x-oc-mtime", "accepted"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/iis.txt"))
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPPATCH"))
+            .and(path("/iis.txt"))
+            .and(body_string_contains(
+                "Win32LastModifiedTime>Wed, 10 Apr 2019 14:00:00 GMT<",
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPPATCH"))
+            .and(path("/iis.txt"))
+            .and(body_string_contains("<D:lastmodified>"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/iis.txt</D:href><D:propstat><D:prop><D:lastmodified/></D:prop><D:status>HTTP/1.1 403 Forbidden</D:status></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        let options = crate::types::put_cmd::PutOptions {
+            mtime: Some(DateTime::from_epoch_seconds(1554904800)),
+            ..Default::default()
+        };
+        let response = client
+            .put_with("/nextcloud.txt", "data", options.clone())
+            .await
+            .unwrap();
+        assert!(response.mtime_set);
+        let response = client.put_with("/iis.txt", "data", options).await.unwrap();
+        assert!(response.mtime_set);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn list_with_sends_selected_props() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/docs"))
+            .and(body_string_contains("<D:getetag/>"))
+            .and(body_string_contains(
+                r#"<x:fileid xmlns:x="http://owncloud.org/ns"/>"#,
+            ))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/docs/</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getetag>"abc"</D:getetag></D:prop></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let responses = client
+            .list_with(
+                "/docs",
+                Depth::Number(0),
+                PropfindRequest::props([("DAV:", "getetag"), ("http://owncloud.org/ns", "fileid")]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            responses[0].prop_stat[0].prop.tag.as_deref(),
+            Some("\"abc\"")
+        );
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn propfind_raw_props_returns_found_properties() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/a.txt"))
+            .and(header("depth", "0"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns"><d:response><d:href>/dav/a.txt</d:href><d:propstat><d:prop><d:getetag>"e1"</d:getetag><oc:fileid>42</oc:fileid></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat><d:propstat><d:prop><oc:permissions/></d:prop><d:status>HTTP/1.1 404 Not Found</d:status></d:propstat></d:response></d:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let props = client
+            .propfind_raw_props(
+                "/a.txt",
+                &[
+                    ("DAV:", "getetag"),
+                    ("http://owncloud.org/ns", "fileid"),
+                    ("http://owncloud.org/ns", "permissions"),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(props.len(), 2);
+        assert_eq!(props[&("DAV:".to_owned(), "getetag".to_owned())], "\"e1\"");
+        assert_eq!(
+            props[&("http://owncloud.org/ns".to_owned(), "fileid".to_owned())],
+            "42"
+        );
+    }
+
+    #[tokio::test]
+    async fn put_returns_version_headers() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/dav/a.txt"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .insert_header("oc-etag", "\"e1\"")
+                    .insert_header("oc-fileid", "00000042oc"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let response = client.put("/a.txt", "data").await.unwrap();
+        assert_eq!(response.etag.as_deref(), Some("\"e1\""));
+        assert_eq!(response.file_id.as_deref(), Some("00000042oc"));
+        assert_eq!(response.status, 201);
+    }
+
+    #[tokio::test]
+    async fn exists_falls_back_to_propfind() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(405))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/a.txt"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/a.txt</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified></D:prop></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/missing.txt"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        assert!(client.exists("/a.txt").await.unwrap());
+        assert!(!client.exists("/missing.txt").await.unwrap());
+    }
+
+    #[test]
+    fn url_for_merges_query_strings() {
+        let client = ClientBuilder::new()
+            .set_host("https://gateway.example.com/dav/?share=abc".to_owned())
+            .build()
+            .unwrap();
+        let url = client.url_for("/docs/a.txt?version=2").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://gateway.example.com/dav/docs/a.txt?share=abc&version=2"
+        );
+        let url = client.url_for("docs/").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://gateway.example.com/dav/docs/?share=abc"
+        );
+    }
+
+    #[test]
+    fn url_for_encodes_path_segments() {
+        let client = ClientBuilder::new()
+            .set_host("https://server/dav/".to_owned())
+            .build()
+            .unwrap();
+        let url = client.url_for("/100% #1/日記.txt").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://server/dav/100%25%20%231/%E6%97%A5%E8%A8%98.txt"
+        );
+        assert_eq!(client.path_of(url.path()), "/100% #1/日記.txt");
+
+        let client = ClientBuilder::new()
+            .set_host("https://server/dav/".to_owned())
+            .set_raw_paths(true)
+            .build()
+            .unwrap();
+        let url = client.url_for("/a%20b.txt").unwrap();
+        assert_eq!(url.as_str(), "https://server/dav/a%20b.txt");
+        assert_eq!(client.path_of(url.path()), "/a%20b.txt");
+    }
+
+    #[tokio::test]
+    async fn joined_client_is_rooted_at_sub_path() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/addressbooks/users/stefan/"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/addressbooks/users/stefan/My%20Contacts/</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap()
+            .join("addressbooks/users/stefan")
+            .unwrap();
+        let entities = client.list("/", Depth::Number(1)).await.unwrap();
+        assert_eq!(entities[0].rel_path(&client), "/My Contacts");
+        mock_server.verify().await;
+    }
+
+    #[test]
+    fn build_rejects_invalid_host() {
+        for host in ["server/dav", "ftp://server/dav", "http://"] {
+            let result = ClientBuilder::new().set_host(host.to_owned()).build();
+            assert!(
+                matches!(result, Err(crate::Error::InvalidHost(ref err)) if err.host == host),
+                "{host}"
+            );
+        }
+        let client = crate::Client::from_parts(
+            reqwest::Client::new(),
+            "https://server/dav".to_owned(),
+            crate::Auth::Anonymous,
+        )
+        .unwrap();
+        assert_eq!(client.host, "https://server/dav");
+    }
+
+    #[cfg(feature = "__tls")]
+    #[test]
+    fn tls_settings_report_errors() {
+        let builder = ClientBuilder::new().set_host("https://server/dav".to_owned());
+        assert!(matches!(
+            builder
+                .clone()
+                .add_root_certificate_file("/nonexistent/ca.pem"),
+            Err(Error::Io(_))
+        ));
+        assert!(builder
+            .clone()
+            .add_root_certificate_der(b"not a certificate")
+            .is_err());
+        assert!(builder
+            .clone()
+            .danger_accept_invalid_certs(true)
+            .set_agent(reqwest::Client::new())
+            .build()
+            .is_err());
+        builder.danger_accept_invalid_certs(true).build().unwrap();
+    }
+
+    #[cfg(feature = "native-tls")]
+    #[test]
+    fn client_cert_pkcs12_reports_errors() {
+        let result = ClientBuilder::new()
+            .set_host("https://server/dav".to_owned())
+            .set_client_cert_pkcs12(b"not an archive", "secret");
+        assert!(matches!(result, Err(Error::Reqwest(_))));
+    }
+
+    #[test]
+    fn path_of_strips_base_path() {
+        let client = ClientBuilder::new()
+            .set_host("https://cloud.example.com/nextcloud/remote.php/dav/files/alice/".to_owned())
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.path_of("/nextcloud/remote.php/dav/files/alice/My%20Docs/a%C3%A9.txt"),
+            "/My Docs/aé.txt"
+        );
+        assert_eq!(
+            client.path_of("https://cloud.example.com/nextcloud/remote.php/dav/files/alice/"),
+            "/"
+        );
+    }
+
+    #[tokio::test]
+    async fn list_cache_is_invalidated_by_writes() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/docs"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/docs/</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/dav/docs/a.txt"))
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .set_list_cache_ttl(std::time::Duration::from_secs(60))
+            .build()
+            .unwrap();
+        client.list("/docs", Depth::Number(1)).await.unwrap();
+        client.list("/docs/", Depth::Number(1)).await.unwrap();
+        client.put("/docs/a.txt", "a").await.unwrap();
+        client.list("/docs", Depth::Number(1)).await.unwrap();
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn move_keeps_query_in_destination() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("MOVE"))
+            .and(path("/dav/a.txt"))
+            .and(query_param("token", "t1"))
+            .and(header(
+                "destination",
+                format!("{}/dav/b.txt?token=t1", mock_server.uri()).as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav?token=t1", mock_server.uri()))
+            .build()
+            .unwrap();
+        client.mv("a.txt", "b.txt").await.unwrap();
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn copy_with_options_sends_overwrite_and_depth() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("COPY"))
+            .and(path("/dav/a"))
+            .and(header(
+                "destination",
+                format!("{}/dav/b", mock_server.uri()).as_str(),
+            ))
+            .and(header("overwrite", "F"))
+            .and(header("depth", "0"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        client
+            .cp_with(
+                "a",
+                "b",
+                CopyOptions {
+                    overwrite: false,
+                    depth: Depth::Number(0),
+                },
+            )
+            .await
+            .unwrap();
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn request_id_is_sent_and_reported_in_errors() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/missing.txt"))
+            .and(header("x-request-id", "op-1"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .set_request_id_header(reqwest::header::HeaderName::from_static("x-request-id"))
+            .build()
+            .unwrap()
+            .with_request_id("op-1".to_owned());
+        match client.get("missing.txt").await {
+            Err(crate::Error::Decode(crate::DecodeError::Server(e))) => {
+                assert_eq!(e.request_id.as_deref(), Some("op-1"))
+            }
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn errors_report_method_url_and_status() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/dav/locked.txt"))
+            .respond_with(ResponseTemplate::new(409))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav?token=secret", mock_server.uri()))
+            .build()
+            .unwrap();
+        let err = client.delete("locked.txt").await.unwrap_err();
+        assert!(err.is_conflict() && !err.is_not_found() && !err.is_retryable());
+        assert_eq!(err.status(), Some(409));
+        assert_eq!(err.method(), Some("DELETE"));
+        let url = format!("{}/dav/locked.txt", mock_server.uri());
+        assert_eq!(err.url(), Some(format!("{}?token=secret", url).as_str()));
+        assert!(err
+            .to_string()
+            .starts_with(&format!("DELETE {} failed", url)));
+    }
+
+    #[tokio::test]
+    async fn delete_many_reports_each_path() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/dav/a.txt"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/dav/docs"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/docs/locked.txt</D:href><D:status>HTTP/1.1 423 Locked</D:status></D:response></D:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/dav/missing.txt"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let results = client
+            .delete_many(["a.txt", "docs", "missing.txt"], 2)
+            .await;
+        let paths: Vec<&str> = results.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, ["a.txt", "docs", "missing.txt"]);
+        assert!(results[0].1.is_ok());
+        assert!(matches!(
+            &results[1].1,
+            Err(Error::Decode(DecodeError::MultiStatus(error))) if error.failures.len() == 1
+        ));
+        assert!(results[2].1.as_ref().unwrap_err().is_not_found());
+    }
+
+    #[tokio::test]
+    async fn hooks_see_every_request() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock_server)
+            .await;
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let requests = seen.clone();
+        let responses = seen.clone();
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .on_request(move |summary| {
+                requests
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", summary.method, summary.url))
+            })
+            .on_response(move |summary| {
+                responses
+                    .lock()
+                    .unwrap()
+                    .push(format!("{:?}", summary.status))
+            })
+            .build()
+            .unwrap();
+        client.delete("a.txt").await.unwrap();
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                format!("DELETE {}/a.txt", mock_server.uri()),
+                "Some(204)".to_owned()
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stats_count_requests_and_failures() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        client.put("a.txt", "12345").await.unwrap();
+        assert!(client.get("b.txt").await.is_err());
+        let stats = client.stats();
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.bytes_sent, 5);
+        assert!(stats.average_latency().is_some());
+        client.reset_stats();
+        assert_eq!(client.stats(), Default::default());
+    }
+
+    #[tokio::test]
+    async fn slow_requests_are_reported() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200).set_delay(std::time::Duration::from_millis(300)),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let slow = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let reported = slow.clone();
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .set_slow_request_threshold(std::time::Duration::from_millis(50))
+            .on_slow_request(move |summary, _| {
+                reported.lock().unwrap().push(summary.method.clone())
+            })
+            .build()
+            .unwrap();
+        client.get("a.txt").await.unwrap();
+        assert_eq!(*slow.lock().unwrap(), vec!["GET".to_owned()]);
+    }
+}
@@ -0,0 +1,164 @@
+//! Listings parsed while the multistatus body is received.
+
+use std::collections::VecDeque;
+
+use futures_util::{Stream, TryStreamExt};
+use quick_xml::NsReader;
+use tokio::io::BufReader;
+use tokio_util::io::StreamReader;
+use tokio_util::sync::CancellationToken;
+
+use crate::types::list_cmd::{ListEntity, ListResponse, MultiStatusParser};
+use crate::types::{DecodeError, Error, Result, StatusMismatchedError};
+use crate::{Client, Depth};
+
+impl Client {
+    /// List files and folders at the given path, yielding them as the response arrives
+    ///
+    /// Unlike `list` the body is never buffered, which keeps the memory use flat for huge
+    /// `Depth: infinity` listings. The list cache and the `finite_depth` quirk are not used.
+    /// Once the client is cancelled, see `Client::with_cancellation`, the stream yields
+    /// `Error::Cancelled` and ends.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list_stream(
+        &self,
+        path: &str,
+        depth: Depth,
+    ) -> Result<impl Stream<Item = Result<ListEntity>>> {
+        let response = self.list_raw(path, depth).await?;
+        if !response.status().is_success() {
+            return Err(Error::Decode(DecodeError::StatusMismatched(
+                StatusMismatchedError::new(&response, 207),
+            )));
+        }
+        let body = StreamReader::new(Box::pin(
+            response.bytes_stream().map_err(std::io::Error::other),
+        ));
+        let state = ListStreamState {
+            reader: NsReader::from_reader(BufReader::new(body)),
+            parser: MultiStatusParser::new(false).lenient(self.quirks.lenient_parsing),
+            buf: vec![],
+            pending: VecDeque::new(),
+            done: false,
+            cancellation: self.cancellation.clone(),
+        };
+        Ok(futures_util::stream::unfold(
+            state,
+            |mut state| async move {
+                let item = state.next().await?;
+                Some((item, state))
+            },
+        ))
+    }
+
+    /// List files and folders at the given path as they arrive, stopping when `token` is
+    /// cancelled
+    ///
+    /// The entities yielded before are what was listed so far, see `list_stream`.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, token))
+    )]
+    pub async fn list_stream_cancellable(
+        &self,
+        path: &str,
+        depth: Depth,
+        token: CancellationToken,
+    ) -> Result<impl Stream<Item = Result<ListEntity>>> {
+        self.with_cancellation(token).list_stream(path, depth).await
+    }
+}
+
+struct ListStreamState<R> {
+    reader: NsReader<R>,
+    parser: MultiStatusParser,
+    buf: Vec<u8>,
+    /// Responses parsed but not yielded yet.
+    pending: VecDeque<ListResponse>,
+    done: bool,
+    cancellation: Option<CancellationToken>,
+}
+
+impl<R: tokio::io::AsyncBufRead + Unpin> ListStreamState<R> {
+    async fn next(&mut self) -> Option<Result<ListEntity>> {
+        loop {
+            if let Some(response) = self.pending.pop_front() {
+                return Some(ListEntity::try_from(response));
+            }
+            if self.done {
+                return None;
+            }
+            self.buf.clear();
+            let reading = self.reader.read_resolved_event_into_async(&mut self.buf);
+            let read = match &self.cancellation {
+                Some(token) => token.run_until_cancelled(reading).await,
+                None => Some(reading.await),
+            };
+            let more = match read {
+                Some(Ok((namespace, event))) => self.parser.event(&namespace, event),
+                Some(Err(e)) => Err(e.into()),
+                None => Err(Error::Cancelled),
+            };
+            match more {
+                Ok(true) => self.pending.extend(self.parser.take_responses()),
+                Ok(false) => {
+                    self.done = true;
+                    if let Err(e) = self.parser.finish() {
+                        return Some(Err(e));
+                    }
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::{ClientBuilder, Depth};
+
+    #[tokio::test]
+    async fn stream_yields_every_response() {
+        let mock_server = MockServer::start().await;
+        let mut body =
+            r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#.to_owned();
+        for i in 0..100 {
+            body.push_str(&format!(
+                r#"<D:response><D:href>/dav/f{}.txt</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:getcontentlength>{}</D:getcontentlength><D:resourcetype/></D:prop></D:propstat></D:response>"#,
+                i, i
+            ));
+        }
+        body.push_str("</D:multistatus>");
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/"))
+            .and(header("depth", "infinity"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(body))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let entities: Vec<_> = client
+            .list_stream("/", Depth::Infinity)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(entities.len(), 100);
+        assert_eq!(entities[99].name(), "f99.txt");
+    }
+}
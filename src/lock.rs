@@ -0,0 +1,204 @@
+//! RFC 4918 locking.
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, Response};
+
+use crate::types::lock_cmd::{parse_lock_discovery, ActiveLock, LockGuard, LockOptions, LockToken};
+use crate::types::{Dav2xx, DecodeError, Error, FieldError, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    pub async fn lock_raw(&self, path: &str, options: &LockOptions) -> Result<Response> {
+        let depth = match options.depth {
+            Depth::Number(0) => "0",
+            _ => "infinity",
+        };
+        let builder = self
+            .start_request(Method::from_bytes(b"LOCK")?, path)
+            .await?
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .header("depth", depth)
+            .header("timeout", options.timeout_header())
+            .body(options.to_xml());
+        self.execute(builder).await
+    }
+
+    /// Lock a file or folder
+    ///
+    /// Writes made through `LockGuard::client` carry the lock token, the lock is released when
+    /// the guard is dropped. Locking a path which does not exist creates an empty file.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn lock(&self, path: &str, options: LockOptions) -> Result<LockGuard> {
+        let response = self.lock_raw(path, &options).await?.dav2xx().await?;
+        let header_token = response
+            .headers()
+            .get("lock-token")
+            .and_then(|value| value.to_str().ok())
+            .map(LockToken::parse);
+        let locks = parse_lock_discovery(&response.text().await?)?;
+        let lock = match header_token {
+            Some(token) => locks
+                .into_iter()
+                .find(|lock| lock.token == token)
+                .unwrap_or(ActiveLock {
+                    token,
+                    scope: options.scope,
+                    depth: None,
+                    timeout: options.timeout,
+                    root: None,
+                }),
+            None => locks.into_iter().next().ok_or_else(|| {
+                Error::Decode(DecodeError::FieldNotFound(FieldError {
+                    field: "locktoken".to_owned(),
+                }))
+            })?,
+        };
+        Ok(LockGuard {
+            client: self.with_lock_token(lock.token.clone()),
+            path: path.to_owned(),
+            lock,
+            released: false,
+        })
+    }
+
+    pub async fn unlock_raw(&self, path: &str, token: &LockToken) -> Result<Response> {
+        let builder = self
+            .start_request(Method::from_bytes(b"UNLOCK")?, path)
+            .await?
+            .header("lock-token", token.to_lock_token_header());
+        self.execute(builder).await
+    }
+
+    /// Release a lock taken with `lock`
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn unlock(&self, path: &str, token: &LockToken) -> Result<()> {
+        self.unlock_raw(path, token).await?.dav2xx().await?;
+        Ok(())
+    }
+
+    /// A client sending `token` in the `If` header of every write
+    pub fn with_lock_token(&self, token: LockToken) -> Client {
+        Client {
+            lock_token: Some(token),
+            ..self.clone()
+        }
+    }
+}
+
+impl LockGuard {
+    pub fn token(&self) -> &LockToken {
+        &self.lock.token
+    }
+
+    pub fn lock(&self) -> &ActiveLock {
+        &self.lock
+    }
+
+    /// Client for writing to the locked resource
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Unlock, reporting the outcome of the UNLOCK request
+    pub async fn release(mut self) -> Result<()> {
+        self.released = true;
+        self.client.unlock(&self.path, &self.lock.token).await
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let client = self.client.clone();
+            let path = std::mem::take(&mut self.path);
+            let token = self.lock.token.clone();
+            runtime.spawn(async move {
+                let _ = client.unlock(&path, &token).await;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::lock_cmd::LockOptions;
+    use crate::ClientBuilder;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const TOKEN: &str = "urn:uuid:e71d4fae-5dec-22d6-fea5-00a0c91e6be4";
+
+    #[tokio::test]
+    async fn writes_through_guard_carry_lock_token() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("LOCK"))
+            .and(path("/dav/doc.txt"))
+            .and(header("timeout", "Second-60"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("lock-token", format!("<{TOKEN}>").as_str())
+                    .set_body_string(format!(
+                        r#"<?xml version="1.0" encoding="utf-8"?><D:prop xmlns:D="DAV:"><D:lockdiscovery><D:activelock><D:lockscope><D:exclusive/></D:lockscope><D:timeout>Second-60</D:timeout><D:locktoken><D:href>{TOKEN}</D:href></D:locktoken></D:activelock></D:lockdiscovery></D:prop>"#
+                    )),
+            )
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/dav/doc.txt"))
+            .and(header("if", format!("(<{TOKEN}>)").as_str()))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("UNLOCK"))
+            .and(path("/dav/doc.txt"))
+            .and(header("lock-token", format!("<{TOKEN}>").as_str()))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let options = LockOptions {
+            timeout: Some(std::time::Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let guard = client.lock("/doc.txt", options.clone()).await.unwrap();
+        assert_eq!(guard.token().as_str(), TOKEN);
+        guard.client().put("/doc.txt", "locked").await.unwrap();
+        guard.release().await.unwrap();
+
+        drop(client.lock("/doc.txt", options).await.unwrap());
+        // the unlock of the dropped guard runs in the background, wait until it arrived
+        let unlocked = async {
+            loop {
+                let requests = mock_server.received_requests().await.unwrap();
+                if requests
+                    .iter()
+                    .filter(|r| r.method.as_str() == "UNLOCK")
+                    .count()
+                    == 2
+                {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(5), unlocked)
+            .await
+            .unwrap();
+        mock_server.verify().await;
+    }
+}
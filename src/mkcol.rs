@@ -0,0 +1,95 @@
+//! Creation of collections with properties, MKCALENDAR (RFC 4791) and extended MKCOL (RFC 5689).
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, Response};
+
+use crate::types::mkcol_cmd::CollectionProps;
+use crate::types::{Dav2xx, Result};
+use crate::Client;
+
+impl Client {
+    pub async fn mkcalendar_raw(&self, path: &str, props: &CollectionProps) -> Result<Response> {
+        self.mkcol_with_body(b"MKCALENDAR", path, props, "C:mkcalendar")
+            .await
+    }
+
+    /// Create a calendar with the given properties, like its display name and components
+    ///
+    /// Use absolute path to the webdav server calendar location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn mkcalendar(&self, path: &str, props: CollectionProps) -> Result<()> {
+        self.mkcalendar_raw(path, &props).await?.dav2xx().await?;
+        Ok(())
+    }
+
+    pub async fn mkcol_ext_raw(&self, path: &str, props: &CollectionProps) -> Result<Response> {
+        self.mkcol_with_body(b"MKCOL", path, props, "D:mkcol").await
+    }
+
+    /// Create a collection with the given properties, like an address book with
+    /// `CollectionProps::resource_type`
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn mkcol_ext(&self, path: &str, props: CollectionProps) -> Result<()> {
+        self.mkcol_ext_raw(path, &props).await?.dav2xx().await?;
+        Ok(())
+    }
+
+    async fn mkcol_with_body(
+        &self,
+        method: &[u8],
+        path: &str,
+        props: &CollectionProps,
+        root: &str,
+    ) -> Result<Response> {
+        let mut builder = self
+            .start_request(Method::from_bytes(method)?, path)
+            .await?;
+        if !props.is_empty() {
+            builder = builder
+                .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+                .body(props.to_xml(root));
+        }
+        self.execute(builder).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::mkcol_cmd::CollectionProps;
+    use crate::ClientBuilder;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn mkcalendar_sends_properties() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("MKCALENDAR"))
+            .and(path("/dav/calendars/work"))
+            .and(body_string_contains("<C:mkcalendar "))
+            .and(body_string_contains("<D:displayname>Work</D:displayname>"))
+            .and(body_string_contains(
+                r#"<C:supported-calendar-component-set><C:comp name="VEVENT"/></C:supported-calendar-component-set>"#,
+            ))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        client
+            .mkcalendar(
+                "/calendars/work",
+                CollectionProps::new()
+                    .display_name("Work")
+                    .calendar_components(["VEVENT"]),
+            )
+            .await
+            .unwrap();
+        mock_server.verify().await;
+    }
+}
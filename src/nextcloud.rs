@@ -0,0 +1,1012 @@
+//! Nextcloud specific DAV endpoints.
+
+use std::collections::HashMap;
+
+use md5::{Digest, Md5};
+use reqwest::{Method, Response};
+use url::Url;
+
+use crate::types::list_cmd::{status_is_ok, ListEntity, ListFile};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::nextcloud_cmd::ChunkOptions;
+use crate::types::nextcloud_cmd::{
+    BulkFile, BulkResponseEntry, BulkUploadResult, FileIdMultiStatus, FileVersion, NextcloudSearch,
+    PreviewOptions, TrashItem, NC_NAMESPACE,
+};
+use crate::types::propfind_cmd::PropfindRequest;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::put_cmd::PutResponse;
+use crate::types::request_cmd::RequestOptions;
+use crate::types::status_cmd::{status_code, StatusFailure};
+use crate::types::timestamp::{DateTime, DavDateTime};
+use crate::types::{
+    check_multi_status, Dav2xx, DecodeError, Error, FieldError, MultiStatusError, Result,
+};
+use crate::{Auth, Client, Depth};
+
+/// Properties needed to turn a REPORT response into `ListEntity` values.
+const LIST_PROPS: &str = r#"
+                <d:getlastmodified/>
+                <d:resourcetype/>
+                <d:getetag/>
+                <d:getcontentlength/>
+                <d:getcontenttype/>
+                <d:quota-used-bytes/>
+                <d:quota-available-bytes/>
+"#;
+
+impl Client {
+    /// The DAV root (`.../remote.php/dav`) and user of a Nextcloud host.
+    pub(crate) fn nextcloud_root(&self) -> Result<(String, String)> {
+        let url = Url::parse(&self.host)?;
+        let path = url.path();
+        let not_found = || {
+            Error::Decode(DecodeError::FieldNotFound(FieldError {
+                field: "nextcloud dav root".to_owned(),
+            }))
+        };
+        let (prefix, user) = if let Some(index) = path.find("/remote.php/dav/files/") {
+            let user = path[index + "/remote.php/dav/files/".len()..]
+                .split('/')
+                .next()
+                .filter(|user| !user.is_empty())
+                .ok_or_else(not_found)?;
+            (&path[..index], user.to_owned())
+        } else if let Some(index) = path.find("/remote.php/webdav") {
+            let user = match &self.auth {
+                Auth::Basic(user, _) => user.clone(),
+                #[cfg(feature = "digest")]
+                Auth::Digest(user, _) => user.clone(),
+                Auth::Auto { username, .. } => username.clone(),
+                _ => return Err(not_found()),
+            };
+            (&path[..index], user)
+        } else {
+            return Err(not_found());
+        };
+        let mut root = url.clone();
+        root.set_path(&format!("{}/remote.php/dav", prefix));
+        root.set_query(None);
+        Ok((root.as_str().trim_end_matches('/').to_owned(), user))
+    }
+
+    /// Translate a path relative to the host into one relative to the user's files root.
+    pub(crate) fn nextcloud_user_path(&self, path: &str) -> Result<String> {
+        let (_, user) = self.nextcloud_root()?;
+        let url = Url::parse(&self.host)?;
+        let host_path = url.path();
+        let files_root = format!("/remote.php/dav/files/{}", user);
+        let base = match host_path.find(&files_root) {
+            Some(index) => &host_path[index + files_root.len()..],
+            None => host_path
+                .find("/remote.php/webdav")
+                .map(|index| &host_path[index + "/remote.php/webdav".len()..])
+                .unwrap_or(""),
+        };
+        Ok(format!(
+            "{}/{}",
+            base.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        ))
+    }
+
+    /// A client rooted at a per user Nextcloud endpoint such as `versions` or `trashbin`.
+    pub(crate) fn nextcloud_endpoint(&self, endpoint: &str) -> Result<Client> {
+        let (root, user) = self.nextcloud_root()?;
+        Ok(self.with_host(format!("{}/{}/{}", root, endpoint, user)))
+    }
+
+    /// Get the Nextcloud file id of a resource
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn file_id(&self, path: &str) -> Result<String> {
+        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
+            <d:propfind xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+                <d:prop>
+                    <oc:fileid/>
+                </d:prop>
+            </d:propfind>
+        "#;
+        let builder = self
+            .start_request(Method::from_bytes(b"PROPFIND")?, path)
+            .await?
+            .header("depth", "0")
+            .body(body);
+        let text = self.execute(builder).await?.dav2xx().await?.text().await?;
+        let multi_status: FileIdMultiStatus = serde_xml_rs::from_str(&text)?;
+        multi_status
+            .responses
+            .into_iter()
+            .flat_map(|response| response.prop_stat)
+            .filter(|prop_stat| status_is_ok(&prop_stat.status))
+            .find_map(|prop_stat| prop_stat.prop.file_id)
+            .ok_or(Error::Decode(DecodeError::FieldNotFound(FieldError {
+                field: "fileid".to_owned(),
+            })))
+    }
+
+    /// List the previous versions of a file kept by the Nextcloud versions app
+    ///
+    /// The file id can be obtained with `file_id`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list_versions(&self, file_id: &str) -> Result<Vec<FileVersion>> {
+        let versions = self.nextcloud_endpoint("versions")?;
+        let entities = versions
+            .list(&format!("versions/{}", file_id), Depth::Number(1))
+            .await?;
+        Ok(entities
+            .into_iter()
+            .filter_map(|entity| match entity {
+                ListEntity::File(file) => Some(FileVersion {
+                    version_id: file
+                        .href
+                        .trim_end_matches('/')
+                        .rsplit('/')
+                        .next()
+                        .unwrap_or_default()
+                        .to_owned(),
+                    file_id: file_id.to_owned(),
+                    href: file.href,
+                    last_modified: file.last_modified,
+                    content_length: file.content_length,
+                }),
+                ListEntity::Folder(_) => None,
+            })
+            .collect())
+    }
+
+    /// Mark or unmark a file or folder as favorite
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn set_favorite(&self, path: &str, favorite: bool) -> Result<()> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+            <d:propertyupdate xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+                <d:set>
+                    <d:prop>
+                        <oc:favorite>{}</oc:favorite>
+                    </d:prop>
+                </d:set>
+            </d:propertyupdate>
+        "#,
+            if favorite { 1 } else { 0 }
+        );
+        let builder = self
+            .start_request(Method::from_bytes(b"PROPPATCH")?, path)
+            .await?
+            .body(body);
+        let text = self.execute(builder).await?.dav2xx().await?.text().await?;
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+        let multi_status = self.parse_multi_status(&text)?;
+        let failures: Vec<StatusFailure> = multi_status
+            .responses
+            .into_iter()
+            .flat_map(|response| {
+                let href = response.href;
+                response
+                    .prop_stat
+                    .into_iter()
+                    .filter(|prop_stat| !status_is_ok(&prop_stat.status))
+                    .map(move |prop_stat| StatusFailure {
+                        href: href.clone(),
+                        status: status_code(&prop_stat.status),
+                        description: None,
+                    })
+            })
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Decode(DecodeError::MultiStatus(MultiStatusError {
+                failures,
+            })))
+        }
+    }
+
+    /// List all files and folders of the user marked as favorite
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list_favorites(&self) -> Result<Vec<ListEntity>> {
+        let files = self.nextcloud_endpoint("files")?;
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+            <oc:filter-files xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+                <d:prop>{}</d:prop>
+                <oc:filter-rules>
+                    <oc:favorite>1</oc:favorite>
+                </oc:filter-rules>
+            </oc:filter-files>
+        "#,
+            LIST_PROPS
+        );
+        let builder = files
+            .start_request(Method::from_bytes(b"REPORT")?, "")
+            .await?
+            .body(body);
+        let text = files.execute(builder).await?.dav2xx().await?.text().await?;
+        let multi_status = files.parse_multi_status(&text)?;
+        multi_status
+            .responses
+            .into_iter()
+            .map(ListEntity::try_from)
+            .collect()
+    }
+
+    /// Search files below `path` on the server with the Nextcloud SEARCH method
+    ///
+    /// Much cheaper than an infinite depth listing to find e.g. all PDFs of the user.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn nextcloud_search(
+        &self,
+        path: &str,
+        search: &NextcloudSearch,
+    ) -> Result<Vec<ListEntity>> {
+        let (root, user) = self.nextcloud_root()?;
+        let scope = format!("/files/{}{}", user, self.nextcloud_user_path(path)?);
+        let body = search.to_xml(scope.trim_end_matches('/'), LIST_PROPS);
+        let builder = self
+            .with_host(root)
+            .start_request(Method::from_bytes(b"SEARCH")?, "")
+            .await?
+            .header("content-type", "text/xml")
+            .body(body);
+        let text = self.execute(builder).await?.dav2xx().await?.text().await?;
+        let multi_status = self.parse_multi_status(&text)?;
+        multi_status
+            .responses
+            .into_iter()
+            .map(ListEntity::try_from)
+            .collect()
+    }
+
+    /// Fetch the preview image the server generated for a file
+    ///
+    /// The file id can be obtained with `file_id`. Fails with a 404 when the server has no
+    /// preview provider for the file type.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn preview(&self, file_id: &str, options: &PreviewOptions) -> Result<Response> {
+        let (root, _) = self.nextcloud_root()?;
+        let base = root.trim_end_matches("/remote.php/dav");
+        let url = Url::parse(&format!("{}/index.php/core/preview", base))?;
+        let (client, path) = self.at_url(&url);
+        let request = RequestOptions::new()
+            .query("fileId", file_id)
+            .query("x", options.width.to_string())
+            .query("y", options.height.to_string())
+            .query("a", (options.keep_aspect as u8).to_string());
+        let builder = client
+            .start_request_with(Method::GET, &path, &request)
+            .await?;
+        client.execute(builder).await?.dav2xx().await
+    }
+
+    /// Fetch the preview image of a listed file, looking up its file id first
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, file))
+    )]
+    pub async fn preview_of(&self, file: &ListFile, options: &PreviewOptions) -> Result<Response> {
+        let file_id = self.file_id(&self.path_of(&file.href)).await?;
+        self.preview(&file_id, options).await
+    }
+
+    /// Upload many small files in a single request with the Nextcloud bulk upload endpoint
+    ///
+    /// When the server does not offer the endpoint (404, 405 or 501) every file is uploaded with
+    /// its own PUT instead.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, files))
+    )]
+    pub async fn bulk_upload(&self, files: Vec<BulkFile>) -> Result<Vec<BulkUploadResult>> {
+        let (root, _) = self.nextcloud_root()?;
+        let boundary = format!(
+            "boundary_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|duration| duration.as_nanos())
+                .unwrap_or(0)
+        );
+        let mut body: Vec<u8> = vec![];
+        let mut paths = vec![];
+        for file in &files {
+            let user_path = self.nextcloud_user_path(&file.path)?;
+            body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+            body.extend_from_slice(format!("X-File-Path: {}\r\n", user_path).as_bytes());
+            body.extend_from_slice(
+                format!(
+                    "X-File-MD5: {}\r\n",
+                    hex::encode(Md5::digest(&file.content))
+                )
+                .as_bytes(),
+            );
+            if let Some(mtime) = file.mtime {
+                body.extend_from_slice(
+                    format!("X-File-Mtime: {}\r\n", mtime.epoch_seconds()).as_bytes(),
+                );
+            }
+            body.extend_from_slice(
+                format!("Content-Length: {}\r\n\r\n", file.content.len()).as_bytes(),
+            );
+            body.extend_from_slice(&file.content);
+            body.extend_from_slice(b"\r\n");
+            paths.push(user_path);
+        }
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+        let builder = self
+            .with_host(root)
+            .start_request(Method::POST, "bulk")
+            .await?
+            .header(
+                "content-type",
+                format!("multipart/related; boundary={}", boundary),
+            )
+            .body(body);
+        let response = self.execute(builder).await?;
+        if matches!(response.status().as_u16(), 404 | 405 | 501) {
+            return self.upload_individually(files).await;
+        }
+        let text = response.dav2xx().await?.text().await?;
+        let entries: HashMap<String, BulkResponseEntry> =
+            serde_json::from_str(&text).map_err(|_| {
+                Error::Decode(DecodeError::FieldNotFound(FieldError {
+                    field: "bulk upload response".to_owned(),
+                }))
+            })?;
+        Ok(files
+            .into_iter()
+            .zip(paths)
+            .map(|(file, user_path)| match entries.get(&user_path) {
+                Some(entry) => BulkUploadResult {
+                    path: file.path,
+                    etag: entry.etag.clone(),
+                    error: if entry.error {
+                        Some(
+                            entry
+                                .message
+                                .clone()
+                                .unwrap_or_else(|| "upload failed".to_owned()),
+                        )
+                    } else {
+                        None
+                    },
+                },
+                None => BulkUploadResult {
+                    path: file.path,
+                    etag: None,
+                    error: Some("missing from bulk upload response".to_owned()),
+                },
+            })
+            .collect())
+    }
+
+    /// Upload a large file in chunks with the Nextcloud chunking v2 protocol
+    ///
+    /// The chunks are put into an upload folder which is then moved to `path`. See
+    /// `ChunkOptions::upload_id` for resuming an interrupted upload.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, reader))
+    )]
+    pub async fn put_chunked<R>(
+        &self,
+        path: &str,
+        mut reader: R,
+        options: ChunkOptions,
+    ) -> Result<PutResponse>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let (root, user) = self.nextcloud_root()?;
+        let uploads = self.with_host(format!("{}/uploads/{}", root, user));
+        let upload_id = options
+            .upload_id
+            .unwrap_or_else(|| format!("reqwest_dav-{}", uuid::Uuid::new_v4()));
+        let destination = self.url_for(path)?.to_string();
+
+        // chunks left by an interrupted upload, with their size
+        let mut uploaded = HashMap::new();
+        match uploads.list(&upload_id, Depth::Number(1)).await {
+            Ok(entities) => {
+                for entity in entities {
+                    if let ListEntity::File(file) = &entity {
+                        let chunk = file.rel_path(&uploads);
+                        let name = chunk.rsplit('/').next().unwrap_or_default().to_owned();
+                        uploaded.insert(name, file.content_length as u64);
+                    }
+                }
+            }
+            Err(Error::Decode(DecodeError::Server(crate::types::ServerError {
+                response_code: 404,
+                ..
+            }))) => {
+                let builder = uploads
+                    .start_request(Method::from_bytes(b"MKCOL")?, &upload_id)
+                    .await?
+                    .header("destination", &destination);
+                uploads.execute(builder).await?.dav2xx().await?;
+            }
+            Err(e) => return Err(e),
+        }
+
+        let mut total = 0u64;
+        for number in 1.. {
+            let mut chunk = Vec::with_capacity(options.chunk_size);
+            (&mut reader)
+                .take(options.chunk_size as u64)
+                .read_to_end(&mut chunk)
+                .await?;
+            if chunk.is_empty() && number > 1 {
+                break;
+            }
+            total += chunk.len() as u64;
+            let name = number.to_string();
+            if uploaded.get(&name) != Some(&(chunk.len() as u64)) {
+                let builder = uploads
+                    .start_request(Method::PUT, &format!("{}/{}", upload_id, name))
+                    .await?
+                    .header("destination", &destination)
+                    .body(chunk);
+                uploads.execute(builder).await?.dav2xx().await?;
+            }
+        }
+
+        let builder = uploads
+            .start_request(
+                Method::from_bytes(b"MOVE")?,
+                &format!("{}/.file", upload_id),
+            )
+            .await?
+            .header("destination", &destination)
+            .header("oc-total-length", total.to_string());
+        let response = uploads.execute(builder).await?.dav2xx().await?;
+        Ok(PutResponse::from_response(&response))
+    }
+
+    async fn upload_individually(&self, files: Vec<BulkFile>) -> Result<Vec<BulkUploadResult>> {
+        let mut results = vec![];
+        for file in files {
+            // a failed request is reported for its file like a failure status
+            let uploaded = match self.put_raw(&file.path, file.content).await {
+                Ok(response) => response.dav2xx().await,
+                Err(e) => Err(e),
+            };
+            let result = match uploaded {
+                Ok(response) => BulkUploadResult {
+                    path: file.path,
+                    etag: response
+                        .headers()
+                        .get("etag")
+                        .and_then(|etag| etag.to_str().ok())
+                        .map(|etag| etag.to_owned()),
+                    error: None,
+                },
+                Err(e) => {
+                    // the causes are not part of the message of the error itself
+                    let mut error = e.to_string();
+                    let mut source = std::error::Error::source(&e);
+                    while let Some(cause) = source {
+                        error = format!("{}: {}", error, cause);
+                        source = cause.source();
+                    }
+                    BulkUploadResult {
+                        path: file.path,
+                        etag: None,
+                        error: Some(error),
+                    }
+                }
+            };
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// Restore a previous version of a file, the current content becomes a version itself
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn restore_version(&self, version: &FileVersion) -> Result<()> {
+        let versions = self.nextcloud_endpoint("versions")?;
+        let from = format!("versions/{}/{}", version.file_id, version.version_id);
+        check_multi_status(
+            versions
+                .mv_raw(&from, "restore/target")
+                .await?
+                .dav2xx()
+                .await?,
+        )
+        .await
+    }
+
+    /// List the files and folders in the trash bin of the user
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list_trash(&self) -> Result<Vec<TrashItem>> {
+        let trashbin = self.nextcloud_endpoint("trashbin")?;
+        let request = PropfindRequest::props([
+            (NC_NAMESPACE, "trashbin-filename"),
+            (NC_NAMESPACE, "trashbin-original-location"),
+            (NC_NAMESPACE, "trashbin-deletion-time"),
+            ("DAV:", "getcontentlength"),
+            ("DAV:", "resourcetype"),
+        ]);
+        let responses = trashbin
+            .list_with("trash", Depth::Number(1), request)
+            .await?;
+        let mut items = vec![];
+        for response in responses {
+            let Some(prop) = response
+                .prop_stat
+                .into_iter()
+                .filter(|prop_stat| prop_stat.status.is_empty() || status_is_ok(&prop_stat.status))
+                .map(|prop_stat| prop_stat.prop)
+                .find(|prop| prop.other.contains_key(&nc("trashbin-filename")))
+            else {
+                // the trash bin itself
+                continue;
+            };
+            let path = trashbin.path_of(&response.href);
+            items.push(TrashItem {
+                id: path
+                    .trim_end_matches('/')
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_owned(),
+                href: response.href,
+                name: prop.other[&nc("trashbin-filename")].clone(),
+                original_location: prop
+                    .other
+                    .get(&nc("trashbin-original-location"))
+                    .cloned()
+                    .unwrap_or_default(),
+                deleted_at: prop
+                    .other
+                    .get(&nc("trashbin-deletion-time"))
+                    .and_then(|time| time.parse().ok())
+                    .map(DateTime::from_epoch_seconds),
+                content_length: prop.content_length,
+                is_folder: prop.resource_type.collection.is_some(),
+            });
+        }
+        Ok(items)
+    }
+
+    /// Restore an item of the trash bin to its original location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn restore_from_trash(&self, item: &TrashItem) -> Result<()> {
+        let trashbin = self.nextcloud_endpoint("trashbin")?;
+        let from = format!("trash/{}", item.id);
+        let to = format!("restore/{}", item.id);
+        check_multi_status(trashbin.mv_raw(&from, &to).await?.dav2xx().await?).await
+    }
+}
+
+/// The key of a Nextcloud property in `ListProp::other`.
+fn nc(name: &str) -> (String, String) {
+    (NC_NAMESPACE.to_owned(), name.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::nextcloud_cmd::{BulkFile, ChunkOptions, NextcloudSearch, PreviewOptions};
+    use crate::{Auth, ClientBuilder};
+    use wiremock::matchers::{
+        body_string_contains, header, header_exists, method, path, query_param,
+    };
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn chunked_upload_skips_uploaded_chunks() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/remote.php/dav/uploads/alice/up1"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:"><d:response><d:href>/remote.php/dav/uploads/alice/up1/</d:href><d:propstat><d:prop><d:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</d:getlastmodified><d:resourcetype><d:collection/></d:resourcetype></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response><d:response><d:href>/remote.php/dav/uploads/alice/up1/1</d:href><d:propstat><d:prop><d:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</d:getlastmodified><d:getcontentlength>10</d:getcontentlength><d:resourcetype/></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response></d:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/remote.php/dav/uploads/alice/up1/2"))
+            .and(header(
+                "destination",
+                format!("{}/remote.php/dav/files/alice/big.bin", mock_server.uri()).as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("MOVE"))
+            .and(path("/remote.php/dav/uploads/alice/up1/.file"))
+            .and(header("oc-total-length", "15"))
+            .respond_with(ResponseTemplate::new(201).insert_header("oc-fileid", "42"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/files/alice", mock_server.uri()))
+            .build()
+            .unwrap();
+        let response = client
+            .put_chunked(
+                "/big.bin",
+                &b"0123456789abcde"[..],
+                ChunkOptions {
+                    chunk_size: 10,
+                    upload_id: Some("up1".to_owned()),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.file_id.as_deref(), Some("42"));
+        mock_server.verify().await;
+    }
+
+    #[test]
+    fn nextcloud_root_from_files_host() {
+        let client = ClientBuilder::new()
+            .set_host("https://cloud.example.com/nc/remote.php/dav/files/alice/".to_owned())
+            .build()
+            .unwrap();
+        let (root, user) = client.nextcloud_root().unwrap();
+        assert_eq!(root, "https://cloud.example.com/nc/remote.php/dav");
+        assert_eq!(user, "alice");
+
+        let client = ClientBuilder::new()
+            .set_host("https://cloud.example.com/remote.php/webdav".to_owned())
+            .set_auth(Auth::Basic("bob".to_owned(), "secret".to_owned()))
+            .build()
+            .unwrap();
+        let (root, user) = client.nextcloud_root().unwrap();
+        assert_eq!(root, "https://cloud.example.com/remote.php/dav");
+        assert_eq!(user, "bob");
+    }
+
+    #[tokio::test]
+    async fn set_favorite_and_list_favorites() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPPATCH"))
+            .and(path("/remote.php/dav/files/alice/a.txt"))
+            .and(body_string_contains("<oc:favorite>1</oc:favorite>"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+                    <d:response>
+                        <d:href>/remote.php/dav/files/alice/a.txt</d:href>
+                        <d:propstat>
+                            <d:prop><oc:favorite/></d:prop>
+                            <d:status>HTTP/1.1 200 OK</d:status>
+                        </d:propstat>
+                    </d:response>
+                </d:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("REPORT"))
+            .and(path("/remote.php/dav/files/alice/"))
+            .and(body_string_contains("oc:filter-rules"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<d:multistatus xmlns:d="DAV:">
+                    <d:response>
+                        <d:href>/remote.php/dav/files/alice/a.txt</d:href>
+                        <d:propstat>
+                            <d:prop>
+                                <d:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</d:getlastmodified>
+                                <d:getcontentlength>3</d:getcontentlength>
+                                <d:resourcetype/>
+                            </d:prop>
+                            <d:status>HTTP/1.1 200 OK</d:status>
+                        </d:propstat>
+                    </d:response>
+                </d:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/files/alice", mock_server.uri()))
+            .build()
+            .unwrap();
+        client.set_favorite("a.txt", true).await.unwrap();
+        let favorites = client.list_favorites().await.unwrap();
+        assert_eq!(favorites.len(), 1);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn search_scope_is_relative_to_the_host_folder() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("SEARCH"))
+            .and(path("/remote.php/dav/"))
+            .and(body_string_contains(
+                "<d:href>/files/alice/docs/reports</d:href>",
+            ))
+            .respond_with(
+                ResponseTemplate::new(207)
+                    .set_body_string(r#"<d:multistatus xmlns:d="DAV:"></d:multistatus>"#),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!(
+                "{}/remote.php/dav/files/alice/docs",
+                mock_server.uri()
+            ))
+            .build()
+            .unwrap();
+        let search = NextcloudSearch::new().name_like("%.pdf");
+        let found = client.nextcloud_search("/reports/", &search).await.unwrap();
+        assert!(found.is_empty());
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn bulk_upload_reports_per_file_results() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/remote.php/dav/bulk"))
+            .and(body_string_contains("X-File-Path: /docs/a.txt"))
+            .and(body_string_contains("X-File-MD5: 5d41402abc4b2a76b9719d911017c592"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"/docs/a.txt":{"error":false,"etag":"abc"},"/docs/b.txt":{"error":true,"message":"quota"}}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!(
+                "{}/remote.php/dav/files/alice/docs",
+                mock_server.uri()
+            ))
+            .build()
+            .unwrap();
+        let results = client
+            .bulk_upload(vec![
+                BulkFile {
+                    path: "a.txt".to_owned(),
+                    content: b"hello".to_vec(),
+                    mtime: None,
+                },
+                BulkFile {
+                    path: "b.txt".to_owned(),
+                    content: b"world".to_vec(),
+                    mtime: None,
+                },
+            ])
+            .await
+            .unwrap();
+        assert_eq!(results[0].etag.as_deref(), Some("abc"));
+        assert!(results[0].error.is_none());
+        assert_eq!(results[1].error.as_deref(), Some("quota"));
+    }
+
+    #[tokio::test]
+    async fn bulk_upload_falls_back_to_put() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/remote.php/dav/files/alice/a.txt"))
+            .respond_with(ResponseTemplate::new(201).append_header("etag", "\"e1\""))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/files/alice", mock_server.uri()))
+            .build()
+            .unwrap();
+        let results = client
+            .bulk_upload(vec![BulkFile {
+                path: "a.txt".to_owned(),
+                content: b"hello".to_vec(),
+                mtime: None,
+            }])
+            .await
+            .unwrap();
+        assert_eq!(results[0].etag.as_deref(), Some("\"e1\""));
+        mock_server.verify().await;
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[tokio::test]
+    async fn bulk_upload_fallback_keeps_going_after_request_errors() {
+        use crate::types::fault_cmd::{Fault, FaultInjector, FaultRule};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/remote.php/dav/files/alice/b.txt"))
+            .respond_with(ResponseTemplate::new(201).append_header("etag", "\"e2\""))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let injector = FaultInjector::new();
+        injector.add(FaultRule::new(Fault::DropConnection).path_contains("a.txt"));
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/files/alice", mock_server.uri()))
+            .set_fault_injector(injector)
+            .build()
+            .unwrap();
+        let files = ["a.txt", "b.txt"].map(|path| BulkFile {
+            path: path.to_owned(),
+            content: b"hello".to_vec(),
+            mtime: None,
+        });
+        let results = client.bulk_upload(files.to_vec()).await.unwrap();
+        assert!(results[0].error.is_some());
+        assert_eq!(results[1].etag.as_deref(), Some("\"e2\""));
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn fetch_preview() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/index.php/core/preview"))
+            .and(query_param("fileId", "42"))
+            .and(query_param("x", "64"))
+            .and(header_exists("x-request-id"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("png", "image/png"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/files/alice", mock_server.uri()))
+            .set_request_id_header("x-request-id".parse().unwrap())
+            .build()
+            .unwrap();
+        let options = PreviewOptions {
+            width: 64,
+            height: 64,
+            keep_aspect: true,
+        };
+        let response = client.preview("42", &options).await.unwrap();
+        assert_eq!(response.bytes().await.unwrap().as_ref(), b"png");
+    }
+
+    #[tokio::test]
+    async fn list_and_restore_versions() {
+        let mock_server = MockServer::start().await;
+        let body = r#"<?xml version="1.0"?>
+        <d:multistatus xmlns:d="DAV:">
+            <d:response>
+                <d:href>/remote.php/dav/versions/alice/versions/42/</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <d:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</d:getlastmodified>
+                        <d:resourcetype><d:collection/></d:resourcetype>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+            <d:response>
+                <d:href>/remote.php/dav/versions/alice/versions/42/1554904800</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <d:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</d:getlastmodified>
+                        <d:getcontentlength>12</d:getcontentlength>
+                        <d:resourcetype/>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+        Mock::given(method("PROPFIND"))
+            .and(path("/remote.php/dav/versions/alice/versions/42"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(body))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("MOVE"))
+            .and(path(
+                "/remote.php/dav/versions/alice/versions/42/1554904800",
+            ))
+            .and(header(
+                "destination",
+                format!(
+                    "{}/remote.php/dav/versions/alice/restore/target",
+                    mock_server.uri()
+                )
+                .as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/files/alice", mock_server.uri()))
+            .build()
+            .unwrap();
+        let versions = client.list_versions("42").await.unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version_id, "1554904800");
+        assert_eq!(versions[0].content_length, 12);
+        client.restore_version(&versions[0]).await.unwrap();
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn list_and_restore_trash() {
+        let mock_server = MockServer::start().await;
+        let body = r#"<?xml version="1.0"?>
+        <d:multistatus xmlns:d="DAV:" xmlns:nc="http://nextcloud.org/ns">
+            <d:response>
+                <d:href>/remote.php/dav/trashbin/alice/trash/</d:href>
+                <d:propstat>
+                    <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+            <d:response>
+                <d:href>/remote.php/dav/trashbin/alice/trash/a%20b.txt.d1554904800</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <nc:trashbin-filename>a b.txt</nc:trashbin-filename>
+                        <nc:trashbin-original-location>Documents/a b.txt</nc:trashbin-original-location>
+                        <nc:trashbin-deletion-time>1554904800</nc:trashbin-deletion-time>
+                        <d:getcontentlength>12</d:getcontentlength>
+                        <d:resourcetype/>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+        Mock::given(method("PROPFIND"))
+            .and(path("/remote.php/dav/trashbin/alice/trash"))
+            .and(body_string_contains("trashbin-original-location"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(body))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("MOVE"))
+            .and(path(
+                "/remote.php/dav/trashbin/alice/trash/a%20b.txt.d1554904800",
+            ))
+            .and(header(
+                "destination",
+                format!(
+                    "{}/remote.php/dav/trashbin/alice/restore/a%20b.txt.d1554904800",
+                    mock_server.uri()
+                )
+                .as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/files/alice", mock_server.uri()))
+            .build()
+            .unwrap();
+        let items = client.list_trash().await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, "a b.txt.d1554904800");
+        assert_eq!(items[0].name, "a b.txt");
+        assert_eq!(items[0].original_location, "Documents/a b.txt");
+        assert_eq!(items[0].content_length, Some(12));
+        assert!(!items[0].is_folder);
+        client.restore_from_trash(&items[0]).await.unwrap();
+        mock_server.verify().await;
+    }
+}
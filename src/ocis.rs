@@ -0,0 +1,73 @@
+//! ownCloud Infinite Scale spaces discovery.
+
+use reqwest::Method;
+use url::Url;
+
+use crate::types::ocis_cmd::{DriveCollection, Space};
+use crate::types::{Dav2xx, Error, Result};
+use crate::Client;
+
+impl Client {
+    /// List the spaces (personal, project and share drives) the user can access
+    ///
+    /// Uses the Graph API of the server the host belongs to.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list_spaces(&self) -> Result<Vec<Space>> {
+        let mut url = Url::parse(&self.host)?;
+        url.set_path("/graph/v1.0/me/drives");
+        url.set_query(None);
+        let (client, path) = self.at_url(&url);
+        let builder = client.start_request(Method::GET, &path).await?;
+        let text = client
+            .execute(builder)
+            .await?
+            .dav2xx()
+            .await?
+            .text()
+            .await?;
+        // an invalid document surfaces as an `InvalidData` io error wrapping the serde_json one
+        let drives: DriveCollection =
+            serde_json::from_str(&text).map_err(|e| Error::Io(e.into()))?;
+        Ok(drives.value.into_iter().map(Space::from).collect())
+    }
+
+    /// A client rooted at the given space, sharing the agent and authentication of this one
+    pub fn space_client(&self, space: &Space) -> Client {
+        self.with_host(space.web_dav_url.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::ocis_cmd::SpaceType;
+    use crate::ClientBuilder;
+    use wiremock::matchers::{header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn discover_spaces_and_scope_client() {
+        let mock_server = MockServer::start().await;
+        let body = format!(
+            r#"{{"value":[{{"id":"p1","name":"Marketing","driveType":"project","root":{{"webDavUrl":"{}/dav/spaces/p1"}}}}]}}"#,
+            mock_server.uri()
+        );
+        Mock::given(method("GET"))
+            .and(path("/graph/v1.0/me/drives"))
+            .and(header_exists("x-request-id"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/files/alice", mock_server.uri()))
+            .set_request_id_header("x-request-id".parse().unwrap())
+            .build()
+            .unwrap();
+        let spaces = client.list_spaces().await.unwrap();
+        assert_eq!(spaces.len(), 1);
+        assert_eq!(spaces[0].space_type, SpaceType::Project);
+        let space = client.space_client(&spaces[0]);
+        assert_eq!(space.host, format!("{}/dav/spaces/p1", mock_server.uri()));
+    }
+}
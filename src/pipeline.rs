@@ -0,0 +1,473 @@
+//! The path every request of the client goes through once it has been built.
+
+use std::future::Future;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+use reqwest::{Request, RequestBuilder, Response};
+use tokio_util::sync::CancellationToken;
+use url::Url;
+use web_time::Instant;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::record::RecordedRequest;
+use crate::types::hooks::{RequestSummary, ResponseSummary};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::record_cmd::RecordMode;
+use crate::types::{Error, Result};
+use crate::Client;
+
+impl Client {
+    /// Urls whose listings a request may change, for invalidating the listing cache.
+    fn changed_urls(&self, request: &Request) -> Vec<String> {
+        if self.list_cache.is_none()
+            || matches!(
+                request.method().as_str(),
+                "GET" | "HEAD" | "OPTIONS" | "PROPFIND" | "REPORT" | "SEARCH"
+            )
+        {
+            return vec![];
+        }
+        let mut url = request.url().clone();
+        url.set_query(None);
+        let mut changed = vec![url.to_string()];
+        if let Some(destination) = request
+            .headers()
+            .get("destination")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| Url::parse(value).ok())
+        {
+            let mut destination = destination;
+            destination.set_query(None);
+            changed.push(destination.to_string());
+        }
+        changed
+    }
+
+    /// Send a request created by `start_request`
+    ///
+    /// The hooks of the client are called around the request. With the `tracing` feature every
+    /// request runs in a `dav_request` span recording the method, path, request id, status,
+    /// duration and transferred bytes. With the `metrics` feature the same numbers are reported
+    /// through the `metrics` facade.
+    pub(crate) async fn execute(&self, builder: RequestBuilder) -> Result<Response> {
+        let request = builder.build()?;
+        // checked before the hooks, a request that is never sent is neither reported nor counted
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(deadline) = self.deadline {
+            if deadline <= Instant::now() {
+                return Err(Error::DeadlineExceeded);
+            }
+        }
+        let request_id = self
+            .request_id_header
+            .as_ref()
+            .and_then(|header| request.headers().get(header))
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_owned());
+        let summary = RequestSummary {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            request_id: request_id.clone(),
+            bytes_sent: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| body.len() as u64),
+        };
+        if let Some(hook) = &self.hooks.on_request {
+            hook(&summary);
+        }
+        let changed = self.changed_urls(&request);
+        #[cfg(not(target_arch = "wasm32"))]
+        let method = request.method().clone();
+        let started = Instant::now();
+        // boxed, so callers awaiting several requests do not inline the whole pipeline
+        let sending = Box::pin(self.send(request, request_id.as_deref()));
+        #[cfg(target_arch = "wasm32")]
+        let result = sending.await;
+        #[cfg(not(target_arch = "wasm32"))]
+        let result = match self.slow_request_threshold {
+            Some(threshold) => {
+                let mut sending = sending;
+                match tokio::time::timeout(threshold, &mut sending).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        self.report_slow_request(&summary, started.elapsed());
+                        sending.await
+                    }
+                }
+            }
+            None => sending.await,
+        };
+        if let Some(cache) = &self.list_cache {
+            for url in &changed {
+                cache.invalidate(url);
+            }
+        }
+        let elapsed = started.elapsed();
+        let response = result.as_ref().ok();
+        let status = response.map(|response| response.status().as_u16());
+        let bytes_received = response.and_then(|response| response.content_length());
+        self.stats
+            .record(status, summary.bytes_sent, bytes_received, elapsed);
+        if let Some(hook) = &self.hooks.on_response {
+            hook(&ResponseSummary {
+                method: summary.method,
+                url: summary.url,
+                request_id: summary.request_id,
+                status,
+                elapsed,
+                bytes_received,
+            });
+        }
+        #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+        let mut response = result?;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            use crate::types::{RequestId, RequestMethod};
+
+            response.extensions_mut().insert(RequestMethod(method));
+            if let Some(id) = &request_id {
+                response.extensions_mut().insert(RequestId(id.clone()));
+            }
+        }
+        Ok(response)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn report_slow_request(&self, summary: &RequestSummary, elapsed: Duration) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            method = %summary.method,
+            url = %crate::redact::scrub(&summary.url),
+            elapsed_ms = elapsed.as_millis() as u64,
+            "slow request"
+        );
+        if let Some(hook) = &self.hooks.on_slow_request {
+            hook(summary, elapsed);
+        }
+    }
+
+    /// Run `future`, failing with `Error::Cancelled` as soon as the client is cancelled.
+    pub(crate) async fn until_cancelled<T>(
+        &self,
+        future: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        match &self.cancellation {
+            Some(token) => token
+                .run_until_cancelled(future)
+                .await
+                .unwrap_or(Err(Error::Cancelled)),
+            None => future.await,
+        }
+    }
+
+    /// Whether the cancellation token of the client was cancelled.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    async fn send(&self, request: Request, request_id: Option<&str>) -> Result<Response> {
+        let sending = self.until_cancelled(self.send_retrying(request, request_id));
+        #[cfg(target_arch = "wasm32")]
+        return sending.await;
+        #[cfg(not(target_arch = "wasm32"))]
+        match self.deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline.into(), sending)
+                .await
+                .unwrap_or(Err(Error::DeadlineExceeded)),
+            None => sending.await,
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn send_retrying(&self, request: Request, request_id: Option<&str>) -> Result<Response> {
+        self.send_authenticated(request, request_id).await
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) async fn send_now(
+        &self,
+        request: Request,
+        request_id: Option<&str>,
+    ) -> Result<Response> {
+        #[cfg(feature = "fault-injection")]
+        if let Some(injector) = &self.fault_injector {
+            use crate::types::fault_cmd::Fault;
+
+            match injector.fault_for(request.method(), request.url().as_str()) {
+                Some(Fault::Delay(delay)) => tokio::time::sleep(delay).await,
+                Some(Fault::Status(status)) => {
+                    let mut response = http::Response::new(String::new());
+                    *response.status_mut() = http::StatusCode::from_u16(status).map_err(|_| {
+                        Error::Decode(crate::types::DecodeError::FieldNotSupported(
+                            crate::types::FieldError {
+                                field: format!("injected status {}", status),
+                            },
+                        ))
+                    })?;
+                    return Ok(Response::from(response));
+                }
+                Some(Fault::DropConnection) => {
+                    return Err(Error::Io(std::io::Error::new(
+                        std::io::ErrorKind::ConnectionReset,
+                        "connection dropped by the fault injector",
+                    )))
+                }
+                None => {}
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let recorded = match &self.recorder {
+            Some(recorder) if recorder.mode == RecordMode::Replay => {
+                return recorder.answer(&RecordedRequest::of(&request));
+            }
+            Some(_) => Some(RecordedRequest::of(&request)),
+            None => None,
+        };
+        #[cfg(feature = "metrics")]
+        let measurement = metrics_support::Measurement::start(&request);
+        #[cfg(feature = "xml-trace")]
+        let method = request.method().clone();
+        #[cfg(feature = "xml-trace")]
+        xml_trace::trace_request(&request);
+        #[cfg(feature = "tracing")]
+        let response = traced(&self.agent, request, request_id).await;
+        #[cfg(not(feature = "tracing"))]
+        let response = self.agent.execute(request).await;
+        #[cfg(feature = "xml-trace")]
+        let response = match response {
+            Ok(response) => xml_trace::trace_response(&method, response).await,
+            Err(e) => Err(e),
+        };
+        #[cfg(feature = "metrics")]
+        measurement.finish(&response);
+        let response = response?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(recorder), Some(recorded)) = (&self.recorder, recorded) {
+            return recorder.capture(recorded, response).await;
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(feature = "tracing")]
+async fn traced(
+    agent: &reqwest::Client,
+    request: reqwest::Request,
+    request_id: Option<&str>,
+) -> Result<Response, reqwest::Error> {
+    use tracing::field::Empty;
+    use tracing::Instrument;
+
+    let span = tracing::debug_span!(
+        "dav_request",
+        method = %request.method(),
+        path = %request.url().path(),
+        request_id,
+        status = Empty,
+        duration_ms = Empty,
+        bytes_sent = request.body().and_then(|body| body.as_bytes()).map(|body| body.len()),
+        bytes_received = Empty,
+    );
+    let started = Instant::now();
+    let result = agent.execute(request).instrument(span.clone()).await;
+    span.record("duration_ms", started.elapsed().as_millis() as u64);
+    match &result {
+        Ok(response) => {
+            span.record("status", response.status().as_u16());
+            if let Some(length) = response.content_length() {
+                span.record("bytes_received", length);
+            }
+            span.in_scope(|| tracing::debug!("request finished"));
+        }
+        Err(e) => span.in_scope(|| tracing::warn!(error = %e, "request failed")),
+    }
+    result
+}
+
+/// Counters and histograms reported through the `metrics` facade.
+///
+/// * `reqwest_dav_requests_total` (counter, labels `method` and `status`, `error` for failed requests)
+/// * `reqwest_dav_request_duration_seconds` (histogram, label `method`)
+/// * `reqwest_dav_bytes_uploaded_total` and `reqwest_dav_bytes_downloaded_total` (counters)
+/// * `reqwest_dav_retries_total` (counter, label `method`), counted in `retry.rs`
+#[cfg(feature = "metrics")]
+mod metrics_support {
+    use reqwest::{Request, Response};
+    use web_time::Instant;
+
+    pub(super) struct Measurement {
+        method: String,
+        bytes_sent: Option<u64>,
+        started: Instant,
+    }
+
+    impl Measurement {
+        pub(super) fn start(request: &Request) -> Measurement {
+            Measurement {
+                method: request.method().to_string(),
+                bytes_sent: request
+                    .body()
+                    .and_then(|body| body.as_bytes())
+                    .map(|body| body.len() as u64),
+                started: Instant::now(),
+            }
+        }
+
+        pub(super) fn finish(self, response: &Result<Response, reqwest::Error>) {
+            let status = match response {
+                Ok(response) => response.status().as_u16().to_string(),
+                Err(_) => "error".to_owned(),
+            };
+            metrics::counter!(
+                "reqwest_dav_requests_total",
+                "method" => self.method.clone(),
+                "status" => status
+            )
+            .increment(1);
+            metrics::histogram!("reqwest_dav_request_duration_seconds", "method" => self.method)
+                .record(self.started.elapsed().as_secs_f64());
+            if let Some(sent) = self.bytes_sent {
+                metrics::counter!("reqwest_dav_bytes_uploaded_total").increment(sent);
+            }
+            if let Some(received) = response.as_ref().ok().and_then(|r| r.content_length()) {
+                metrics::counter!("reqwest_dav_bytes_downloaded_total").increment(received);
+            }
+        }
+    }
+}
+
+/// Logging of XML request and response bodies, for diagnosing incompatible servers.
+#[cfg(feature = "xml-trace")]
+mod xml_trace {
+    use reqwest::{Method, Request, Response, ResponseBuilderExt};
+
+    use crate::redact::scrub;
+
+    /// Bodies longer than this are cut off in the log.
+    const LIMIT: usize = 16 * 1024;
+    fn is_xml_method(method: &str) -> bool {
+        matches!(
+            method,
+            "PROPFIND" | "PROPPATCH" | "REPORT" | "SEARCH" | "LOCK" | "MKCALENDAR" | "ACL"
+        )
+    }
+
+    /// Whether bodies of `method` are logged, so nothing is read when the target is off.
+    fn is_traced(method: &Method) -> bool {
+        tracing::enabled!(target: "reqwest_dav::xml", tracing::Level::DEBUG)
+            && is_xml_method(method.as_str())
+    }
+
+    fn capped(body: &[u8]) -> String {
+        let text = String::from_utf8_lossy(&body[..body.len().min(LIMIT)]);
+        let mut text = scrub(&text);
+        if body.len() > LIMIT {
+            text.push_str(&format!("... ({} bytes total)", body.len()));
+        }
+        text
+    }
+
+    pub(super) fn trace_request(request: &Request) {
+        if !is_traced(request.method()) {
+            return;
+        }
+        if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            tracing::debug!(
+                target: "reqwest_dav::xml",
+                method = %request.method(),
+                url = %scrub(request.url().as_str()),
+                body = %capped(body),
+                "xml request body"
+            );
+        }
+    }
+
+    /// Log multistatus and other XML bodies, handing back an equivalent response.
+    ///
+    /// Only responses to the XML methods are read, a GET of an `.xml` file is streamed as is.
+    pub(super) async fn trace_response(
+        method: &Method,
+        response: Response,
+    ) -> Result<Response, reqwest::Error> {
+        if !is_traced(method) {
+            return Ok(response);
+        }
+        let is_xml = response.status().as_u16() == 207
+            || response
+                .headers()
+                .get("content-type")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.contains("xml"))
+                .unwrap_or(false);
+        if !is_xml {
+            return Ok(response);
+        }
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let url = response.url().clone();
+        let body = response.bytes().await?;
+        tracing::debug!(
+            target: "reqwest_dav::xml",
+            status = status.as_u16(),
+            url = %scrub(url.as_str()),
+            body = %capped(&body),
+            "xml response body"
+        );
+        let mut rebuilt = http::Response::builder()
+            .status(status)
+            .version(version)
+            .url(url)
+            .body(body)
+            .expect("status and version come from a valid response");
+        *rebuilt.headers_mut() = headers;
+        Ok(Response::from(rebuilt))
+    }
+}
+
+#[cfg(all(test, feature = "fault-injection"))]
+mod tests {
+    use crate::types::fault_cmd::{Fault, FaultInjector, FaultRule};
+    use crate::{ClientBuilder, Error};
+    use reqwest::Method;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn injected_faults_replace_responses() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let injector = FaultInjector::new();
+        injector.add(
+            FaultRule::new(Fault::Status(503))
+                .method(Method::GET)
+                .times(1),
+        );
+        injector.add(FaultRule::new(Fault::DropConnection).path_contains("dropped"));
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .set_fault_injector(injector)
+            .build()
+            .unwrap();
+        assert_eq!(client.get_raw("a").await.unwrap().status().as_u16(), 503);
+        assert_eq!(client.get_raw("a").await.unwrap().status().as_u16(), 200);
+        let err = client.get_raw("dropped").await.unwrap_err();
+        assert!(
+            matches!(&err, Error::Io(err) if err.kind() == std::io::ErrorKind::ConnectionReset)
+        );
+        assert!(err.is_retryable());
+        mock_server.verify().await;
+    }
+}
@@ -0,0 +1,10 @@
+//! The types and traits needed by most users of the crate.
+//!
+//! ```
+//! use reqwest_dav::prelude::*;
+//! ```
+
+pub use crate::types::list_cmd::{ListEntity, ListFile, ListFolder};
+pub use crate::types::timestamp::{DateTime, DavDateTime};
+pub use crate::types::{Dav2xx, DavClient, DecodeError, Error, Result, TokenProvider};
+pub use crate::{Auth, Client, ClientBuilder, Depth};
@@ -0,0 +1,122 @@
+//! Principals (RFC 3744) of the users and groups of the server.
+
+use reqwest::Response;
+use url::Url;
+
+use crate::types::principals_cmd::{parse_principals, Principal, PrincipalQuery, PRINCIPAL_PROPS};
+use crate::types::propfind_cmd::PropfindRequest;
+use crate::types::{Dav2xx, DecodeError, Error, FieldError, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    /// The principal of the authenticated user, from the `current-user-principal` of the host
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn current_user_principal(&self) -> Result<Principal> {
+        let host = Url::parse(&self.host)?;
+        let url = self
+            .property_urls(&host, "DAV:", "current-user-principal")
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| not_found("current-user-principal"))?;
+        let (client, path) = self.at_url(&url);
+        let request = PropfindRequest::props(PRINCIPAL_PROPS);
+        let response = client
+            .list_with_raw(&path, Depth::Number(0), &request)
+            .await?
+            .dav2xx()
+            .await?;
+        parse_principals(&response.text().await?)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| not_found("response"))
+    }
+
+    pub async fn principal_search_raw(&self, query: &PrincipalQuery) -> Result<Response> {
+        self.report_raw("", Depth::Number(0), &query.to_xml()).await
+    }
+
+    /// Find the users and groups matching `query` with a `principal-property-search` REPORT
+    ///
+    /// All principal collections of the server are searched, as for picking who to share with.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn principal_search(&self, query: PrincipalQuery) -> Result<Vec<Principal>> {
+        let response = self.principal_search_raw(&query).await?.dav2xx().await?;
+        parse_principals(&response.text().await?)
+    }
+}
+
+fn not_found(field: &str) -> Error {
+    Error::Decode(DecodeError::FieldNotFound(FieldError {
+        field: field.to_owned(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::principals_cmd::PrincipalQuery;
+    use crate::ClientBuilder;
+
+    fn principal(href: &str, prop: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:s="http://sabredav.org/ns"><d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response></d:multistatus>"#,
+            href, prop
+        )
+    }
+
+    #[tokio::test]
+    async fn current_user_principal_and_search() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/remote.php/dav/"))
+            .and(body_string_contains("current-user-principal"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(principal(
+                "/remote.php/dav/",
+                "<d:current-user-principal><d:href>/remote.php/dav/principals/users/alice/</d:href></d:current-user-principal>",
+            )))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/remote.php/dav/principals/users/alice/"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(principal(
+                "/remote.php/dav/principals/users/alice/",
+                "<d:displayname>Alice</d:displayname><s:email-address>alice@example.com</s:email-address>",
+            )))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("REPORT"))
+            .and(path("/remote.php/dav/"))
+            .and(body_string_contains("<D:match>bo</D:match>"))
+            .and(body_string_contains(
+                "<D:apply-to-principal-collection-set/>",
+            ))
+            .respond_with(ResponseTemplate::new(207).set_body_string(principal(
+                "/remote.php/dav/principals/users/bob/",
+                "<d:displayname>Bob</d:displayname>",
+            )))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/remote.php/dav/", mock_server.uri()))
+            .build()
+            .unwrap();
+        let me = client.current_user_principal().await.unwrap();
+        assert_eq!(me.href, "/remote.php/dav/principals/users/alice/");
+        assert_eq!(me.display_name.as_deref(), Some("Alice"));
+        assert_eq!(me.email.as_deref(), Some("alice@example.com"));
+
+        let found = client
+            .principal_search(PrincipalQuery::new("bo"))
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].href, "/remote.php/dav/principals/users/bob/");
+        assert_eq!(found[0].display_name.as_deref(), Some("Bob"));
+        mock_server.verify().await;
+    }
+}
@@ -0,0 +1,115 @@
+//! Setting and removing dead properties.
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, Response, StatusCode};
+
+use crate::types::proppatch_cmd::{PropOutcome, PropPatch, PropPatchMultiStatus};
+use crate::types::timestamp::{DateTime, DavDateTime};
+use crate::types::{Dav2xx, Result};
+use crate::Client;
+
+impl Client {
+    pub async fn proppatch_raw(&self, path: &str, patch: &PropPatch) -> Result<Response> {
+        let builder = self
+            .start_request(Method::from_bytes(b"PROPPATCH").unwrap(), path)
+            .await?
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(patch.to_xml());
+        self.execute(builder).await
+    }
+
+    /// Set and remove properties of a file or folder
+    ///
+    /// The changes are atomic: when one property fails the others are reported with
+    /// `424 Failed Dependency`. Failed properties are returned as outcomes, not as an error.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn proppatch(&self, path: &str, patch: &PropPatch) -> Result<Vec<PropOutcome>> {
+        let response = self.proppatch_raw(path, patch).await?.dav2xx().await?;
+        if response.status() != StatusCode::MULTI_STATUS {
+            let outcomes = patch.properties().map(|property| PropOutcome {
+                property: property.clone(),
+                status: response.status().as_u16(),
+                description: None,
+            });
+            return Ok(outcomes.collect());
+        }
+        let multi_status: PropPatchMultiStatus = serde_xml_rs::from_str(&response.text().await?)?;
+        Ok(multi_status.outcomes(patch))
+    }
+
+    /// Set the modification time of a file, returning whether the server took it
+    ///
+    /// Tries the `lastmodified` property, then the `Win32LastModifiedTime` of IIS. Servers which
+    /// keep their own time refuse both. Nextcloud and ownCloud take the time on upload instead,
+    /// see `PutOptions::mtime`.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn set_mtime(&self, path: &str, mtime: DateTime) -> Result<bool> {
+        let value = httpdate::fmt_http_date(mtime.to_system_time());
+        for (namespace, name) in [
+            ("DAV:", "lastmodified"),
+            ("urn:schemas-microsoft-com:", "Win32LastModifiedTime"),
+        ] {
+            let patch = PropPatch::new().set(namespace, name, value.clone());
+            if self
+                .proppatch(path, &patch)
+                .await?
+                .iter()
+                .all(PropOutcome::is_ok)
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::proppatch_cmd::PropPatch;
+    use crate::ClientBuilder;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn proppatch_reports_each_property() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPPATCH"))
+            .and(path("/dav/report.txt"))
+            .and(body_string_contains("<ns0:author>Alice</ns0:author>"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+                <D:multistatus xmlns:D="DAV:" xmlns:Z="http://example.com/ns">
+                    <D:response>
+                        <D:href>/dav/report.txt</D:href>
+                        <D:propstat><D:prop><Z:author/></D:prop><D:status>HTTP/1.1 424 Failed Dependency</D:status></D:propstat>
+                        <D:propstat><D:prop><Z:owner/></D:prop><D:status>HTTP/1.1 409 Conflict</D:status></D:propstat>
+                        <D:responsedescription>Owner cannot be removed</D:responsedescription>
+                    </D:response>
+                </D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let patch = PropPatch::new()
+            .set("http://example.com/ns", "author", "Alice")
+            .remove("http://example.com/ns", "owner");
+        let outcomes = client.proppatch("/report.txt", &patch).await.unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0].status, 424);
+        assert_eq!(outcomes[1].status, 409);
+        assert_eq!(
+            outcomes[1].description.as_deref(),
+            Some("Owner cannot be removed")
+        );
+        assert!(!outcomes[1].is_ok());
+    }
+}
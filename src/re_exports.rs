@@ -18,6 +18,7 @@ pub mod serde_xml_rs {
     pub use serde_xml_rs::*;
 }
 
+#[cfg(feature = "json")]
 pub mod serde_json {
     pub use serde_json::*;
 }
@@ -29,3 +30,7 @@ pub mod url {
 pub mod tokio {
     pub use tokio::*;
 }
+
+pub mod tokio_util {
+    pub use tokio_util::*;
+}
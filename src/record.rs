@@ -0,0 +1,191 @@
+//! Recording and replaying the traffic of a client.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Request, Response};
+
+use crate::redact::scrub;
+use crate::types::record_cmd::{Interaction, Recorder};
+use crate::types::{DecodeError, Error, FieldError, Result};
+
+/// Headers which are never written to a fixture.
+const SECRET_HEADERS: [&str; 3] = ["authorization", "cookie", "set-cookie"];
+
+/// The part of a request identifying it in a recording.
+pub(crate) struct RecordedRequest {
+    method: String,
+    url: String,
+    body: Option<String>,
+}
+
+impl RecordedRequest {
+    pub(crate) fn of(request: &Request) -> RecordedRequest {
+        let url = request.url();
+        let url = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_owned(),
+        };
+        RecordedRequest {
+            method: request.method().to_string(),
+            url: scrub(&url),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|body| scrub(&String::from_utf8_lossy(body))),
+        }
+    }
+}
+
+/// The response body of `interaction`, decoded from base64 when it is binary.
+fn recorded_body(interaction: &Interaction) -> Result<Bytes> {
+    if !interaction.binary {
+        return Ok(Bytes::from(interaction.body.clone()));
+    }
+    let body = STANDARD.decode(&interaction.body).map_err(|_| {
+        Error::Decode(DecodeError::FieldNotSupported(FieldError {
+            field: format!(
+                "recorded body of {} {}",
+                interaction.method, interaction.url
+            ),
+        }))
+    })?;
+    Ok(Bytes::from(body))
+}
+
+fn to_response(interaction: &Interaction, body: Bytes) -> Result<Response> {
+    let mut response = http::Response::new(body);
+    *response.status_mut() = http::StatusCode::from_u16(interaction.status).map_err(|_| {
+        Error::Decode(DecodeError::FieldNotSupported(FieldError {
+            field: format!("recorded status {}", interaction.status),
+        }))
+    })?;
+    for (name, value) in &interaction.headers {
+        response.headers_mut().append(
+            HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+                Error::Decode(DecodeError::FieldNotSupported(FieldError {
+                    field: format!("recorded header {}", name),
+                }))
+            })?,
+            HeaderValue::from_str(value)?,
+        );
+    }
+    Ok(Response::from(response))
+}
+
+impl Recorder {
+    /// Answer a request with the first unused interaction matching its method and url.
+    pub(crate) fn answer(&self, request: &RecordedRequest) -> Result<Response> {
+        let interactions = self.interactions.lock().unwrap();
+        let mut replayed = self.replayed.lock().unwrap();
+        let index = interactions
+            .iter()
+            .enumerate()
+            .position(|(index, interaction)| {
+                !replayed[index]
+                    && interaction.method == request.method
+                    && interaction.url == request.url
+            })
+            .ok_or_else(|| {
+                Error::Decode(DecodeError::FieldNotFound(FieldError {
+                    field: format!("recorded response for {} {}", request.method, request.url),
+                }))
+            })?;
+        replayed[index] = true;
+        let interaction = &interactions[index];
+        to_response(interaction, recorded_body(interaction)?)
+    }
+
+    /// Keep the exchange, handing back a response with the same status, headers and body.
+    pub(crate) async fn capture(
+        &self,
+        request: RecordedRequest,
+        response: Response,
+    ) -> Result<Response> {
+        let interaction = Interaction {
+            method: request.method,
+            url: request.url,
+            request_body: request.body,
+            status: response.status().as_u16(),
+            headers: response
+                .headers()
+                .iter()
+                .filter(|(name, _)| !SECRET_HEADERS.contains(&name.as_str()))
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_owned()))
+                })
+                .collect(),
+            body: String::new(),
+            binary: false,
+        };
+        let body = response.bytes().await?;
+        let interaction = match std::str::from_utf8(&body) {
+            Ok(text) => Interaction {
+                body: scrub(text),
+                ..interaction
+            },
+            Err(_) => Interaction {
+                body: STANDARD.encode(&body),
+                binary: true,
+                ..interaction
+            },
+        };
+        let replayed = to_response(&interaction, body)?;
+        self.interactions.lock().unwrap().push(interaction);
+        self.replayed.lock().unwrap().push(true);
+        Ok(replayed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::record_cmd::Recorder;
+    use crate::ClientBuilder;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn replays_recorded_responses_offline() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/a.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("content"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let binary = vec![0xff, 0x00, 0x80, 0xfe];
+        Mock::given(method("GET"))
+            .and(path("/a.bin"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(binary.clone()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let recorder = Recorder::record();
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/?token=secret", mock_server.uri()))
+            .set_recorder(recorder.clone())
+            .build()
+            .unwrap();
+        let body = client.get("a.txt").await.unwrap().text().await.unwrap();
+        assert_eq!(body, "content");
+        let body = client.get("a.bin").await.unwrap().bytes().await.unwrap();
+        assert_eq!(body, binary);
+        let interactions = recorder.interactions();
+        assert_eq!(interactions[0].url, "/a.txt?token=***");
+        assert!(!interactions[0].binary && interactions[1].binary);
+
+        let client = ClientBuilder::new()
+            .set_host("http://offline.invalid/?token=other".to_owned())
+            .set_recorder(Recorder::replay(interactions))
+            .build()
+            .unwrap();
+        let body = client.get("a.txt").await.unwrap().text().await.unwrap();
+        assert_eq!(body, "content");
+        let body = client.get("a.bin").await.unwrap().bytes().await.unwrap();
+        assert_eq!(body, binary);
+        assert!(client.get("a.txt").await.is_err());
+        mock_server.verify().await;
+    }
+}
@@ -0,0 +1,340 @@
+//! Recursive operations built on top of depth 1 requests.
+
+use std::collections::VecDeque;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+#[cfg(not(target_arch = "wasm32"))]
+use web_time::Instant;
+
+use crate::types::list_cmd::{ListEntity, ListReport};
+use crate::types::{DecodeError, Error, Result, StatusMismatchedError};
+use crate::{Client, Depth};
+
+impl Client {
+    /// Translate an href returned by the server into a path relative to the client host.
+    pub(crate) fn href_to_path(&self, href: &str) -> String {
+        let href_path = match Url::parse(href) {
+            Ok(url) => url.path().to_owned(),
+            Err(_) => href.to_owned(),
+        };
+        let base_path = match Url::parse(&self.host) {
+            Ok(url) => url.path().to_owned(),
+            Err(_) => String::new(),
+        };
+        let base_path = base_path.trim_end_matches('/');
+        href_path
+            .strip_prefix(base_path)
+            .unwrap_or(&href_path)
+            .trim_matches('/')
+            .to_owned()
+    }
+
+    /// Emulate a `Depth: infinity` listing with one depth 1 PROPFIND per collection.
+    ///
+    /// Used for servers which refuse infinite depth. The result has the same shape as a single
+    /// infinite listing: the requested resource first, followed by all of its descendants.
+    pub(crate) async fn list_breadth_first(&self, path: &str) -> Result<Vec<ListEntity>> {
+        Ok(self.crawl(path).await?.entities)
+    }
+
+    /// List a folder and all of its descendants
+    ///
+    /// Sends a `Depth: infinity` PROPFIND, falling back to one depth 1 PROPFIND per collection
+    /// when the server refuses it with `propfind-finite-depth` or the `finite_depth` quirk is set.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list_recursive(&self, path: &str) -> Result<Vec<ListEntity>> {
+        if self.quirks.finite_depth {
+            return self.list_breadth_first(path).await;
+        }
+        let response = self.list_raw(path, Depth::Infinity).await?;
+        if response.status() == StatusCode::FORBIDDEN {
+            let error = StatusMismatchedError::new(&response, 207);
+            if response.text().await?.contains("propfind-finite-depth") {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(path, "infinite depth refused, listing breadth first");
+                return self.list_breadth_first(path).await;
+            }
+            return Err(Error::Decode(DecodeError::StatusMismatched(error)));
+        }
+        self.list_responses(response)
+            .await?
+            .into_iter()
+            .map(ListEntity::try_from)
+            .collect()
+    }
+
+    /// List a folder and all of its descendants, stopping early when `token` is cancelled
+    ///
+    /// The listing is done with one depth 1 PROPFIND per collection. Once cancelled no further
+    /// request is issued and the report holds the entities listed so far.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, token))
+    )]
+    pub async fn list_cancellable(
+        &self,
+        path: &str,
+        token: CancellationToken,
+    ) -> Result<ListReport> {
+        self.with_cancellation(token).crawl(path).await
+    }
+
+    /// List a folder and all of its descendants within `budget`
+    ///
+    /// Works like `list_cancellable`, returning what was listed when the time is up.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn list_within(&self, path: &str, budget: Duration) -> Result<ListReport> {
+        self.with_deadline(Instant::now() + budget)
+            .crawl(path)
+            .await
+    }
+
+    async fn crawl(&self, path: &str) -> Result<ListReport> {
+        let client = self.operation();
+        let mut result = vec![];
+        let mut pending = VecDeque::from([path.trim_matches('/').to_owned()]);
+        let mut is_root = true;
+        while let Some(current) = pending.pop_front() {
+            let responses = match client.list_rsp(&current, Depth::Number(1)).await {
+                Ok(responses) => responses,
+                Err(Error::Cancelled | Error::DeadlineExceeded) => {
+                    pending.push_front(current);
+                    return Ok(ListReport {
+                        entities: result,
+                        pending: pending.into(),
+                        cancelled: true,
+                    });
+                }
+                Err(e) => return Err(e),
+            };
+            for response in responses {
+                let entity = ListEntity::try_from(response)?;
+                let (href, is_folder) = match &entity {
+                    ListEntity::File(file) => (&file.href, false),
+                    ListEntity::Folder(folder) => (&folder.href, true),
+                };
+                let entity_path = client.path_of(href).trim_matches('/').to_owned();
+                if entity_path == current {
+                    if is_root {
+                        result.push(entity);
+                    }
+                    continue;
+                }
+                if is_folder {
+                    pending.push_back(entity_path);
+                }
+                result.push(entity);
+            }
+            is_root = false;
+        }
+        Ok(ListReport {
+            entities: result,
+            pending: vec![],
+            cancelled: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::types::list_cmd::ListEntity;
+    use crate::types::server_cmd::ServerQuirks;
+    use crate::{ClientBuilder, Depth};
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn multistatus(entries: &[(&str, bool)]) -> String {
+        let mut body =
+            r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#.to_owned();
+        for (href, is_folder) in entries {
+            body.push_str(&format!(
+                r#"<D:response><D:href>{}</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:resourcetype>{}</D:resourcetype></D:prop></D:propstat></D:response>"#,
+                href,
+                if *is_folder { "<D:collection/>" } else { "" }
+            ));
+        }
+        body.push_str("</D:multistatus>");
+        body
+    }
+
+    #[tokio::test]
+    async fn finite_depth_quirk_crawls_with_depth_one() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/"))
+            .and(header("depth", "1"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(multistatus(&[
+                ("/dav/", true),
+                ("/dav/a.txt", false),
+                ("/dav/sub/", true),
+            ])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/sub"))
+            .and(header("depth", "1"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(multistatus(&[
+                ("/dav/sub/", true),
+                ("/dav/sub/b.txt", false),
+            ])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .set_quirks(ServerQuirks {
+                finite_depth: true,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        let entities = client.list("/", Depth::Infinity).await.unwrap();
+        let hrefs: Vec<&str> = entities
+            .iter()
+            .map(|entity| match entity {
+                ListEntity::File(file) => file.href.as_str(),
+                ListEntity::Folder(folder) => folder.href.as_str(),
+            })
+            .collect();
+        assert_eq!(
+            hrefs,
+            vec!["/dav/", "/dav/a.txt", "/dav/sub/", "/dav/sub/b.txt"]
+        );
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn refused_infinite_depth_falls_back_to_crawl() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/"))
+            .and(header("depth", "infinity"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?><D:error xmlns:D="DAV:"><D:propfind-finite-depth/></D:error>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/"))
+            .and(header("depth", "1"))
+            .respond_with(
+                ResponseTemplate::new(207)
+                    .set_body_string(multistatus(&[("/dav/", true), ("/dav/a.txt", false)])),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let entities = client.list_recursive("/").await.unwrap();
+        assert_eq!(entities.len(), 2);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn cancelled_listing_returns_partial_report() {
+        let mock_server = MockServer::start().await;
+        let token = tokio_util::sync::CancellationToken::new();
+        let cancel = token.clone();
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/"))
+            .respond_with(
+                ResponseTemplate::new(207)
+                    .set_body_string(multistatus(&[("/dav/", true), ("/dav/sub/", true)])),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counted = requests.clone();
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .on_request(move |_| {
+                counted.fetch_add(1, Ordering::SeqCst);
+            })
+            .on_response(move |_| cancel.cancel())
+            .build()
+            .unwrap();
+        let report = client.list_cancellable("/", token).await.unwrap();
+        assert!(report.cancelled);
+        assert_eq!(report.entities.len(), 2);
+        assert_eq!(report.pending, vec!["sub".to_owned()]);
+        // the cancelled request is neither reported to the hooks nor counted
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(client.stats().requests, 1);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn listing_within_budget_returns_partial_report() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/"))
+            .respond_with(
+                ResponseTemplate::new(207)
+                    .set_body_string(multistatus(&[("/dav/", true), ("/dav/sub/", true)])),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/sub"))
+            .respond_with(ResponseTemplate::new(207).set_delay(std::time::Duration::from_secs(5)))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let report = client
+            .list_within("/", std::time::Duration::from_millis(300))
+            .await
+            .unwrap();
+        assert!(report.cancelled);
+        assert_eq!(report.entities.len(), 2);
+        assert_eq!(report.pending, vec!["sub".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn expired_budget_sends_nothing() {
+        let mock_server = MockServer::start().await;
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counted = requests.clone();
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .on_request(move |_| {
+                counted.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+        let report = client
+            .list_within("/", std::time::Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(report.cancelled);
+        assert!(report.entities.is_empty());
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+        assert_eq!(client.stats().requests, 0);
+    }
+}
@@ -0,0 +1,189 @@
+//! REPORT requests (RFC 3253) with a body given by the caller.
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, Response};
+
+use crate::types::list_cmd::ListMultiStatus;
+use crate::types::report_cmd::{
+    expand_property_body, parse_expanded, ExpandProperty, ExpandedResponse, Report,
+};
+use crate::types::{Dav2xx, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    pub async fn report_raw(&self, path: &str, depth: Depth, body: &str) -> Result<Response> {
+        let builder = self
+            .start_request(Method::from_bytes(b"REPORT")?, path)
+            .await?
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .header(
+                "depth",
+                match depth {
+                    Depth::Number(value) => value.to_string(),
+                    Depth::Infinity => "infinity".to_owned(),
+                },
+            )
+            .body(body.to_owned());
+        self.execute(builder).await
+    }
+
+    /// Send a REPORT the crate has no method for and parse the multistatus it returns
+    ///
+    /// Properties without a field of their own are kept as text in `ListProp::other`.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn report(&self, path: &str, report: Report) -> Result<ListMultiStatus> {
+        let response = self
+            .report_raw(path, report.depth, &report.body)
+            .await?
+            .dav2xx()
+            .await?;
+        self.parse_multi_status(&response.text().await?)
+    }
+
+    pub async fn expand_property_raw(
+        &self,
+        path: &str,
+        spec: &[ExpandProperty],
+    ) -> Result<Response> {
+        self.report_raw(path, Depth::Number(0), &expand_property_body(spec))
+            .await
+    }
+
+    /// Get properties of a resource together with those of the resources they link to, with the
+    /// expand-property REPORT
+    ///
+    /// Replaces a PROPFIND per linked resource, like the display names of the `group-member-set`
+    /// of a group. Hrefs the server could not expand are left in `ExpandedProperty::hrefs`.
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn expand_property(
+        &self,
+        path: &str,
+        spec: Vec<ExpandProperty>,
+    ) -> Result<Vec<ExpandedResponse>> {
+        let response = self
+            .expand_property_raw(path, &spec)
+            .await?
+            .dav2xx()
+            .await?;
+        parse_expanded(&response.text().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::report_cmd::{ExpandProperty, Report};
+    use crate::{ClientBuilder, Depth};
+
+    #[tokio::test]
+    async fn report_parses_the_multistatus() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("REPORT"))
+            .and(path("/dav/principals"))
+            .and(header("depth", "1"))
+            .and(header("content-type", "application/xml; charset=utf-8"))
+            .and(body_string_contains("<V:vendor-report"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:" xmlns:V="urn:vendor"><D:response><D:href>/dav/principals/alice/</D:href><D:propstat><D:prop><D:displayname>Alice</D:displayname><V:quota>12</V:quota></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let report =
+            Report::new(r#"<V:vendor-report xmlns:V="urn:vendor"/>"#).depth(Depth::Number(1));
+        let multi_status = client.report("/principals", report).await.unwrap();
+        assert_eq!(multi_status.responses.len(), 1);
+        let response = &multi_status.responses[0];
+        assert_eq!(response.href, "/dav/principals/alice/");
+        let prop = &response.prop_stat[0].prop;
+        assert_eq!(prop.display_name.as_deref(), Some("Alice"));
+        assert_eq!(
+            prop.other
+                .get(&("urn:vendor".to_owned(), "quota".to_owned()))
+                .map(String::as_str),
+            Some("12")
+        );
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn expand_property_nests_linked_resources() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("REPORT"))
+            .and(path("/dav/principals/team/"))
+            .and(header("depth", "0"))
+            .and(body_string_contains(
+                r#"<D:expand-property xmlns:D="DAV:"><D:property name="group-member-set" namespace="DAV:"><D:property name="displayname" namespace="DAV:"/></D:property><D:property name="displayname" namespace="DAV:"/></D:expand-property>"#,
+            ))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+<D:multistatus xmlns:D="DAV:">
+  <D:response>
+    <D:href>/dav/principals/team/</D:href>
+    <D:propstat>
+      <D:prop>
+        <D:displayname>Team</D:displayname>
+        <D:group-member-set>
+          <D:response>
+            <D:href>/dav/principals/alice/</D:href>
+            <D:propstat><D:prop><D:displayname>Alice</D:displayname></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat>
+          </D:response>
+          <D:href>/dav/principals/gone/</D:href>
+        </D:group-member-set>
+      </D:prop>
+      <D:status>HTTP/1.1 200 OK</D:status>
+    </D:propstat>
+    <D:propstat>
+      <D:prop><D:owner/></D:prop>
+      <D:status>HTTP/1.1 404 Not Found</D:status>
+    </D:propstat>
+  </D:response>
+</D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let spec = vec![
+            ExpandProperty::new("DAV:", "group-member-set")
+                .expand(ExpandProperty::new("DAV:", "displayname")),
+            ExpandProperty::new("DAV:", "displayname"),
+        ];
+        let responses = client
+            .expand_property("/principals/team/", spec)
+            .await
+            .unwrap();
+        assert_eq!(responses.len(), 1);
+        let team = &responses[0];
+        assert_eq!(team.href, "/dav/principals/team/");
+        assert_eq!(team.prop("DAV:", "displayname").unwrap().text, "Team");
+        assert!(team.prop("DAV:", "owner").is_none());
+        let members = team.prop("DAV:", "group-member-set").unwrap();
+        assert_eq!(members.hrefs, vec!["/dav/principals/gone/"]);
+        assert_eq!(members.responses.len(), 1);
+        assert_eq!(members.responses[0].href, "/dav/principals/alice/");
+        assert_eq!(
+            members.responses[0]
+                .prop("DAV:", "displayname")
+                .unwrap()
+                .text,
+            "Alice"
+        );
+        mock_server.verify().await;
+    }
+}
@@ -0,0 +1,163 @@
+//! Automatic retries of failed requests, see `RetryPolicy`.
+
+use reqwest::{Request, Response};
+
+use crate::types::retry_cmd::{is_retryable_status, retry_after};
+use crate::types::Result;
+use crate::Client;
+
+impl Client {
+    /// Send a request, sending it again as allowed by the retry policy of the client.
+    pub(crate) async fn send_retrying(
+        &self,
+        request: Request,
+        request_id: Option<&str>,
+    ) -> Result<Response> {
+        let policy = match &self.retry_policy {
+            Some(policy) if policy.applies_to(request.method()) => policy,
+            _ => return self.send_authenticated(request, request_id).await,
+        };
+        let mut request = request;
+        let mut retry = 1;
+        loop {
+            let next = match retry < policy.max_attempts {
+                true => request.try_clone(),
+                false => None,
+            };
+            let Some(next) = next else {
+                return self.send_authenticated(request, request_id).await;
+            };
+            let result = self.send_authenticated(request, request_id).await;
+            let delay = match &result {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    let retry_after = retry_after(response);
+                    if policy.honor_retry_after
+                        && retry_after.is_some_and(|wait| wait > policy.max_backoff)
+                    {
+                        return result;
+                    }
+                    policy.delay(retry, retry_after)
+                }
+                Err(e) if e.is_retryable() => policy.delay(retry, None),
+                _ => return result,
+            };
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                method = %next.method(),
+                path = %next.url().path(),
+                retry,
+                delay_ms = delay.as_millis() as u64,
+                "retrying request"
+            );
+            #[cfg(feature = "metrics")]
+            metrics::counter!("reqwest_dav_retries_total", "method" => next.method().to_string())
+                .increment(1);
+            tokio::time::sleep(delay).await;
+            request = next;
+            retry += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::retry_cmd::RetryPolicy;
+    use crate::ClientBuilder;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            jitter: false,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn unavailable_server_is_retried() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dav/a.txt"))
+            .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "0"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/a.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("data"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .set_retry_policy(policy())
+            .build()
+            .unwrap();
+        let body = client.get("/a.txt").await.unwrap().text().await.unwrap();
+        assert_eq!(body, "data");
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn put_is_not_retried_by_default() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/dav/a.txt"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .set_retry_policy(policy())
+            .build()
+            .unwrap();
+        assert!(client.put("/a.txt", "data").await.is_err());
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn long_retry_after_is_left_to_the_caller() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dav/a.txt"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "120"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let later = std::time::SystemTime::now() + Duration::from_secs(3600);
+        Mock::given(method("GET"))
+            .and(path("/dav/b.txt"))
+            .respond_with(
+                ResponseTemplate::new(503)
+                    .insert_header("retry-after", httpdate::fmt_http_date(later).as_str()),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .set_retry_policy(policy())
+            .build()
+            .unwrap();
+        let err = client.get("/a.txt").await.unwrap_err();
+        assert_eq!(err.status(), Some(429));
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(120)));
+        let wait = client
+            .get("/b.txt")
+            .await
+            .unwrap_err()
+            .retry_after()
+            .unwrap();
+        assert!(wait > Duration::from_secs(3500) && wait <= Duration::from_secs(3600));
+        mock_server.verify().await;
+    }
+}
@@ -0,0 +1,78 @@
+//! Server side search with the RFC 5323 DASL SEARCH method.
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, Response};
+
+use crate::types::list_cmd::ListEntity;
+use crate::types::search_cmd::SearchQuery;
+use crate::types::{Dav2xx, Result};
+use crate::Client;
+
+impl Client {
+    pub async fn search_raw(&self, scope: &str, query: &SearchQuery) -> Result<Response> {
+        let href = self.url_for(scope)?.path().to_owned();
+        let builder = self
+            .start_request(Method::from_bytes(b"SEARCH")?, scope)
+            .await?
+            .header(CONTENT_TYPE, "text/xml; charset=utf-8")
+            .body(query.to_xml(&href));
+        self.execute(builder).await
+    }
+
+    /// Search the files and folders below `scope` with a DASL `basicsearch`
+    ///
+    /// Needs a server supporting RFC 5323, like SabreDAV with its search plugin or Apache
+    /// mod_dav_search.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn search(&self, scope: &str, query: SearchQuery) -> Result<Vec<ListEntity>> {
+        let response = self.search_raw(scope, &query).await?.dav2xx().await?;
+        self.parse_multi_status(&response.text().await?)?
+            .responses
+            .into_iter()
+            .map(ListEntity::try_from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::types::search_cmd::{SearchCondition, SearchQuery};
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn search_returns_matching_entities() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("SEARCH"))
+            .and(path("/dav/My%20Docs"))
+            .and(body_string_contains(
+                "<D:href>/dav/My%20Docs</D:href><D:depth>infinity</D:depth>",
+            ))
+            .and(body_string_contains("<D:literal>%.pdf</D:literal>"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/My%20Docs/a.pdf</D:href><D:propstat><D:status>HTTP/1.1 200 OK</D:status><D:prop><D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified><D:getcontentlength>12</D:getcontentlength><D:resourcetype/></D:prop></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let entities = client
+            .search(
+                "/My Docs",
+                SearchQuery::new().filter(SearchCondition::like("DAV:", "displayname", "%.pdf")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].rel_path(&client), "/My Docs/a.pdf");
+        mock_server.verify().await;
+    }
+}
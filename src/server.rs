@@ -0,0 +1,222 @@
+//! Discovery of the server implementation behind the client.
+
+use reqwest::Method;
+
+use crate::types::about_cmd::{About, AboutMultiStatus, AboutProp, AboutStrategy, Quota};
+use crate::types::options_cmd::DavCapabilities;
+use crate::types::server_cmd::{ServerInfo, ServerQuirks};
+use crate::types::{Dav2xx, DecodeError, Error, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    /// Identify the server implementation and version
+    ///
+    /// Issues a depth 0 PROPFIND against the host root and inspects the response headers and body.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn server_info(&self) -> Result<ServerInfo> {
+        let response = self.list_raw("", Depth::Number(0)).await?;
+        let headers = response.headers().clone();
+        let body = response.text().await?;
+        Ok(ServerInfo::detect(&self.host, &headers, &body))
+    }
+
+    /// Work out the quirks of the server from a PROPFIND and an OPTIONS of the host root
+    ///
+    /// See `ServerQuirks::detect`. A server refusing OPTIONS is treated as advertising nothing.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn detect_quirks(&self) -> Result<ServerQuirks> {
+        let info = self.server_info().await?;
+        let capabilities = match self.options("").await {
+            Ok(capabilities) => capabilities,
+            Err(Error::Decode(DecodeError::Server(_))) => DavCapabilities::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(ServerQuirks::detect(&info, &capabilities))
+    }
+
+    /// A client using the quirks detected by `detect_quirks` instead of the ones it was built with
+    pub async fn with_detected_quirks(&self) -> Result<Client> {
+        Ok(Client {
+            quirks: self.detect_quirks().await?,
+            ..self.clone()
+        })
+    }
+
+    /// Capabilities the server advertises for a path in the `DAV` and `Allow` headers
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn options(&self, path: &str) -> Result<DavCapabilities> {
+        let builder = self.start_request(Method::OPTIONS, path).await?;
+        let response = self.execute(builder).await?.dav2xx().await?;
+        Ok(DavCapabilities::from_headers(response.headers()))
+    }
+
+    /// Storage usage of the account with `{ total, used, free }` normalized across servers
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn about(&self) -> Result<About> {
+        self.about_with(AboutStrategy::Auto).await
+    }
+
+    /// Storage usage of the account, interpreting the properties with the given strategy
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn about_with(&self, strategy: AboutStrategy) -> Result<About> {
+        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
+            <D:propfind xmlns:D="DAV:" xmlns:oc="http://owncloud.org/ns">
+                <D:prop>
+                    <D:quota-used-bytes/>
+                    <D:quota-available-bytes/>
+                    <oc:size/>
+                </D:prop>
+            </D:propfind>
+        "#;
+        let prop = self.storage_prop("", body).await?;
+        Ok(About::from_prop(&prop, strategy))
+    }
+
+    /// RFC 4331 quota of a folder
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn quota(&self, path: &str) -> Result<Quota> {
+        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
+            <D:propfind xmlns:D="DAV:">
+                <D:prop>
+                    <D:quota-used-bytes/>
+                    <D:quota-available-bytes/>
+                </D:prop>
+            </D:propfind>
+        "#;
+        let prop = self.storage_prop(path, body).await?;
+        Ok(Quota::from_prop(&prop))
+    }
+
+    async fn storage_prop(&self, path: &str, body: &'static str) -> Result<AboutProp> {
+        let builder = self
+            .start_request(Method::from_bytes(b"PROPFIND")?, path)
+            .await?
+            .header("depth", "0")
+            .body(body);
+        let text = self.execute(builder).await?.dav2xx().await?.text().await?;
+        let multi_status: AboutMultiStatus = serde_xml_rs::from_str(&text)?;
+        Ok(multi_status
+            .responses
+            .into_iter()
+            .next()
+            .map(AboutProp::from_response)
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::about_cmd::Quota;
+    use crate::types::server_cmd::{ServerKind, ServerQuirks};
+    use crate::{ClientBuilder, Depth};
+    use wiremock::matchers::{body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn options_reports_capabilities() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("OPTIONS"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .append_header("dav", "1, 2, addressbook")
+                    .append_header("allow", "OPTIONS, GET, PUT, REPORT"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        let capabilities = client.options("/contacts").await.unwrap();
+        assert!(capabilities.is_class_2() && capabilities.addressbook());
+        assert!(capabilities.allows("REPORT"));
+    }
+
+    #[tokio::test]
+    async fn quota_of_folder() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/photos"))
+            .and(header("depth", "0"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:"><d:response><d:href>/dav/photos/</d:href><d:propstat><d:prop><d:quota-used-bytes>2048</d:quota-used-bytes></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat><d:propstat><d:prop><d:quota-available-bytes/></d:prop><d:status>HTTP/1.1 404 Not Found</d:status></d:propstat></d:response></d:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let quota = client.quota("/photos").await.unwrap();
+        assert_eq!(
+            quota,
+            Quota {
+                used_bytes: Some(2048),
+                available_bytes: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn detected_quirks_parse_leniently() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:nc="http://nextcloud.org/ns"><d:response><d:href>/contacts/</d:href><d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat><d:propstat><d:prop><d:getlastmodified/></d:prop><d:status>HTTP/1.1 404 Not Found</d:status></d:propstat></d:response></d:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("OPTIONS"))
+            .respond_with(ResponseTemplate::new(405))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        assert!(client.list("/contacts", Depth::Number(0)).await.is_err());
+        let client = client.with_detected_quirks().await.unwrap();
+        assert!(client.quirks.lenient_parsing);
+        let entities = client.list("/contacts", Depth::Number(0)).await.unwrap();
+        assert_eq!(entities.len(), 1);
+        mock_server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn empty_propfind_body_quirk() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(body_string(""))
+            .respond_with(
+                ResponseTemplate::new(207)
+                    .append_header("server", "Apache/2.4.58")
+                    .set_body_string(r#"<D:multistatus xmlns:D="DAV:"/>"#),
+            )
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .set_quirks(ServerQuirks {
+                empty_propfind_body: true,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        client.list_raw("", Depth::Number(1)).await.unwrap();
+        let info = client.server_info().await.unwrap();
+        assert_eq!(info.kind, ServerKind::ApacheModDav);
+        mock_server.verify().await;
+    }
+}
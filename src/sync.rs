@@ -0,0 +1,140 @@
+//! Incremental listings with the RFC 6578 sync-collection REPORT and change detection with
+//! the calendarserver `getctag`.
+
+use reqwest::Response;
+
+use crate::types::list_cmd::CALENDARSERVER_NAMESPACE;
+use crate::types::propfind_cmd::PropfindRequest;
+use crate::types::sync_cmd::{sync_collection_body, SyncChanges};
+use crate::types::{Dav2xx, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    pub async fn sync_collection_raw(
+        &self,
+        path: &str,
+        sync_token: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Response> {
+        let body = sync_collection_body(sync_token, limit);
+        self.report_raw(path, Depth::Number(0), &body).await
+    }
+
+    /// Changes of the members of a collection since `sync_token`
+    ///
+    /// Without a token all members are reported as changed. Keep the returned token for the
+    /// next call; when the result is `truncated` call again right away to get the rest. Servers
+    /// answer an expired token with a 403 or 409 error, start over without a token then.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn sync_collection(
+        &self,
+        path: &str,
+        sync_token: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<SyncChanges> {
+        let response = self
+            .sync_collection_raw(path, sync_token.as_deref(), limit)
+            .await?
+            .dav2xx()
+            .await?;
+        SyncChanges::try_from(self.parse_multi_status(&response.text().await?)?)
+    }
+
+    /// The `getctag` of a calendar or address book, `None` when the server has none
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn collection_ctag(&self, path: &str) -> Result<Option<String>> {
+        let request = PropfindRequest::props([(CALENDARSERVER_NAMESPACE, "getctag")]);
+        let response = self
+            .list_with_raw(path, Depth::Number(0), &request)
+            .await?
+            .dav2xx()
+            .await?;
+        let multi_status = self.parse_multi_status(&response.text().await?)?;
+        Ok(multi_status
+            .responses
+            .into_iter()
+            .flat_map(|response| response.prop_stat)
+            .find_map(|prop_stat| prop_stat.prop.ctag))
+    }
+
+    /// Whether any member of a calendar or address book changed since `previous_ctag` was
+    /// read with `collection_ctag`
+    ///
+    /// Collections without a ctag are always reported as changed.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn has_changed(&self, path: &str, previous_ctag: &str) -> Result<bool> {
+        let ctag = self.collection_ctag(path).await?;
+        Ok(ctag.as_deref() != Some(previous_ctag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ClientBuilder;
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn sync_collection_sends_token_and_limit() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("REPORT"))
+            .and(path("/dav/cal"))
+            .and(header("depth", "0"))
+            .and(body_string_contains("<D:sync-token>token-1</D:sync-token>"))
+            .and(body_string_contains("<D:nresults>10</D:nresults>"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8" ?><D:multistatus xmlns:D="DAV:"><D:sync-token>token-2</D:sync-token></D:multistatus>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        let changes = client
+            .sync_collection("/cal", Some("token-1".to_owned()), Some(10))
+            .await
+            .unwrap();
+        assert!(changes.changed.is_empty() && changes.deleted.is_empty());
+        assert_eq!(changes.sync_token, "token-2");
+    }
+
+    #[tokio::test]
+    async fn ctag_detects_changes() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dav/calendars/work"))
+            .and(header("depth", "0"))
+            .and(body_string_contains("getctag"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8" ?><d:multistatus xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/"><d:response><d:href>/dav/calendars/work/</d:href><d:propstat><d:prop><cs:getctag>ctag-7</cs:getctag></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response></d:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.collection_ctag("/calendars/work").await.unwrap(),
+            Some("ctag-7".to_owned())
+        );
+        assert!(!client
+            .has_changed("/calendars/work", "ctag-7")
+            .await
+            .unwrap());
+        assert!(client
+            .has_changed("/calendars/work", "ctag-6")
+            .await
+            .unwrap());
+    }
+}
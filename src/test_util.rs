@@ -0,0 +1,450 @@
+//! Fixtures for testing code built on this crate without a live server.
+//!
+//! Enabled with the `test-util` feature. `MockDav` is an in-process WebDAV server keeping its
+//! resources in memory, it answers PROPFIND, GET, PUT, DELETE, MKCOL, MOVE and COPY.
+//! `multistatus` renders canned PROPFIND responses for hand written mocks.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use wiremock::matchers::any;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use crate::types::timestamp::{DateTime, DavDateTime};
+use crate::types::xml_escape;
+use crate::{encode_path, Client, ClientBuilder};
+
+/// One `response` element of a canned multistatus body.
+#[derive(Debug, Clone)]
+pub struct MockEntry {
+    pub href: String,
+    pub last_modified: DateTime,
+    pub etag: Option<String>,
+    /// `None` for collections.
+    pub content_length: Option<u64>,
+    pub content_type: Option<String>,
+}
+
+impl MockEntry {
+    pub fn file(href: &str, content_length: u64) -> MockEntry {
+        MockEntry {
+            href: href.to_owned(),
+            last_modified: DateTime::unix_epoch(),
+            etag: Some(format!("\"{:x}\"", content_length)),
+            content_length: Some(content_length),
+            content_type: Some("application/octet-stream".to_owned()),
+        }
+    }
+
+    pub fn folder(href: &str) -> MockEntry {
+        MockEntry {
+            href: href.to_owned(),
+            last_modified: DateTime::unix_epoch(),
+            etag: None,
+            content_length: None,
+            content_type: None,
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        let mut prop = format!(
+            "<d:getlastmodified>{}</d:getlastmodified>",
+            httpdate::fmt_http_date(self.last_modified.to_system_time())
+        );
+        match self.content_length {
+            Some(length) => {
+                prop.push_str("<d:resourcetype/>");
+                prop.push_str(&format!(
+                    "<d:getcontentlength>{}</d:getcontentlength>",
+                    length
+                ));
+            }
+            None => prop.push_str("<d:resourcetype><d:collection/></d:resourcetype>"),
+        }
+        if let Some(etag) = &self.etag {
+            prop.push_str(&format!("<d:getetag>{}</d:getetag>", xml_escape(etag)));
+        }
+        if let Some(content_type) = &self.content_type {
+            prop.push_str(&format!(
+                "<d:getcontenttype>{}</d:getcontenttype>",
+                xml_escape(content_type)
+            ));
+        }
+        format!(
+            "<d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+            xml_escape(&self.href),
+            prop
+        )
+    }
+}
+
+/// Render a PROPFIND multistatus body listing `entries`.
+pub fn multistatus(entries: &[MockEntry]) -> String {
+    let responses: String = entries.iter().map(MockEntry::to_xml).collect();
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><d:multistatus xmlns:d="DAV:">{}</d:multistatus>"#,
+        responses
+    )
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    File(Vec<u8>, DateTime),
+    Folder(DateTime),
+}
+
+type Tree = Arc<Mutex<BTreeMap<String, Node>>>;
+
+fn normalize(path: &str) -> String {
+    let decoded = percent_encoding::percent_decode_str(path).decode_utf8_lossy();
+    decoded.trim_matches('/').to_owned()
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/')
+        .map(|(parent, _)| parent)
+        .unwrap_or("")
+}
+
+/// A WebDAV server backed by an in-memory tree.
+///
+/// Requests it does not understand are answered with 405, additional mocks can be mounted on
+/// `server()` to simulate failures.
+pub struct MockDav {
+    server: MockServer,
+    tree: Tree,
+}
+
+impl MockDav {
+    /// Start a server with an empty root collection.
+    pub async fn start() -> MockDav {
+        let server = MockServer::start().await;
+        let tree: Tree = Default::default();
+        tree.lock()
+            .unwrap()
+            .insert(String::new(), Node::Folder(DateTime::now()));
+        Mock::given(any())
+            .respond_with(TreeResponder { tree: tree.clone() })
+            .mount(&server)
+            .await;
+        MockDav { server, tree }
+    }
+
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// The underlying wiremock server, to mount additional mocks on.
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// An anonymous client rooted at the server.
+    pub fn client(&self) -> Client {
+        ClientBuilder::new().set_host(self.uri()).build().unwrap()
+    }
+
+    /// Add a file, creating the missing parent collections.
+    pub fn add_file(&self, path: &str, data: impl Into<Vec<u8>>) {
+        let path = normalize(path);
+        self.add_folder(parent(&path));
+        self.tree
+            .lock()
+            .unwrap()
+            .insert(path, Node::File(data.into(), DateTime::now()));
+    }
+
+    /// Add a collection and its missing parents.
+    pub fn add_folder(&self, path: &str) {
+        let path = normalize(path);
+        let mut tree = self.tree.lock().unwrap();
+        let mut current = String::new();
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            if !current.is_empty() {
+                current.push('/');
+            }
+            current.push_str(segment);
+            tree.entry(current.clone())
+                .or_insert_with(|| Node::Folder(DateTime::now()));
+        }
+    }
+
+    /// Content of the file at `path`.
+    pub fn file(&self, path: &str) -> Option<Vec<u8>> {
+        match self.tree.lock().unwrap().get(&normalize(path)) {
+            Some(Node::File(data, _)) => Some(data.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn is_folder(&self, path: &str) -> bool {
+        matches!(
+            self.tree.lock().unwrap().get(&normalize(path)),
+            Some(Node::Folder(_))
+        )
+    }
+
+    /// The requests received so far with the given method, in order.
+    pub async fn requests(&self, method: &str) -> Vec<Request> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|request| request.method.as_str() == method)
+            .collect()
+    }
+
+    /// Panic unless `method` was requested on `path` exactly `times` times.
+    pub async fn assert_requested(&self, method: &str, path: &str, times: usize) {
+        let path = normalize(path);
+        let count = self
+            .requests(method)
+            .await
+            .iter()
+            .filter(|request| normalize(request.url.path()) == path)
+            .count();
+        assert_eq!(
+            count, times,
+            "expected {} {} request(s) on /{}, got {}",
+            times, method, path, count
+        );
+    }
+}
+
+struct TreeResponder {
+    tree: Tree,
+}
+
+impl TreeResponder {
+    fn propfind(&self, path: &str, request: &Request) -> ResponseTemplate {
+        let tree = self.tree.lock().unwrap();
+        if !tree.contains_key(path) {
+            return ResponseTemplate::new(404);
+        }
+        let depth = request
+            .headers
+            .get("depth")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("infinity");
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", path)
+        };
+        let entries: Vec<MockEntry> = tree
+            .iter()
+            .filter(|(key, _)| {
+                if key.as_str() == path {
+                    return true;
+                }
+                let Some(rest) = key.strip_prefix(&prefix) else {
+                    return false;
+                };
+                match depth {
+                    "0" => false,
+                    "1" => !rest.is_empty() && !rest.contains('/'),
+                    _ => !rest.is_empty(),
+                }
+            })
+            .map(|(key, node)| {
+                let href = format!("/{}", encode_path(key));
+                match node {
+                    Node::File(data, modified) => MockEntry {
+                        last_modified: *modified,
+                        ..MockEntry::file(&href, data.len() as u64)
+                    },
+                    Node::Folder(modified) => MockEntry {
+                        last_modified: *modified,
+                        ..MockEntry::folder(&format!("{}/", href.trim_end_matches('/')))
+                    },
+                }
+            })
+            .collect();
+        ResponseTemplate::new(207).set_body_raw(multistatus(&entries), "application/xml")
+    }
+
+    fn get(&self, path: &str) -> ResponseTemplate {
+        match self.tree.lock().unwrap().get(path) {
+            Some(Node::File(data, _)) => ResponseTemplate::new(200).set_body_bytes(data.clone()),
+            Some(Node::Folder(_)) => ResponseTemplate::new(405),
+            None => ResponseTemplate::new(404),
+        }
+    }
+
+    fn put(&self, path: &str, request: &Request) -> ResponseTemplate {
+        let mut tree = self.tree.lock().unwrap();
+        if !matches!(tree.get(parent(path)), Some(Node::Folder(_))) {
+            return ResponseTemplate::new(409);
+        }
+        match tree.insert(
+            path.to_owned(),
+            Node::File(request.body.clone(), DateTime::now()),
+        ) {
+            Some(Node::Folder(previous)) => {
+                tree.insert(path.to_owned(), Node::Folder(previous));
+                ResponseTemplate::new(405)
+            }
+            Some(_) => ResponseTemplate::new(204),
+            None => ResponseTemplate::new(201),
+        }
+    }
+
+    fn delete(&self, path: &str) -> ResponseTemplate {
+        let mut tree = self.tree.lock().unwrap();
+        if path.is_empty() {
+            return ResponseTemplate::new(403);
+        }
+        if tree.remove(path).is_none() {
+            return ResponseTemplate::new(404);
+        }
+        let prefix = format!("{}/", path);
+        tree.retain(|key, _| !key.starts_with(&prefix));
+        ResponseTemplate::new(204)
+    }
+
+    fn mkcol(&self, path: &str) -> ResponseTemplate {
+        let mut tree = self.tree.lock().unwrap();
+        if tree.contains_key(path) {
+            return ResponseTemplate::new(405);
+        }
+        if !matches!(tree.get(parent(path)), Some(Node::Folder(_))) {
+            return ResponseTemplate::new(409);
+        }
+        tree.insert(path.to_owned(), Node::Folder(DateTime::now()));
+        ResponseTemplate::new(201)
+    }
+
+    /// MOVE and COPY of a resource and everything below it.
+    fn transfer(&self, path: &str, request: &Request, keep_source: bool) -> ResponseTemplate {
+        let Some(destination) = request
+            .headers
+            .get("destination")
+            .and_then(|value| value.to_str().ok())
+        else {
+            return ResponseTemplate::new(400);
+        };
+        let destination = match url::Url::parse(destination) {
+            Ok(url) => normalize(url.path()),
+            Err(_) => normalize(destination.split('?').next().unwrap_or_default()),
+        };
+        let overwrite = request
+            .headers
+            .get("overwrite")
+            .map(|value| value.as_bytes() != b"F")
+            .unwrap_or(true);
+
+        let mut tree = self.tree.lock().unwrap();
+        if !tree.contains_key(path) {
+            return ResponseTemplate::new(404);
+        }
+        if destination == path || destination.starts_with(&format!("{}/", path)) {
+            return ResponseTemplate::new(403);
+        }
+        if !matches!(tree.get(parent(&destination)), Some(Node::Folder(_))) {
+            return ResponseTemplate::new(409);
+        }
+        let existed = tree.contains_key(&destination);
+        if existed && !overwrite {
+            return ResponseTemplate::new(412);
+        }
+        let destination_prefix = format!("{}/", destination);
+        tree.retain(|key, _| key != &destination && !key.starts_with(&destination_prefix));
+
+        let prefix = format!("{}/", path);
+        let moved: Vec<(String, Node)> = tree
+            .iter()
+            .filter(|(key, _)| key.as_str() == path || key.starts_with(&prefix))
+            .map(|(key, node)| {
+                (
+                    format!("{}{}", destination, &key[path.len()..]),
+                    node.clone(),
+                )
+            })
+            .collect();
+        if !keep_source {
+            tree.retain(|key, _| key != path && !key.starts_with(&prefix));
+        }
+        tree.extend(moved);
+        ResponseTemplate::new(if existed { 204 } else { 201 })
+    }
+}
+
+impl Respond for TreeResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let path = normalize(request.url.path());
+        match request.method.as_str() {
+            "PROPFIND" => self.propfind(&path, request),
+            "GET" => self.get(&path),
+            "PUT" => self.put(&path, request),
+            "DELETE" => self.delete(&path),
+            "MKCOL" => self.mkcol(&path),
+            "MOVE" => self.transfer(&path, request, false),
+            "COPY" => self.transfer(&path, request, true),
+            _ => ResponseTemplate::new(405),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::list_cmd::ListEntity;
+    use crate::Depth;
+
+    #[tokio::test]
+    async fn serves_tree_to_client() {
+        let dav = MockDav::start().await;
+        dav.add_file("docs/a b.txt", "hello");
+        let client = dav.client();
+
+        let entities = client.list("docs", Depth::Number(1)).await.unwrap();
+        assert_eq!(entities.len(), 2);
+        assert!(matches!(&entities[1], ListEntity::File(file) if file.content_length == 5));
+
+        client.put("docs/new.txt", "new").await.unwrap();
+        assert_eq!(dav.file("docs/new.txt").as_deref(), Some(&b"new"[..]));
+        let body = client
+            .get("docs/a b.txt")
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "hello");
+        assert!(client.put("missing/x.txt", "x").await.is_err());
+        dav.assert_requested("PUT", "docs/new.txt", 1).await;
+    }
+
+    #[tokio::test]
+    async fn moves_copies_and_deletes_subtrees() {
+        let dav = MockDav::start().await;
+        dav.add_file("a/b/c.txt", "c");
+        let client = dav.client();
+
+        client.mkcol("d").await.unwrap();
+        client.mv("a", "d/a").await.unwrap();
+        assert_eq!(dav.file("d/a/b/c.txt").as_deref(), Some(&b"c"[..]));
+        assert!(!dav.is_folder("a"));
+
+        client.cp("d/a/b", "b").await.unwrap();
+        assert!(dav.is_folder("d/a/b"));
+        assert_eq!(dav.file("b/c.txt").as_deref(), Some(&b"c"[..]));
+
+        client.delete("d").await.unwrap();
+        assert!(dav.file("d/a/b/c.txt").is_none());
+        assert_eq!(client.list("", Depth::Infinity).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn breadth_first_listing_matches_infinite_depth() {
+        let dav = MockDav::start().await;
+        dav.add_file("x/y/z.txt", "z");
+        dav.add_file("x/w.txt", "w");
+        let client = dav.client();
+        let infinite = client.list("x", Depth::Infinity).await.unwrap();
+        let crawled = client.list_breadth_first("x").await.unwrap();
+        assert_eq!(infinite.len(), 4);
+        assert_eq!(crawled.len(), infinite.len());
+    }
+}
@@ -0,0 +1,150 @@
+//! Background queue running transfer jobs.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+use crate::types::transfer_cmd::{TransferFailure, TransferJob, TransferQueue, TransferReport};
+use crate::types::{Error, Result};
+use crate::Client;
+
+impl Client {
+    /// Start a queue running at most `concurrency` jobs at a time
+    ///
+    /// Must be called within a tokio runtime.
+    pub fn transfer_queue(&self, concurrency: usize) -> TransferQueue {
+        let (sender, receiver) = unbounded_channel();
+        let cancellation = CancellationToken::new();
+        let report = Arc::new(Mutex::new(TransferReport::default()));
+        let worker = tokio::spawn(run_queue(
+            self.with_cancellation(cancellation.clone()),
+            receiver,
+            concurrency.max(1),
+            report.clone(),
+        ));
+        TransferQueue {
+            sender,
+            worker,
+            cancellation,
+            report,
+        }
+    }
+
+    async fn run_job(&self, job: TransferJob) -> Result<()> {
+        match job {
+            TransferJob::Upload { path, data } => self.put(&path, data).await.map(|_| ()),
+            TransferJob::Delete { path } => self.delete(&path).await,
+            TransferJob::Mkcol { path } => self.mkcol(&path).await,
+            TransferJob::Copy { from, to } => self.cp(&from, &to).await,
+            TransferJob::Move { from, to } => self.mv(&from, &to).await,
+        }
+    }
+}
+
+async fn run_queue(
+    client: Client,
+    mut receiver: UnboundedReceiver<TransferJob>,
+    concurrency: usize,
+    report: Arc<Mutex<TransferReport>>,
+) {
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let mut running = JoinSet::new();
+    while let Some(job) = receiver.recv().await {
+        let permit = semaphore.clone().acquire_owned().await;
+        let client = client.clone();
+        let report = report.clone();
+        running.spawn(async move {
+            let _permit = permit;
+            let path = job.path().to_owned();
+            let result = client.run_job(job).await;
+            let mut report = report.lock().unwrap();
+            match result {
+                Ok(()) => report.completed.push(path),
+                Err(Error::Cancelled) => report.cancelled.push(path),
+                Err(error) => report.failed.push(TransferFailure { path, error }),
+            }
+        });
+    }
+    while running.join_next().await.is_some() {}
+}
+
+impl TransferQueue {
+    /// Queue a job, handing it back if the queue is shut down.
+    pub fn submit(&self, job: TransferJob) -> Result<(), TransferJob> {
+        self.sender.send(job).map_err(|e| e.0)
+    }
+
+    /// Stop accepting jobs and wait for the queued ones
+    ///
+    /// Jobs still running after `grace_period` are cancelled, as are the ones not started yet.
+    pub async fn shutdown(self, grace_period: Duration) -> TransferReport {
+        let TransferQueue {
+            sender,
+            mut worker,
+            cancellation,
+            report,
+        } = self;
+        drop(sender);
+        if tokio::time::timeout(grace_period, &mut worker)
+            .await
+            .is_err()
+        {
+            cancellation.cancel();
+            let _ = worker.await;
+        }
+        let mut report = report.lock().unwrap();
+        std::mem::take(&mut *report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::types::transfer_cmd::TransferJob;
+    use crate::ClientBuilder;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn shutdown_finishes_or_cancels_jobs() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(201).set_delay(Duration::from_secs(5)))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(mock_server.uri())
+            .build()
+            .unwrap();
+        let queue = client.transfer_queue(1);
+        queue
+            .submit(TransferJob::Delete {
+                path: "a".to_owned(),
+            })
+            .unwrap();
+        for path in ["b", "c"] {
+            queue
+                .submit(TransferJob::Upload {
+                    path: path.to_owned(),
+                    data: vec![1],
+                })
+                .unwrap();
+        }
+        let report = queue.shutdown(Duration::from_millis(200)).await;
+        assert_eq!(report.completed, vec!["a".to_owned()]);
+        let mut cancelled = report.cancelled;
+        cancelled.sort();
+        assert_eq!(cancelled, vec!["b".to_owned(), "c".to_owned()]);
+        assert!(report.failed.is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+//! Types and serialisation for storage usage reporting.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::types::list_cmd::{empty_number, status_is_ok};
+use crate::types::server_cmd::ServerKind;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AboutMultiStatus {
+    #[serde(rename = "response", default)]
+    pub responses: Vec<AboutResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AboutResponse {
+    pub href: String,
+    #[serde(rename = "propstat", default)]
+    pub prop_stat: Vec<AboutPropStat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AboutPropStat {
+    #[serde(default)]
+    pub status: String,
+    pub prop: AboutProp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AboutProp {
+    #[serde(
+        rename = "quota-used-bytes",
+        deserialize_with = "empty_number",
+        default
+    )]
+    pub quota_used_bytes: Option<i64>,
+    #[serde(
+        rename = "quota-available-bytes",
+        deserialize_with = "empty_number",
+        default
+    )]
+    pub quota_available_bytes: Option<i64>,
+    /// `oc:size`, the size of a folder including all its children.
+    #[serde(deserialize_with = "empty_number", default)]
+    pub size: Option<i64>,
+}
+
+/// How the storage properties of a server are interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AboutStrategy {
+    /// Use whatever the server returned, RFC 4331 properties first.
+    #[default]
+    Auto,
+    /// Only the RFC 4331 `quota-used-bytes` and `quota-available-bytes` properties.
+    Rfc4331,
+    /// Nextcloud/ownCloud: `oc:size` for usage, negative available bytes mean no limit.
+    Nextcloud,
+}
+
+impl AboutStrategy {
+    /// The strategy suited to a server implementation, see `Client::server_info`.
+    pub fn for_server(kind: &ServerKind) -> AboutStrategy {
+        match kind {
+            ServerKind::Nextcloud | ServerKind::OwnCloud => AboutStrategy::Nextcloud,
+            _ => AboutStrategy::Auto,
+        }
+    }
+}
+
+/// Storage usage of the account, normalized across servers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct About {
+    /// `None` when the storage is unlimited or the server does not say.
+    pub total: Option<i64>,
+    pub used: Option<i64>,
+    pub free: Option<i64>,
+}
+
+/// RFC 4331 quota of a folder, see `Client::quota`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quota {
+    /// `None` when the server does not report the property or a negative value.
+    pub used_bytes: Option<u64>,
+    /// `None` when the server does not report the property or a negative value, which
+    /// Nextcloud uses for unlimited storage.
+    pub available_bytes: Option<u64>,
+}
+
+impl Quota {
+    pub fn from_prop(prop: &AboutProp) -> Quota {
+        let non_negative = |value: Option<i64>| value.and_then(|value| u64::try_from(value).ok());
+        Quota {
+            used_bytes: non_negative(prop.quota_used_bytes),
+            available_bytes: non_negative(prop.quota_available_bytes),
+        }
+    }
+}
+
+impl AboutProp {
+    /// Merge the properties of all successful propstats.
+    pub fn from_response(response: AboutResponse) -> AboutProp {
+        response
+            .prop_stat
+            .into_iter()
+            .filter(|prop_stat| status_is_ok(&prop_stat.status))
+            .fold(AboutProp::default(), |acc, prop_stat| AboutProp {
+                quota_used_bytes: acc.quota_used_bytes.or(prop_stat.prop.quota_used_bytes),
+                quota_available_bytes: acc
+                    .quota_available_bytes
+                    .or(prop_stat.prop.quota_available_bytes),
+                size: acc.size.or(prop_stat.prop.size),
+            })
+    }
+}
+
+impl About {
+    pub fn from_prop(prop: &AboutProp, strategy: AboutStrategy) -> About {
+        // Nextcloud reports -1 (not computed), -2 (unknown) and -3 (unlimited).
+        let non_negative = |value: Option<i64>| value.filter(|value| *value >= 0);
+        let (used, free) = match strategy {
+            AboutStrategy::Rfc4331 => (prop.quota_used_bytes, prop.quota_available_bytes),
+            AboutStrategy::Nextcloud => (
+                non_negative(prop.size.or(prop.quota_used_bytes)),
+                non_negative(prop.quota_available_bytes),
+            ),
+            AboutStrategy::Auto => (
+                non_negative(prop.quota_used_bytes.or(prop.size)),
+                non_negative(prop.quota_available_bytes),
+            ),
+        };
+        About {
+            total: match (used, free) {
+                (Some(used), Some(free)) => Some(used + free),
+                _ => None,
+            },
+            used,
+            free,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nextcloud_unlimited_quota() {
+        let xml = r#"<?xml version="1.0"?>
+        <d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+            <d:response>
+                <d:href>/remote.php/dav/files/alice/</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <d:quota-used-bytes>2048</d:quota-used-bytes>
+                        <d:quota-available-bytes>-3</d:quota-available-bytes>
+                        <oc:size>4096</oc:size>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+        let parsed: AboutMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let prop = AboutProp::from_response(parsed.responses[0].clone());
+
+        let about = About::from_prop(&prop, AboutStrategy::Nextcloud);
+        assert_eq!(about.used, Some(4096));
+        assert_eq!(about.free, None);
+        assert_eq!(about.total, None);
+
+        let about = About::from_prop(&prop, AboutStrategy::Auto);
+        assert_eq!(about.used, Some(2048));
+    }
+
+    #[test]
+    fn rfc4331_quota() {
+        let prop = AboutProp {
+            quota_used_bytes: Some(100),
+            quota_available_bytes: Some(900),
+            size: None,
+        };
+        let about = About::from_prop(&prop, AboutStrategy::Auto);
+        assert_eq!(
+            about,
+            About {
+                total: Some(1000),
+                used: Some(100),
+                free: Some(900),
+            }
+        );
+    }
+}
@@ -0,0 +1,372 @@
+//! Types of the RFC 3744 access control properties.
+
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+use quick_xml::events::Event;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+use crate::types::proppatch_cmd::PropName;
+use crate::types::xml_escape;
+
+/// A set of RFC 3744 privileges, like the `current-user-privilege-set` of a resource
+///
+/// The aggregate privileges `WRITE` and `ALL` are the union of the privileges they contain, a
+/// server reporting `DAV:write` grants `WRITE_CONTENT` as well.
+///
+/// ```
+/// use reqwest_dav::types::acl_cmd::Privileges;
+///
+/// let privileges = Privileges::READ | Privileges::WRITE_CONTENT;
+/// assert!(privileges.contains(Privileges::READ));
+/// assert!(!privileges.contains(Privileges::WRITE));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Privileges(u16);
+
+impl Privileges {
+    pub const READ: Privileges = Privileges(1);
+    pub const WRITE_PROPERTIES: Privileges = Privileges(1 << 1);
+    pub const WRITE_CONTENT: Privileges = Privileges(1 << 2);
+    pub const UNLOCK: Privileges = Privileges(1 << 3);
+    pub const READ_ACL: Privileges = Privileges(1 << 4);
+    pub const READ_CURRENT_USER_PRIVILEGE_SET: Privileges = Privileges(1 << 5);
+    pub const WRITE_ACL: Privileges = Privileges(1 << 6);
+    /// Add members to a collection.
+    pub const BIND: Privileges = Privileges(1 << 7);
+    /// Remove members from a collection.
+    pub const UNBIND: Privileges = Privileges(1 << 8);
+    pub const WRITE: Privileges = Privileges(
+        Self::WRITE_PROPERTIES.0 | Self::WRITE_CONTENT.0 | Self::BIND.0 | Self::UNBIND.0,
+    );
+    pub const ALL: Privileges = Privileges((1 << 9) - 1);
+
+    /// The `DAV:` privileges by element name, aggregates first.
+    const NAMES: [(&'static str, Privileges); 11] = [
+        ("all", Self::ALL),
+        ("write", Self::WRITE),
+        ("read", Self::READ),
+        ("write-properties", Self::WRITE_PROPERTIES),
+        ("write-content", Self::WRITE_CONTENT),
+        ("unlock", Self::UNLOCK),
+        ("read-acl", Self::READ_ACL),
+        (
+            "read-current-user-privilege-set",
+            Self::READ_CURRENT_USER_PRIVILEGE_SET,
+        ),
+        ("write-acl", Self::WRITE_ACL),
+        ("bind", Self::BIND),
+        ("unbind", Self::UNBIND),
+    ];
+
+    pub const fn empty() -> Privileges {
+        Privileges(0)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every privilege of `other` is in the set
+    pub fn contains(self, other: Privileges) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Privileges) {
+        self.0 |= other.0;
+    }
+
+    /// The privilege of a `DAV:` privilege element, `None` for unknown ones.
+    pub fn from_name(name: &str) -> Option<Privileges> {
+        Self::NAMES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, privileges)| *privileges)
+    }
+
+    /// The fewest privilege names covering the set, aggregates are used when complete.
+    pub fn names(self) -> Vec<&'static str> {
+        let mut covered = Privileges::empty();
+        let mut names = vec![];
+        for (name, privileges) in Self::NAMES {
+            if self.contains(privileges) && !covered.contains(privileges) {
+                covered |= privileges;
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+impl BitOr for Privileges {
+    type Output = Privileges;
+
+    fn bitor(self, other: Privileges) -> Privileges {
+        Privileges(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for Privileges {
+    fn bitor_assign(&mut self, other: Privileges) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for Privileges {
+    type Output = Privileges;
+
+    fn bitand(self, other: Privileges) -> Privileges {
+        Privileges(self.0 & other.0)
+    }
+}
+
+/// Who an access control entry applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Principal {
+    /// A principal resource, like `/principals/users/alice/`.
+    Href(String),
+    All,
+    Authenticated,
+    Unauthenticated,
+    /// The principal the resource is, for principal resources.
+    SelfPrincipal,
+    /// The principals named by a property of the resource, like `DAV:owner`.
+    Property(PropName),
+}
+
+impl Principal {
+    fn to_xml(&self) -> String {
+        let principal = match self {
+            Principal::Href(href) => format!("<D:href>{}</D:href>", xml_escape(href)),
+            Principal::All => "<D:all/>".to_owned(),
+            Principal::Authenticated => "<D:authenticated/>".to_owned(),
+            Principal::Unauthenticated => "<D:unauthenticated/>".to_owned(),
+            Principal::SelfPrincipal => "<D:self/>".to_owned(),
+            Principal::Property(prop) => {
+                format!("<D:property>{}</D:property>", prop.to_empty_element())
+            }
+        };
+        format!("<D:principal>{}</D:principal>", principal)
+    }
+}
+
+/// An access control entry granting or denying privileges to a principal, see
+/// `Client::set_acl`
+///
+/// ```
+/// use reqwest_dav::types::acl_cmd::{Ace, Principal, Privileges};
+///
+/// let aces = vec![
+///     Ace::grant(Principal::Href("/principals/users/bob/".to_owned()), Privileges::READ),
+///     Ace::deny(Principal::Unauthenticated, Privileges::ALL),
+/// ];
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ace {
+    pub principal: Principal,
+    pub privileges: Privileges,
+    /// The privileges are denied instead of granted.
+    pub deny: bool,
+    /// Set on entries read back which the server does not allow to change.
+    pub protected: bool,
+    /// The resource the entry is inherited from, on entries read back.
+    pub inherited: Option<String>,
+}
+
+impl Ace {
+    pub fn grant(principal: Principal, privileges: Privileges) -> Ace {
+        Ace {
+            principal,
+            privileges,
+            deny: false,
+            protected: false,
+            inherited: None,
+        }
+    }
+
+    pub fn deny(principal: Principal, privileges: Privileges) -> Ace {
+        Ace {
+            deny: true,
+            ..Ace::grant(principal, privileges)
+        }
+    }
+
+    /// Whether the entry is sent by `Client::set_acl`, protected and inherited ones are not.
+    pub fn is_editable(&self) -> bool {
+        !self.protected && self.inherited.is_none()
+    }
+
+    fn to_xml(&self) -> String {
+        let privileges = self
+            .privileges
+            .names()
+            .into_iter()
+            .map(|name| format!("<D:privilege><D:{}/></D:privilege>", name))
+            .collect::<String>();
+        let mode = if self.deny { "deny" } else { "grant" };
+        format!(
+            "<D:ace>{principal}<D:{mode}>{privileges}</D:{mode}></D:ace>",
+            principal = self.principal.to_xml(),
+        )
+    }
+}
+
+/// The `acl` body of an ACL request, leaving out the entries which are not editable.
+pub(crate) fn acl_body(aces: &[Ace]) -> String {
+    let aces = aces
+        .iter()
+        .filter(|ace| ace.is_editable())
+        .map(Ace::to_xml)
+        .collect::<String>();
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8" ?><D:acl xmlns:D="DAV:">{}</D:acl>"#,
+        aces
+    )
+}
+
+/// The entries of the first `DAV:acl` property of a multistatus, in order
+///
+/// Privileges outside the `DAV:` namespace are left out.
+pub(crate) fn parse_acl(xml: &str) -> crate::types::Result<Vec<Ace>> {
+    let mut reader = NsReader::from_str(xml);
+    let mut aces = vec![];
+    // local names of the open elements, `None` for the ones outside the `DAV:` namespace
+    let mut stack: Vec<Option<String>> = vec![];
+    let mut ace: Option<Ace> = None;
+    let mut text = String::new();
+    loop {
+        let (namespace, event) = reader.read_resolved_event()?;
+        let (element, empty) = match event {
+            Event::Start(element) => (element, false),
+            Event::Empty(element) => (element, true),
+            Event::End(_) => {
+                let name = stack.pop().flatten();
+                let parent = stack.last().cloned().flatten();
+                match (parent.as_deref(), name.as_deref(), &mut ace) {
+                    (Some("acl"), Some("ace"), ace) => aces.extend(ace.take()),
+                    (Some("principal"), Some("href"), Some(ace)) => {
+                        ace.principal = Principal::Href(text.trim().to_owned())
+                    }
+                    (Some("inherited"), Some("href"), Some(ace)) => {
+                        ace.inherited = Some(text.trim().to_owned())
+                    }
+                    (_, Some("acl"), _) => break,
+                    _ => {}
+                }
+                text.clear();
+                continue;
+            }
+            Event::Text(value) => {
+                text.push_str(&value.unescape()?);
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        let local_name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+        let name = match namespace {
+            ResolveResult::Bound(Namespace(b"DAV:")) => Some(local_name.clone()),
+            _ => None,
+        };
+        let parent = stack.last().cloned().flatten();
+        let grandparent = stack.iter().rev().nth(1).cloned().flatten();
+        text.clear();
+        match (parent.as_deref(), name.as_deref(), &mut ace) {
+            (Some("acl"), Some("ace"), ace) => {
+                *ace = Some(Ace::grant(Principal::All, Privileges::empty()))
+            }
+            (Some("principal"), Some(kind), Some(ace)) => match kind {
+                "all" => ace.principal = Principal::All,
+                "authenticated" => ace.principal = Principal::Authenticated,
+                "unauthenticated" => ace.principal = Principal::Unauthenticated,
+                "self" => ace.principal = Principal::SelfPrincipal,
+                _ => {}
+            },
+            (Some("property"), _, Some(ace)) if grandparent.as_deref() == Some("principal") => {
+                let namespace = match namespace {
+                    ResolveResult::Bound(Namespace(uri)) => {
+                        String::from_utf8_lossy(uri).into_owned()
+                    }
+                    _ => String::new(),
+                };
+                ace.principal = Principal::Property(PropName::new(namespace, local_name));
+            }
+            (Some("ace"), Some("deny"), Some(ace)) => ace.deny = true,
+            (Some("ace"), Some("protected"), Some(ace)) => ace.protected = true,
+            (Some("privilege"), Some(privilege), Some(ace)) => {
+                if let Some(privilege) = Privileges::from_name(privilege) {
+                    ace.privileges |= privilege;
+                }
+            }
+            _ => {}
+        }
+        if !empty {
+            stack.push(name);
+        }
+    }
+    Ok(aces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acl_round_trip() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <d:multistatus xmlns:d="DAV:">
+            <d:response>
+                <d:href>/dav/shared/</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <d:acl>
+                            <d:ace>
+                                <d:principal><d:property><d:owner/></d:property></d:principal>
+                                <d:grant><d:privilege><d:all/></d:privilege></d:grant>
+                                <d:protected/>
+                            </d:ace>
+                            <d:ace>
+                                <d:principal><d:href>/principals/bob/</d:href></d:principal>
+                                <d:grant>
+                                    <d:privilege><d:read/></d:privilege>
+                                    <d:privilege><d:write-content/></d:privilege>
+                                </d:grant>
+                            </d:ace>
+                            <d:ace>
+                                <d:principal><d:all/></d:principal>
+                                <d:deny><d:privilege><d:write/></d:privilege></d:deny>
+                                <d:inherited><d:href>/dav/</d:href></d:inherited>
+                            </d:ace>
+                        </d:acl>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+        let aces = parse_acl(xml).unwrap();
+        assert_eq!(
+            aces,
+            vec![
+                Ace {
+                    protected: true,
+                    ..Ace::grant(
+                        Principal::Property(PropName::new("DAV:", "owner")),
+                        Privileges::ALL
+                    )
+                },
+                Ace::grant(
+                    Principal::Href("/principals/bob/".to_owned()),
+                    Privileges::READ | Privileges::WRITE_CONTENT
+                ),
+                Ace {
+                    inherited: Some("/dav/".to_owned()),
+                    ..Ace::deny(Principal::All, Privileges::WRITE)
+                },
+            ]
+        );
+        assert_eq!(
+            acl_body(&aces),
+            r#"<?xml version="1.0" encoding="utf-8" ?><D:acl xmlns:D="DAV:"><D:ace><D:principal><D:href>/principals/bob/</D:href></D:principal><D:grant><D:privilege><D:read/></D:privilege><D:privilege><D:write-content/></D:privilege></D:grant></D:ace></D:acl>"#
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Storage of the GET cache, see `ClientBuilder::set_cache`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Largest body kept by the GET cache unless set otherwise, 8 MiB.
+pub const DEFAULT_MAX_CACHED_BODY: u64 = 8 * 1024 * 1024;
+
+/// A response body saved with the entity tag it was served with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedBody {
+    /// The `ETag` header, as sent by the server.
+    pub etag: String,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Where cached bodies are kept, keyed by the url of the resource.
+///
+/// The cache is best effort, a store failing to save or load an entry simply misses.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+pub trait CacheStore: Send + Sync {
+    async fn load(&self, key: &str) -> Option<CachedBody>;
+    async fn store(&self, key: &str, body: CachedBody);
+    async fn remove(&self, key: &str);
+}
+
+/// Keeps the cached bodies in memory.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, CachedBody>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl CacheStore for MemoryStore {
+    async fn load(&self, key: &str) -> Option<CachedBody> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    async fn store(&self, key: &str, body: CachedBody) {
+        self.entries.lock().unwrap().insert(key.to_owned(), body);
+    }
+
+    async fn remove(&self, key: &str) {
+        self.entries.lock().unwrap().remove(key);
+    }
+}
+
+/// Keeps the cached bodies as files in a directory, surviving restarts.
+///
+/// Each entry is a `.body` file holding the content and a `.meta` file holding the key, the
+/// entity tag and the content type on three lines. The files are named after a hash of the key.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct DirectoryStore {
+    dir: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DirectoryStore {
+    /// Store entries in `dir`, which is created when missing.
+    pub fn new(dir: impl Into<PathBuf>) -> std::io::Result<DirectoryStore> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(DirectoryStore { dir })
+    }
+
+    fn file(&self, key: &str, extension: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.{}", fnv1a(key.as_bytes()), extension))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait::async_trait]
+impl CacheStore for DirectoryStore {
+    async fn load(&self, key: &str) -> Option<CachedBody> {
+        let meta = tokio::fs::read_to_string(self.file(key, "meta"))
+            .await
+            .ok()?;
+        let mut lines = meta.lines();
+        // another key with the same hash
+        if lines.next()? != key {
+            return None;
+        }
+        let etag = lines.next()?.to_owned();
+        let content_type = lines.next().filter(|line| !line.is_empty());
+        Some(CachedBody {
+            etag,
+            content_type: content_type.map(str::to_owned),
+            body: tokio::fs::read(self.file(key, "body")).await.ok()?,
+        })
+    }
+
+    async fn store(&self, key: &str, body: CachedBody) {
+        let meta = format!(
+            "{}\n{}\n{}\n",
+            key,
+            body.etag,
+            body.content_type.as_deref().unwrap_or_default()
+        );
+        // the body is written first, a crash in between leaves no meta pointing at a stale body
+        self.remove(key).await;
+        if tokio::fs::write(self.file(key, "body"), &body.body)
+            .await
+            .is_ok()
+        {
+            let _ = tokio::fs::write(self.file(key, "meta"), meta).await;
+        }
+    }
+
+    async fn remove(&self, key: &str) {
+        let _ = tokio::fs::remove_file(self.file(key, "meta")).await;
+        let _ = tokio::fs::remove_file(self.file(key, "body")).await;
+    }
+}
+
+/// 64 bit FNV-1a, stable across builds unlike the hasher of the standard library.
+#[cfg(not(target_arch = "wasm32"))]
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The store used by a client.
+#[derive(Clone)]
+pub struct GetCache {
+    pub(crate) store: Arc<dyn CacheStore>,
+    /// Larger bodies, and those of unknown length, are streamed without being cached.
+    pub(crate) max_body_size: u64,
+}
+
+impl Debug for GetCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GetCache")
+            .field("max_body_size", &self.max_body_size)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn directory_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("reqwest_dav-{}", uuid::Uuid::new_v4()));
+        let store = DirectoryStore::new(&dir).unwrap();
+        let key = format!(
+            "https://server/dav/{}/config.json",
+            "deep%20folder/".repeat(40)
+        );
+        let key = key.as_str();
+        assert_eq!(store.load(key).await, None);
+        let body = CachedBody {
+            etag: "\"v1\"".to_owned(),
+            content_type: Some("application/json".to_owned()),
+            body: b"{}".to_vec(),
+        };
+        store.store(key, body.clone()).await;
+        assert_eq!(
+            DirectoryStore::new(&dir).unwrap().load(key).await,
+            Some(body)
+        );
+        assert_eq!(store.load("https://server/dav/other.json").await, None);
+        store.remove(key).await;
+        assert_eq!(store.load(key).await, None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
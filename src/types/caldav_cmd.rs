@@ -0,0 +1,154 @@
+//! Types and serialisation for the CalDAV REPORTs.
+
+use crate::types::list_cmd::{status_is_ok, ListMultiStatus};
+use crate::types::timestamp::{DateTime, DavDateTime};
+
+/// Filter of a calendar-query REPORT
+///
+/// ```
+/// use reqwest_dav::types::caldav_cmd::CalendarQuery;
+/// use reqwest_dav::types::timestamp::{DateTime, DavDateTime};
+///
+/// let query = CalendarQuery::events().time_range(
+///     DateTime::from_epoch_seconds(1_700_000_000),
+///     DateTime::from_epoch_seconds(1_700_604_800),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct CalendarQuery {
+    /// Component to match inside `VCALENDAR`, e.g. `VEVENT`.
+    pub component: String,
+    /// Only components overlapping `[start, end)`.
+    pub time_range: Option<(DateTime, DateTime)>,
+    /// Request the `calendar-data` of the matches, otherwise only their href and etag.
+    pub calendar_data: bool,
+}
+
+impl CalendarQuery {
+    pub fn new(component: impl Into<String>) -> CalendarQuery {
+        CalendarQuery {
+            component: component.into(),
+            time_range: None,
+            calendar_data: true,
+        }
+    }
+
+    pub fn events() -> CalendarQuery {
+        CalendarQuery::new("VEVENT")
+    }
+
+    pub fn todos() -> CalendarQuery {
+        CalendarQuery::new("VTODO")
+    }
+
+    pub fn time_range(mut self, start: DateTime, end: DateTime) -> Self {
+        self.time_range = Some((start, end));
+        self
+    }
+
+    /// Only fetch the href and etag of the matches.
+    pub fn without_calendar_data(mut self) -> Self {
+        self.calendar_data = false;
+        self
+    }
+
+    pub(crate) fn to_xml(&self) -> String {
+        let time_range = match &self.time_range {
+            Some((start, end)) => format!(
+                r#"<C:time-range start="{}" end="{}"/>"#,
+                ical_utc(start),
+                ical_utc(end)
+            ),
+            None => String::new(),
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?><C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav"><D:prop><D:getetag/>{}</D:prop><C:filter><C:comp-filter name="VCALENDAR"><C:comp-filter name="{}">{}</C:comp-filter></C:comp-filter></C:filter></C:calendar-query>"#,
+            if self.calendar_data {
+                "<C:calendar-data/>"
+            } else {
+                ""
+            },
+            crate::types::xml_escape(&self.component),
+            time_range
+        )
+    }
+}
+
+/// iCalendar UTC date-time, e.g. `20060104T000000Z`.
+fn ical_utc(time: &DateTime) -> String {
+    time.to_rfc3339_seconds().replace(['-', ':'], "")
+}
+
+/// A calendar object resource returned by a CalDAV REPORT.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarObject {
+    pub href: String,
+    pub etag: Option<String>,
+    /// The iCalendar text, when requested.
+    pub calendar_data: Option<String>,
+}
+
+impl From<ListMultiStatus> for Vec<CalendarObject> {
+    fn from(multi_status: ListMultiStatus) -> Self {
+        multi_status
+            .responses
+            .into_iter()
+            .filter_map(|response| {
+                let prop = response
+                    .prop_stat
+                    .into_iter()
+                    .find(|prop_stat| {
+                        prop_stat.status.is_empty() || status_is_ok(&prop_stat.status)
+                    })?
+                    .prop;
+                Some(CalendarObject {
+                    href: response.href,
+                    etag: prop.tag,
+                    calendar_data: prop.calendar_data,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calendar_query_body() {
+        let query = CalendarQuery::events().time_range(
+            DateTime::from_epoch_seconds(1136332800),
+            DateTime::from_epoch_seconds(1136419200),
+        );
+        let xml = query.to_xml();
+        assert!(xml.contains(r#"<C:comp-filter name="VEVENT"><C:time-range start="20060104T000000Z" end="20060105T000000Z"/></C:comp-filter>"#));
+        assert!(xml.contains("<C:calendar-data/>"));
+    }
+
+    #[test]
+    fn parse_calendar_data() {
+        let xml = "<?xml version=\"1.0\" encoding=\"utf-8\" ?>
+        <D:multistatus xmlns:D=\"DAV:\" xmlns:C=\"urn:ietf:params:xml:ns:caldav\">
+            <D:response>
+                <D:href>/cal/abcd2.ics</D:href>
+                <D:propstat>
+                    <D:prop>
+                        <D:getetag>\"fffff-abcd2\"</D:getetag>
+                        <C:calendar-data>BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n</C:calendar-data>
+                    </D:prop>
+                    <D:status>HTTP/1.1 200 OK</D:status>
+                </D:propstat>
+            </D:response>
+        </D:multistatus>";
+        let objects: Vec<CalendarObject> = ListMultiStatus::parse(xml).unwrap().into();
+        assert_eq!(
+            objects,
+            vec![CalendarObject {
+                href: "/cal/abcd2.ics".to_owned(),
+                etag: Some("\"fffff-abcd2\"".to_owned()),
+                calendar_data: Some("BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n".to_owned()),
+            }]
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Types and serialisation for the CardDAV REPORTs.
+
+use crate::types::list_cmd::{status_is_ok, ListMultiStatus};
+use crate::types::xml_escape;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextMatch {
+    #[default]
+    Contains,
+    Equals,
+    StartsWith,
+    EndsWith,
+}
+
+impl TextMatch {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TextMatch::Contains => "contains",
+            TextMatch::Equals => "equals",
+            TextMatch::StartsWith => "starts-with",
+            TextMatch::EndsWith => "ends-with",
+        }
+    }
+}
+
+/// Case insensitive match on a vCard property, e.g. `FN` or `EMAIL`.
+#[derive(Debug, Clone)]
+pub struct PropFilter {
+    pub property: String,
+    pub text: String,
+    pub match_type: TextMatch,
+}
+
+impl PropFilter {
+    pub fn contains(property: impl Into<String>, text: impl Into<String>) -> PropFilter {
+        PropFilter {
+            property: property.into(),
+            text: text.into(),
+            match_type: TextMatch::Contains,
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        format!(
+            r#"<C:prop-filter name="{}"><C:text-match collation="i;unicode-casemap" match-type="{}">{}</C:text-match></C:prop-filter>"#,
+            xml_escape(&self.property),
+            self.match_type.as_str(),
+            xml_escape(&self.text)
+        )
+    }
+}
+
+const ADDRESS_DATA_PROP: &str = "<D:prop><D:getetag/><C:address-data/></D:prop>";
+
+/// Body of an addressbook-query matching cards passing any of `filters`, or all cards.
+pub(crate) fn addressbook_query_body(filters: &[PropFilter]) -> String {
+    let filters: String = filters.iter().map(PropFilter::to_xml).collect();
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8" ?><C:addressbook-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav">{}<C:filter test="anyof">{}</C:filter></C:addressbook-query>"#,
+        ADDRESS_DATA_PROP, filters
+    )
+}
+
+pub(crate) fn addressbook_multiget_body<S: AsRef<str>>(hrefs: &[S]) -> String {
+    let hrefs: String = hrefs
+        .iter()
+        .map(|href| format!("<D:href>{}</D:href>", xml_escape(href.as_ref())))
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8" ?><C:addressbook-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:carddav">{}{}</C:addressbook-multiget>"#,
+        ADDRESS_DATA_PROP, hrefs
+    )
+}
+
+/// Href and vCard text of the cards in a CardDAV REPORT response.
+pub(crate) fn address_data(multi_status: ListMultiStatus) -> Vec<(String, String)> {
+    multi_status
+        .responses
+        .into_iter()
+        .filter_map(|response| {
+            let data = response
+                .prop_stat
+                .into_iter()
+                .filter(|prop_stat| prop_stat.status.is_empty() || status_is_ok(&prop_stat.status))
+                .find_map(|prop_stat| prop_stat.prop.address_data)?;
+            Some((response.href, data))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addressbook_query_filters() {
+        let xml = addressbook_query_body(&[PropFilter::contains("EMAIL", "@example.com")]);
+        assert!(xml.contains(r#"<C:filter test="anyof"><C:prop-filter name="EMAIL"><C:text-match collation="i;unicode-casemap" match-type="contains">@example.com</C:text-match></C:prop-filter></C:filter>"#));
+    }
+}
@@ -0,0 +1,150 @@
+//! Checksums of uploads and downloads, as used by Nextcloud and ownCloud.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use md5::{Digest, Md5};
+use sha1::Sha1;
+
+/// Algorithms of `OC-Checksum` and `oc:checksums`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Sha1,
+    Md5,
+    Adler32,
+}
+
+impl ChecksumAlgorithm {
+    /// The name used in `OC-Checksum`, `SHA1`, `MD5` or `ADLER32`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Sha1 => "SHA1",
+            ChecksumAlgorithm::Md5 => "MD5",
+            ChecksumAlgorithm::Adler32 => "ADLER32",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ChecksumAlgorithm> {
+        match name.to_ascii_uppercase().as_str() {
+            "SHA1" => Some(ChecksumAlgorithm::Sha1),
+            "MD5" => Some(ChecksumAlgorithm::Md5),
+            "ADLER32" => Some(ChecksumAlgorithm::Adler32),
+            _ => None,
+        }
+    }
+
+    pub fn compute(&self, data: &[u8]) -> Checksum {
+        let value = match self {
+            ChecksumAlgorithm::Sha1 => hex::encode(Sha1::digest(data)),
+            ChecksumAlgorithm::Md5 => hex::encode(Md5::digest(data)),
+            ChecksumAlgorithm::Adler32 => format!("{:08x}", adler32(data)),
+        };
+        Checksum {
+            algorithm: *self,
+            value,
+        }
+    }
+}
+
+/// A digest of some content, in lowercase hex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    pub value: String,
+}
+
+impl Checksum {
+    /// The checksums of an `OC-Checksum` header or `oc:checksums` property, `SHA1:abc MD5:def`
+    ///
+    /// Unknown algorithms are left out.
+    pub fn parse_list(value: &str) -> Vec<Checksum> {
+        value
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter_map(|token| {
+                let (name, value) = token.split_once(':')?;
+                Some(Checksum {
+                    algorithm: ChecksumAlgorithm::from_name(name)?,
+                    value: value.to_ascii_lowercase(),
+                })
+            })
+            .collect()
+    }
+
+    /// The MD5 of a `Content-MD5` header, which is base64 instead of hex.
+    pub fn from_content_md5(value: &str) -> Option<Checksum> {
+        let digest = STANDARD.decode(value.trim()).ok()?;
+        Some(Checksum {
+            algorithm: ChecksumAlgorithm::Md5,
+            value: hex::encode(digest),
+        })
+    }
+
+    /// The `Content-MD5` header value, for MD5 checksums only.
+    pub fn to_content_md5(&self) -> Option<String> {
+        match self.algorithm {
+            ChecksumAlgorithm::Md5 => Some(STANDARD.encode(hex::decode(&self.value).ok()?)),
+            _ => None,
+        }
+    }
+
+    /// Whether `data` has this checksum.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.algorithm.compute(data).value == self.value
+    }
+}
+
+/// The `OC-Checksum` form, `SHA1:abc`.
+impl Display for Checksum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm.name(), self.value)
+    }
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before `b` may overflow
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += u32::from(*byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_and_parse() {
+        let data = b"Wikipedia";
+        assert_eq!(ChecksumAlgorithm::Adler32.compute(data).value, "11e60398");
+        let md5 = ChecksumAlgorithm::Md5.compute(b"");
+        assert_eq!(md5.value, "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(
+            md5.to_content_md5().as_deref(),
+            Some("1B2M2Y8AsgTpgAmY7PhCfg==")
+        );
+        assert_eq!(
+            Checksum::from_content_md5("1B2M2Y8AsgTpgAmY7PhCfg=="),
+            Some(md5)
+        );
+
+        let sha1 = ChecksumAlgorithm::Sha1.compute(b"abc");
+        assert_eq!(
+            sha1.to_string(),
+            "SHA1:a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        let parsed = Checksum::parse_list(
+            "SHA1:A9993E364706816ABA3E25717850C26C9CD0D89D CRC32:352441c2 MD5:900150983cd24fb0d6963f7d28e17f72",
+        );
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed.iter().all(|checksum| checksum.matches(b"abc")));
+    }
+}
@@ -0,0 +1,12 @@
+//! Types of the requests guarded by an entity tag.
+
+use reqwest::Response;
+
+/// Answer to `Client::get_if_none_match`
+#[derive(Debug)]
+pub enum ConditionalGet {
+    /// The file changed, the response carries the new content.
+    Modified(Response),
+    /// The file still has the given tag.
+    NotModified,
+}
@@ -0,0 +1,48 @@
+//! Types describing how a COPY or MOVE is carried out.
+
+use crate::types::Depth;
+
+/// The way a copy or move reached its destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferMode {
+    /// The server handled the COPY or MOVE itself.
+    Native,
+    /// The server does not support the verb, the content was downloaded and uploaded again.
+    GetPut,
+    /// MOVE emulated by a server side COPY followed by a DELETE of the source.
+    CopyDelete,
+    /// MOVE emulated by GET + PUT followed by a DELETE of the source.
+    GetPutDelete,
+}
+
+/// Settings of `Client::mv_with`
+#[derive(Debug, Clone)]
+pub struct MoveOptions {
+    /// Replace an existing resource at the destination, the server answers 412 otherwise.
+    pub overwrite: bool,
+}
+
+impl Default for MoveOptions {
+    fn default() -> Self {
+        MoveOptions { overwrite: true }
+    }
+}
+
+/// Settings of `Client::cp_with`
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    /// Replace an existing resource at the destination, the server answers 412 otherwise.
+    pub overwrite: bool,
+    /// `Depth::Number(0)` copies a collection without its members, servers only accept
+    /// 0 and infinity.
+    pub depth: Depth,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        CopyOptions {
+            overwrite: true,
+            depth: Depth::Infinity,
+        }
+    }
+}
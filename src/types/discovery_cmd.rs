@@ -0,0 +1,98 @@
+//! Types of the CalDAV and CardDAV service discovery (RFC 6764).
+
+use quick_xml::events::Event;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+/// Where the calendars or address books of the user live, see `Client::discover_caldav`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DavHome {
+    /// The service root, after following `/.well-known/caldav` or `/.well-known/carddav`.
+    pub context_url: String,
+    /// The `current-user-principal` of the authenticated user.
+    pub principal_url: String,
+    /// The `calendar-home-set` or `addressbook-home-set` collections, usually a single one.
+    pub home_set_urls: Vec<String>,
+}
+
+/// The `DAV:href` values inside the `namespace` `name` property of a multistatus, in order.
+pub(crate) fn property_hrefs(
+    xml: &str,
+    namespace: &str,
+    name: &str,
+) -> crate::types::Result<Vec<String>> {
+    let mut reader = NsReader::from_str(xml);
+    let mut hrefs = vec![];
+    // depth of the elements open inside the property, if in one
+    let mut inside: Option<usize> = None;
+    let mut href: Option<String> = None;
+    loop {
+        let (resolved, event) = reader.read_resolved_event()?;
+        let matches = |uri: &[u8], local_name: &[u8]| {
+            matches!(&resolved, ResolveResult::Bound(Namespace(bound)) if *bound == uri)
+                && local_name == name.as_bytes()
+        };
+        match event {
+            Event::Start(element) => match &mut inside {
+                Some(depth) => {
+                    *depth += 1;
+                    if matches!(&resolved, ResolveResult::Bound(Namespace(b"DAV:")))
+                        && element.local_name().as_ref() == b"href"
+                    {
+                        href = Some(String::new());
+                    }
+                }
+                None if matches(namespace.as_bytes(), element.local_name().as_ref()) => {
+                    inside = Some(0)
+                }
+                None => {}
+            },
+            Event::End(_) => match &mut inside {
+                Some(0) => inside = None,
+                Some(depth) => {
+                    *depth -= 1;
+                    if let Some(href) = href.take() {
+                        hrefs.push(href.trim().to_owned());
+                    }
+                }
+                None => {}
+            },
+            Event::Text(text) => {
+                if let Some(href) = &mut href {
+                    href.push_str(&text.unescape()?);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(hrefs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::property_hrefs;
+
+    #[test]
+    fn hrefs_of_property() {
+        let xml = r#"<?xml version="1.0"?>
+        <d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+            <d:response>
+                <d:href>/principals/alice/</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <cal:calendar-home-set>
+                            <d:href>/calendars/alice/</d:href>
+                            <d:href>/shared/alice/</d:href>
+                        </cal:calendar-home-set>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+        assert_eq!(
+            property_hrefs(xml, "urn:ietf:params:xml:ns:caldav", "calendar-home-set").unwrap(),
+            vec!["/calendars/alice/", "/shared/alice/"]
+        );
+    }
+}
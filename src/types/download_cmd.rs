@@ -0,0 +1,48 @@
+//! Types of streamed downloads.
+
+use std::ops::{RangeFrom, RangeInclusive};
+
+/// How far a streamed download got, reported after every chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    /// `Content-Length` of the response, if given.
+    pub total: Option<u64>,
+}
+
+/// Bytes of a file to get, see `Client::get_range`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// From the offset to the end of the file.
+    From(u64),
+    /// From `start` to `end`, both included.
+    Bounded { start: u64, end: u64 },
+    /// The given number of bytes at the end of the file.
+    Last(u64),
+}
+
+impl ByteRange {
+    /// Value of the `Range` header.
+    pub fn to_header(&self) -> String {
+        match self {
+            ByteRange::From(start) => format!("bytes={}-", start),
+            ByteRange::Bounded { start, end } => format!("bytes={}-{}", start, end),
+            ByteRange::Last(length) => format!("bytes=-{}", length),
+        }
+    }
+}
+
+impl From<RangeFrom<u64>> for ByteRange {
+    fn from(range: RangeFrom<u64>) -> Self {
+        ByteRange::From(range.start)
+    }
+}
+
+impl From<RangeInclusive<u64>> for ByteRange {
+    fn from(range: RangeInclusive<u64>) -> Self {
+        ByteRange::Bounded {
+            start: *range.start(),
+            end: *range.end(),
+        }
+    }
+}
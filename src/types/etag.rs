@@ -0,0 +1,71 @@
+//! Entity tag handling shared by the conditional request helpers.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// A parsed `ETag`/`getetag` value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ETag {
+    /// `W/` prefixed tags only support weak comparison.
+    pub weak: bool,
+    /// The opaque tag without quotes.
+    pub tag: String,
+}
+
+impl ETag {
+    pub fn parse(value: &str) -> ETag {
+        let value = value.trim();
+        let (weak, value) = match value.strip_prefix("W/") {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        ETag {
+            weak,
+            tag: value.trim_matches('"').to_owned(),
+        }
+    }
+
+    /// RFC 7232 strong comparison, both tags must be strong and identical.
+    pub fn strong_eq(&self, other: &ETag) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// RFC 7232 weak comparison, the opaque tags must be identical.
+    pub fn weak_eq(&self, other: &ETag) -> bool {
+        self.tag == other.tag
+    }
+
+    /// Value to send in `If-Match`/`If-None-Match`
+    ///
+    /// Apache mod_dav hands out weak tags for files modified within the last second but only does
+    /// strong comparison on `If-Match`, so `strip_weak` sends those tags as strong ones.
+    pub fn to_header(&self, strip_weak: bool) -> String {
+        if self.weak && !strip_weak {
+            format!("W/\"{}\"", self.tag)
+        } else {
+            format!("\"{}\"", self.tag)
+        }
+    }
+}
+
+impl Display for ETag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_header(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weak_apache_tag_compares_weakly() {
+        let weak = ETag::parse("W/\"3e-5d3f1f2c8e5c0\"");
+        let strong = ETag::parse("\"3e-5d3f1f2c8e5c0\"");
+        assert!(weak.weak);
+        assert!(weak.weak_eq(&strong));
+        assert!(!weak.strong_eq(&strong));
+        assert_eq!(weak.to_header(true), "\"3e-5d3f1f2c8e5c0\"");
+        assert_eq!(weak.to_string(), "W/\"3e-5d3f1f2c8e5c0\"");
+    }
+}
@@ -0,0 +1,93 @@
+//! Artificial failures for exercising error handling in tests.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::Method;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Wait before sending the request.
+    Delay(Duration),
+    /// Answer with this status instead of sending the request.
+    Status(u16),
+    /// Fail as if the connection was dropped, with an `Error::Io` of kind `ConnectionReset`.
+    DropConnection,
+}
+
+/// When a fault applies.
+#[derive(Debug, Clone)]
+pub struct FaultRule {
+    pub fault: Fault,
+    pub method: Option<Method>,
+    /// Applies to urls containing this.
+    pub path_contains: Option<String>,
+    /// Number of requests the fault applies to, `None` for all of them.
+    pub times: Option<usize>,
+}
+
+impl FaultRule {
+    pub fn new(fault: Fault) -> FaultRule {
+        FaultRule {
+            fault,
+            method: None,
+            path_contains: None,
+            times: None,
+        }
+    }
+
+    pub fn method(mut self, method: Method) -> FaultRule {
+        self.method = Some(method);
+        self
+    }
+
+    pub fn path_contains(mut self, path: &str) -> FaultRule {
+        self.path_contains = Some(path.to_owned());
+        self
+    }
+
+    pub fn times(mut self, times: usize) -> FaultRule {
+        self.times = Some(times);
+        self
+    }
+}
+
+/// Rules injecting faults into the requests of a client, see `ClientBuilder::set_fault_injector`
+///
+/// Rules are checked in the order they were added, the first one matching a request applies.
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    pub(crate) rules: Arc<Mutex<Vec<FaultRule>>>,
+}
+
+impl FaultInjector {
+    pub fn new() -> FaultInjector {
+        Default::default()
+    }
+
+    pub fn add(&self, rule: FaultRule) {
+        self.rules.lock().unwrap().push(rule);
+    }
+
+    pub fn clear(&self) {
+        self.rules.lock().unwrap().clear();
+    }
+
+    /// The fault for a request, using up one of the times of its rule.
+    pub(crate) fn fault_for(&self, method: &Method, url: &str) -> Option<Fault> {
+        let mut rules = self.rules.lock().unwrap();
+        let rule = rules.iter_mut().find(|rule| {
+            rule.times != Some(0)
+                && rule.method.as_ref().map(|m| m == method).unwrap_or(true)
+                && rule
+                    .path_contains
+                    .as_ref()
+                    .map(|path| url.contains(path.as_str()))
+                    .unwrap_or(true)
+        })?;
+        if let Some(times) = rule.times.as_mut() {
+            *times -= 1;
+        }
+        Some(rule.fault.clone())
+    }
+}
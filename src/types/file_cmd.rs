@@ -0,0 +1,131 @@
+//! Types of the remote file handles.
+
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+
+use bytes::Bytes;
+
+use crate::types::put_cmd::PutResponse;
+use crate::types::Result;
+use crate::Client;
+
+/// Bytes read ahead by default with every range request of a `DavFile`.
+pub(crate) const DEFAULT_READ_AHEAD: usize = 1024 * 1024;
+
+/// A remote file read with `Range` requests, see `Client::open`
+///
+/// Implements `AsyncRead` and `AsyncSeek`. Every request gets at least the read-ahead size, reads
+/// within the last response are answered from memory. Seeking is free, the next read fetches
+/// the bytes at the new position.
+pub struct DavFile {
+    pub(crate) client: Client,
+    pub(crate) path: String,
+    pub(crate) len: u64,
+    /// Sent in `If-Match` so reads fail once the file changes, unless it is a weak tag.
+    pub(crate) etag: Option<String>,
+    pub(crate) position: u64,
+    pub(crate) buffer: Bytes,
+    /// Offset of the first byte of `buffer` in the file.
+    pub(crate) buffer_start: u64,
+    pub(crate) read_ahead: usize,
+    pub(crate) pending: Option<PendingRead>,
+}
+
+/// A range request in flight.
+pub(crate) struct PendingRead {
+    pub(crate) start: u64,
+    pub(crate) future: Pin<Box<dyn Future<Output = Result<Bytes>> + Send>>,
+}
+
+impl DavFile {
+    /// Fetch at least `bytes` bytes with every request
+    pub fn with_read_ahead(mut self, bytes: usize) -> Self {
+        self.read_ahead = bytes.max(1);
+        self
+    }
+
+    /// Size of the file when it was opened
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The offset the next read starts at
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The tag the file had when it was opened
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+}
+
+impl Debug for DavFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DavFile")
+            .field("path", &self.path)
+            .field("len", &self.len)
+            .field("etag", &self.etag)
+            .field("position", &self.position)
+            .field("read_ahead", &self.read_ahead)
+            .finish()
+    }
+}
+
+/// A remote file written through memory, see `Client::create` and `Client::append`
+///
+/// Implements `AsyncWrite`. Writes are buffered and uploaded with a single PUT by `shutdown`,
+/// `flush` does not send anything. Writing after `shutdown` fails with `BrokenPipe`.
+pub struct DavFileWriter {
+    pub(crate) client: Client,
+    pub(crate) path: String,
+    pub(crate) buffer: Vec<u8>,
+    /// Larger uploads to Nextcloud use the chunking protocol, see `with_chunk_size`.
+    #[cfg(feature = "nextcloud")]
+    pub(crate) chunk_size: Option<usize>,
+    pub(crate) upload: Option<Pin<Box<dyn Future<Output = Result<PutResponse>> + Send>>>,
+    pub(crate) response: Option<PutResponse>,
+}
+
+impl DavFileWriter {
+    /// Upload files larger than `chunk_size` with `Client::put_chunked`
+    ///
+    /// Only used when the host of the client is a Nextcloud files root, other servers get a
+    /// single PUT regardless of the size.
+    #[cfg(feature = "nextcloud")]
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size.max(1));
+        self
+    }
+
+    /// Bytes written so far
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// What the server answered to the upload, once `shutdown` completed
+    pub fn response(&self) -> Option<&PutResponse> {
+        self.response.as_ref()
+    }
+}
+
+impl Debug for DavFileWriter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DavFileWriter")
+            .field("path", &self.path)
+            .field("len", &self.buffer.len())
+            .field("uploading", &self.upload.is_some())
+            .field("response", &self.response)
+            .finish()
+    }
+}
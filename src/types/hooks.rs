@@ -0,0 +1,84 @@
+//! Callbacks observing the requests of a client.
+
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A request about to be sent.
+#[derive(Debug, Clone)]
+pub struct RequestSummary {
+    pub method: String,
+    pub url: String,
+    pub request_id: Option<String>,
+    /// Length of the body, unknown for streamed bodies.
+    pub bytes_sent: Option<u64>,
+}
+
+/// The outcome of a request.
+#[derive(Debug, Clone)]
+pub struct ResponseSummary {
+    pub method: String,
+    pub url: String,
+    pub request_id: Option<String>,
+    /// `None` when no response was received.
+    pub status: Option<u16>,
+    pub elapsed: Duration,
+    /// `Content-Length` of the response, if given.
+    pub bytes_received: Option<u64>,
+}
+
+/// Operations which destroy data on the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    /// DELETE, recursive for collections.
+    Delete,
+    /// MOVE, overwriting an existing destination.
+    Move,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditStage {
+    Before,
+    /// `status` is `None` when no response was received.
+    After {
+        success: bool,
+        status: Option<u16>,
+    },
+}
+
+/// A destructive operation passed to the audit hook, see `ClientBuilder::on_audit`
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub action: AuditAction,
+    pub path: String,
+    pub destination: Option<String>,
+    pub stage: AuditStage,
+}
+
+pub type RequestHook = Arc<dyn Fn(&RequestSummary) + Send + Sync>;
+pub type ResponseHook = Arc<dyn Fn(&ResponseSummary) + Send + Sync>;
+/// Called before and after destructive operations, returning `false` before one cancels it.
+pub type AuditHook = Arc<dyn Fn(&AuditEvent) -> bool + Send + Sync>;
+/// Called with the request and the time it has been in flight.
+pub type SlowRequestHook = Arc<dyn Fn(&RequestSummary, Duration) + Send + Sync>;
+
+/// Callbacks invoked around every request, see `ClientBuilder::on_request`
+#[derive(Clone, Default)]
+pub struct Hooks {
+    pub on_request: Option<RequestHook>,
+    pub on_response: Option<ResponseHook>,
+    pub on_slow_request: Option<SlowRequestHook>,
+    pub on_audit: Option<AuditHook>,
+}
+
+impl Debug for Hooks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("on_request", &self.on_request.is_some())
+            .field("on_response", &self.on_response.is_some())
+            .field("on_slow_request", &self.on_slow_request.is_some())
+            .field("on_audit", &self.on_audit.is_some())
+            .finish()
+    }
+}
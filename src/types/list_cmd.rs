@@ -1,13 +1,28 @@
 //! Types and serialisation expected for the PROPFIND command.
 
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use web_time::Instant;
+
+use crate::types::acl_cmd::Privileges;
+use crate::types::lock_cmd::LockScope;
+use crate::types::timestamp::{parse_http_date, DateTime, DavDateTime};
 use crate::types::{DecodeError, Error, FieldError};
-use chrono::{DateTime, Utc};
+use crate::Client;
+use quick_xml::events::Event;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListMultiStatus {
     #[serde(rename = "response")]
     pub responses: Vec<ListResponse>,
+    /// Token to pass to the next sync-collection REPORT, only in responses to one.
+    #[serde(rename = "sync-token", default)]
+    pub sync_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,10 +30,14 @@ pub struct ListResponse {
     pub href: String,
     #[serde(rename = "propstat")]
     pub prop_stat: Vec<ListPropStat>,
+    /// IIS reports the status on the response instead of each propstat.
+    #[serde(default)]
+    pub status: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListPropStat {
+    #[serde(default)]
     pub status: String,
     pub prop: ListProp,
 }
@@ -33,10 +52,10 @@ pub struct ListResourceType {
     pub redirect_lifetime: Option<()>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ListProp {
     #[serde(rename = "getlastmodified", deserialize_with = "http_time", default)]
-    pub last_modified: Option<DateTime<Utc>>,
+    pub last_modified: Option<DateTime>,
     #[serde(rename = "resourcetype", default)]
     pub resource_type: ListResourceType,
     #[serde(
@@ -61,33 +80,764 @@ pub struct ListProp {
     pub content_length: Option<i64>,
     #[serde(rename = "getcontenttype")]
     pub content_type: Option<String>,
+    #[serde(rename = "getcontentlanguage")]
+    pub content_language: Option<String>,
+    #[serde(rename = "displayname")]
+    pub display_name: Option<String>,
+    #[serde(
+        rename = "creationdate",
+        with = "crate::types::timestamp::rfc3339_option",
+        default
+    )]
+    pub creation_date: Option<DateTime>,
+    /// IIS flavour of `resourcetype`, `1` for collections.
+    #[serde(rename = "iscollection", default)]
+    pub is_collection: Option<String>,
+    /// iCalendar text returned by CalDAV reports.
+    #[serde(rename = "calendar-data", default)]
+    pub calendar_data: Option<String>,
+    /// vCard text returned by CardDAV reports.
+    #[serde(rename = "address-data", default)]
+    pub address_data: Option<String>,
+    /// `cs:getctag` of calendars and address books, changing with any of their members.
+    #[serde(rename = "getctag", default)]
+    pub ctag: Option<String>,
+    /// The privileges of the current user, from `current-user-privilege-set`.
+    #[serde(skip)]
+    pub privileges: Option<Privileges>,
+    /// Principal href of the `owner` of the resource.
+    #[serde(skip)]
+    pub owner: Option<String>,
+    /// The reports of `supported-report-set`, by namespace and local name.
+    #[serde(skip)]
+    pub supported_reports: Option<Vec<(String, String)>>,
+    /// The lock scopes of `supportedlock`, empty when the resource cannot be locked.
+    #[serde(skip)]
+    pub supported_locks: Option<Vec<LockScope>>,
+    /// Text of the properties without a field of their own, like `oc:fileid`, keyed by
+    /// namespace and local name.
+    #[serde(skip)]
+    pub other: HashMap<(String, String), String>,
+}
+
+impl ListMultiStatus {
+    /// Parse the multistatus body of a PROPFIND response
+    ///
+    /// Elements are matched on the `DAV:` namespace whatever their prefix, elements of other
+    /// namespaces sharing a local name with a DAV property are not mistaken for it. Properties
+    /// without a field of their own are kept as text in `ListProp::other`.
+    pub fn parse(xml: &str) -> crate::types::Result<ListMultiStatus> {
+        Self::parse_with(xml, false, false)
+    }
+
+    /// Parse a multistatus with the leniency of `ServerQuirks::lenient_parsing`.
+    pub(crate) fn parse_lenient(xml: &str) -> crate::types::Result<ListMultiStatus> {
+        Self::parse_with(xml, false, true)
+    }
+
+    /// Parse a multistatus keeping every property as text in `ListProp::other`.
+    pub(crate) fn parse_raw(xml: &str) -> crate::types::Result<ListMultiStatus> {
+        Self::parse_with(xml, true, false)
+    }
+
+    fn parse_with(xml: &str, raw: bool, lenient: bool) -> crate::types::Result<ListMultiStatus> {
+        let mut reader = NsReader::from_str(xml);
+        let mut parser = MultiStatusParser::new(raw).lenient(lenient);
+        loop {
+            let (namespace, event) = reader.read_resolved_event()?;
+            if !parser.event(&namespace, event)? {
+                break;
+            }
+        }
+        parser.finish()?;
+        Ok(ListMultiStatus {
+            responses: parser.responses,
+            sync_token: parser.sync_token,
+        })
+    }
+}
+
+pub(crate) const CALDAV_NAMESPACE: &str = "urn:ietf:params:xml:ns:caldav";
+pub(crate) const CARDDAV_NAMESPACE: &str = "urn:ietf:params:xml:ns:carddav";
+pub(crate) const CALENDARSERVER_NAMESPACE: &str = "http://calendarserver.org/ns/";
+
+/// The local name of an element of the `DAV:` namespace, prefixed with `caldav:` or `carddav:`
+/// for the elements of those namespaces and `cs:` for the calendarserver ones.
+fn dav_name(namespace: &ResolveResult, local_name: &[u8]) -> Option<String> {
+    let local_name = String::from_utf8_lossy(local_name);
+    match namespace {
+        ResolveResult::Bound(Namespace(b"DAV:")) => Some(local_name.into_owned()),
+        ResolveResult::Bound(Namespace(uri)) if *uri == CALDAV_NAMESPACE.as_bytes() => {
+            Some(format!("caldav:{}", local_name))
+        }
+        ResolveResult::Bound(Namespace(uri)) if *uri == CARDDAV_NAMESPACE.as_bytes() => {
+            Some(format!("carddav:{}", local_name))
+        }
+        ResolveResult::Bound(Namespace(uri)) if *uri == CALENDARSERVER_NAMESPACE.as_bytes() => {
+            Some(format!("cs:{}", local_name))
+        }
+        _ => None,
+    }
+}
+
+/// The namespace and local name of an element.
+pub(crate) fn qualified_name(namespace: &ResolveResult, local_name: &[u8]) -> (String, String) {
+    let namespace = match namespace {
+        ResolveResult::Bound(Namespace(uri)) => String::from_utf8_lossy(uri).into_owned(),
+        _ => String::new(),
+    };
+    (namespace, String::from_utf8_lossy(local_name).into_owned())
 }
 
+/// Properties parsed into a field of `ListProp`.
+fn is_known_property(name: &str) -> bool {
+    matches!(
+        name,
+        "getlastmodified"
+            | "getetag"
+            | "getcontenttype"
+            | "getcontentlength"
+            | "getcontentlanguage"
+            | "displayname"
+            | "creationdate"
+            | "quota-used-bytes"
+            | "quota-available-bytes"
+            | "iscollection"
+            | "resourcetype"
+            | "caldav:calendar-data"
+            | "carddav:address-data"
+            | "cs:getctag"
+            | "current-user-privilege-set"
+            | "owner"
+            | "supported-report-set"
+            | "supportedlock"
+    )
+}
+
+#[derive(Default)]
+pub(crate) struct MultiStatusParser {
+    /// Local names of the open elements, `None` for the ones outside the `DAV:` namespace.
+    stack: Vec<Option<String>>,
+    text: String,
+    found_root: bool,
+    responses: Vec<ListResponse>,
+    sync_token: Option<String>,
+    response: Option<ListResponse>,
+    prop_stat: Option<ListPropStat>,
+    /// Keep every property as text, not only the unknown ones.
+    raw: bool,
+    /// Drop malformed values instead of failing, see `ServerQuirks::lenient_parsing`.
+    lenient: bool,
+    /// The property kept as text being read, with the depth of its element.
+    property: Option<((String, String), usize)>,
+}
+
+impl MultiStatusParser {
+    /// Parser keeping every property as text when `raw` is set.
+    pub(crate) fn new(raw: bool) -> MultiStatusParser {
+        MultiStatusParser {
+            raw,
+            ..Default::default()
+        }
+    }
+
+    /// Drop malformed values and default missing dates, see `ServerQuirks::lenient_parsing`.
+    pub(crate) fn lenient(mut self, lenient: bool) -> MultiStatusParser {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Handle the next event of the reader, false once the document ended.
+    pub(crate) fn event(
+        &mut self,
+        namespace: &ResolveResult,
+        event: Event,
+    ) -> crate::types::Result<bool> {
+        match event {
+            // the content of a property kept as text
+            Event::Start(_) if self.property.is_some() => self.stack.push(None),
+            Event::Empty(_) if self.property.is_some() => {}
+            Event::End(_) if self.property.is_some() => {
+                self.stack.pop();
+                self.end_property();
+            }
+            Event::Start(element) => {
+                let name = dav_name(namespace, element.local_name().as_ref());
+                self.supported_report(namespace, element.local_name().as_ref());
+                if self.is_other_property(name.as_deref()) {
+                    let key = qualified_name(namespace, element.local_name().as_ref());
+                    self.property = Some((key, self.stack.len()));
+                    self.text.clear();
+                } else {
+                    self.start(name.as_deref());
+                }
+                self.stack.push(name);
+            }
+            Event::Empty(element) => {
+                let name = dav_name(namespace, element.local_name().as_ref());
+                self.supported_report(namespace, element.local_name().as_ref());
+                if self.is_other_property(name.as_deref()) {
+                    let key = qualified_name(namespace, element.local_name().as_ref());
+                    self.property = Some((key, self.stack.len()));
+                    self.text.clear();
+                    self.end_property();
+                } else {
+                    self.start(name.as_deref());
+                    self.end(name.as_deref())?;
+                }
+            }
+            Event::End(_) => {
+                let name = self.stack.pop().flatten();
+                self.end(name.as_deref())?;
+            }
+            Event::Text(text) => self.text.push_str(&text.unescape()?),
+            Event::CData(data) => self.text.push_str(&String::from_utf8_lossy(&data)),
+            Event::Eof => return Ok(false),
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    /// The responses parsed so far, in document order.
+    pub(crate) fn take_responses(&mut self) -> Vec<ListResponse> {
+        std::mem::take(&mut self.responses)
+    }
+
+    /// Fail when the document had no multistatus root.
+    pub(crate) fn finish(&self) -> crate::types::Result<()> {
+        if !self.found_root {
+            return Err(Error::Decode(DecodeError::FieldNotFound(FieldError {
+                field: "multistatus".to_owned(),
+            })));
+        }
+        Ok(())
+    }
+
+    fn parent(&self) -> Option<&str> {
+        self.ancestor(0)
+    }
+
+    /// The open element `generations` levels above the parent.
+    fn ancestor(&self, generations: usize) -> Option<&str> {
+        let index = self.stack.len().checked_sub(generations + 1)?;
+        self.stack[index].as_deref()
+    }
+
+    /// Record an element naming a report of `supported-report-set`, in any namespace.
+    fn supported_report(&mut self, namespace: &ResolveResult, local_name: &[u8]) {
+        if self.parent() != Some("report")
+            || self.ancestor(1) != Some("supported-report")
+            || self.ancestor(2) != Some("supported-report-set")
+        {
+            return;
+        }
+        if let Some(prop_stat) = &mut self.prop_stat {
+            prop_stat
+                .prop
+                .supported_reports
+                .get_or_insert_default()
+                .push(qualified_name(namespace, local_name));
+        }
+    }
+
+    fn is_other_property(&self, name: Option<&str>) -> bool {
+        self.parent() == Some("prop")
+            && self.prop_stat.is_some()
+            && (self.raw || !name.is_some_and(is_known_property))
+    }
+
+    fn end_property(&mut self) {
+        match &self.property {
+            Some((_, depth)) if *depth == self.stack.len() => {}
+            _ => return,
+        }
+        if let (Some((key, _)), Some(prop_stat)) = (self.property.take(), &mut self.prop_stat) {
+            let text = std::mem::take(&mut self.text);
+            prop_stat.prop.other.insert(key, text.trim().to_owned());
+        }
+    }
+
+    fn start(&mut self, name: Option<&str>) {
+        self.text.clear();
+        match (self.parent(), name) {
+            (None, Some("multistatus")) if self.stack.is_empty() => self.found_root = true,
+            (Some("multistatus"), Some("response")) => {
+                self.response = Some(ListResponse {
+                    href: String::new(),
+                    prop_stat: vec![],
+                    status: None,
+                })
+            }
+            (Some("response"), Some("propstat")) => {
+                self.prop_stat = Some(ListPropStat {
+                    status: String::new(),
+                    prop: ListProp::default(),
+                })
+            }
+            (Some("prop"), Some("current-user-privilege-set")) => {
+                if let Some(prop_stat) = &mut self.prop_stat {
+                    prop_stat.prop.privileges = Some(Privileges::empty());
+                }
+            }
+            (Some("privilege"), Some(privilege))
+                if self.ancestor(1) == Some("current-user-privilege-set") =>
+            {
+                let privilege = Privileges::from_name(privilege);
+                if let (Some(prop_stat), Some(privilege)) = (&mut self.prop_stat, privilege) {
+                    prop_stat
+                        .prop
+                        .privileges
+                        .get_or_insert_default()
+                        .insert(privilege);
+                }
+            }
+            (Some("prop"), Some("supported-report-set")) => {
+                if let Some(prop_stat) = &mut self.prop_stat {
+                    prop_stat.prop.supported_reports = Some(vec![]);
+                }
+            }
+            (Some("prop"), Some("supportedlock")) => {
+                if let Some(prop_stat) = &mut self.prop_stat {
+                    prop_stat.prop.supported_locks = Some(vec![]);
+                }
+            }
+            (Some("lockscope"), Some(scope))
+                if self.ancestor(1) == Some("lockentry")
+                    && self.ancestor(2) == Some("supportedlock") =>
+            {
+                let scope = match scope {
+                    "exclusive" => LockScope::Exclusive,
+                    "shared" => LockScope::Shared,
+                    _ => return,
+                };
+                if let Some(prop_stat) = &mut self.prop_stat {
+                    prop_stat
+                        .prop
+                        .supported_locks
+                        .get_or_insert_default()
+                        .push(scope);
+                }
+            }
+            (Some("resourcetype"), Some(kind)) => {
+                if let Some(prop_stat) = &mut self.prop_stat {
+                    let resource_type = &mut prop_stat.prop.resource_type;
+                    match kind {
+                        "collection" => resource_type.collection = Some(()),
+                        "redirectref" => resource_type.redirect_ref = Some(()),
+                        "redirect-lifetime" => resource_type.redirect_lifetime = Some(()),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: Option<&str>) -> crate::types::Result<()> {
+        // calendar and address data keep their line endings, the rest is trimmed
+        let raw = std::mem::take(&mut self.text);
+        let text = raw.trim().to_owned();
+        match (self.parent(), name) {
+            (Some("multistatus"), Some("response")) => {
+                if let Some(mut response) = self.response.take() {
+                    if self.lenient {
+                        default_last_modified(&mut response);
+                    }
+                    self.responses.push(response);
+                }
+            }
+            (Some("multistatus"), Some("sync-token")) => self.sync_token = Some(text),
+            (Some("response"), Some(name)) => {
+                if let Some(response) = &mut self.response {
+                    match name {
+                        "href" => response.href = text,
+                        "status" => response.status = Some(text),
+                        "propstat" => response.prop_stat.extend(self.prop_stat.take()),
+                        _ => {}
+                    }
+                }
+            }
+            (Some("owner"), Some("href")) if self.ancestor(1) == Some("prop") => {
+                if let Some(prop_stat) = &mut self.prop_stat {
+                    prop_stat.prop.owner = Some(text);
+                }
+            }
+            (Some("propstat"), Some("status")) => {
+                if let Some(prop_stat) = &mut self.prop_stat {
+                    prop_stat.status = text;
+                }
+            }
+            (Some("prop"), Some(name)) => {
+                let lenient = self.lenient;
+                if let Some(prop_stat) = &mut self.prop_stat {
+                    let prop = &mut prop_stat.prop;
+                    let number = |text: &str| tolerate(lenient, parse_number(name, text));
+                    match name {
+                        "getlastmodified" => {
+                            prop.last_modified =
+                                tolerate(lenient, parse_http_time(&text).map(Some))?
+                        }
+                        "getetag" => prop.tag = Some(text),
+                        "getcontenttype" => prop.content_type = Some(text),
+                        "getcontentlanguage" => prop.content_language = Some(text),
+                        "displayname" => prop.display_name = Some(text),
+                        "creationdate" => {
+                            prop.creation_date = tolerate(lenient, parse_creation_date(&text))?
+                        }
+                        "getcontentlength" => prop.content_length = number(&text)?,
+                        "quota-used-bytes" => prop.quota_used_bytes = number(&text)?,
+                        "quota-available-bytes" => prop.quota_available_bytes = number(&text)?,
+                        "iscollection" => prop.is_collection = Some(text),
+                        "caldav:calendar-data" => prop.calendar_data = Some(raw),
+                        "carddav:address-data" => prop.address_data = Some(raw),
+                        "cs:getctag" => prop.ctag = Some(text),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// A value which failed to parse is `None` when `lenient`.
+fn tolerate<T>(
+    lenient: bool,
+    value: crate::types::Result<Option<T>>,
+) -> crate::types::Result<Option<T>> {
+    match value {
+        Err(_) if lenient => Ok(None),
+        value => value,
+    }
+}
+
+/// List a response without any `getlastmodified` as modified at the Unix epoch.
+fn default_last_modified(response: &mut ListResponse) {
+    if response
+        .prop_stat
+        .iter()
+        .all(|prop_stat| prop_stat.prop.last_modified.is_none())
+    {
+        for prop_stat in &mut response.prop_stat {
+            prop_stat.prop.last_modified = Some(DateTime::unix_epoch());
+        }
+    }
+}
+
+fn parse_http_time(value: &str) -> crate::types::Result<DateTime> {
+    parse_http_date(value).ok_or_else(|| {
+        Error::Decode(DecodeError::FieldNotSupported(FieldError {
+            field: format!("getlastmodified {}", value),
+        }))
+    })
+}
+
+/// An RFC 3339 `creationdate`, HTTP dates are accepted as well.
+fn parse_creation_date(value: &str) -> crate::types::Result<Option<DateTime>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    parse_http_date(value).map(Some).ok_or_else(|| {
+        Error::Decode(DecodeError::FieldNotSupported(FieldError {
+            field: format!("creationdate {}", value),
+        }))
+    })
+}
+
+/// A number which may be left empty.
+fn parse_number(name: &str, value: &str) -> crate::types::Result<Option<i64>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value.parse().map(Some).map_err(|_| {
+        Error::Decode(DecodeError::FieldNotSupported(FieldError {
+            field: format!("{} {}", name, value),
+        }))
+    })
+}
+
+impl ListProp {
+    fn is_collection(&self) -> bool {
+        self.resource_type.collection.is_some()
+            || matches!(self.is_collection.as_deref(), Some("1") | Some("true"))
+    }
+}
+
+/// Version of the serialized form of `ListEntity`.
+pub const LIST_SCHEMA_VERSION: u32 = 1;
+
+/// A resource of a listing
+///
+/// The serialized form is stable and versioned, for persisting listings or passing them
+/// between processes. In JSON:
+///
+/// ```json
+/// {"schemaVersion": 1, "type": "file", "href": "/dav/a.txt",
+///  "lastModified": "2019-04-10T14:00:00Z", "contentLength": 1234,
+///  "contentType": "text/plain", "tag": "\"5cafae80b1e3e\""}
+/// ```
+///
+/// Folders have `"type": "folder"` and the `quotaUsedBytes` and `quotaAvailableBytes` fields
+/// instead of `contentLength` and `contentType`. The optional `displayName`, `creationDate`,
+/// `supportedReports`, `supportedLocks` and, for files, `contentLanguage` fields are left out
+/// when the server did not send them. Dates
+/// are RFC 3339 in UTC with second precision. Entities with an unknown `schemaVersion` are
+/// rejected.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "VersionedEntity", try_from = "VersionedEntity")]
 pub enum ListEntity {
     File(ListFile),
     Folder(ListFolder),
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionedEntity {
+    schema_version: u32,
+    #[serde(flatten)]
+    entity: TaggedEntity,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum TaggedEntity {
+    File(ListFile),
+    Folder(ListFolder),
+}
+
+impl From<ListEntity> for VersionedEntity {
+    fn from(entity: ListEntity) -> Self {
+        VersionedEntity {
+            schema_version: LIST_SCHEMA_VERSION,
+            entity: match entity {
+                ListEntity::File(file) => TaggedEntity::File(file),
+                ListEntity::Folder(folder) => TaggedEntity::Folder(folder),
+            },
+        }
+    }
+}
+
+impl TryFrom<VersionedEntity> for ListEntity {
+    type Error = String;
+
+    fn try_from(versioned: VersionedEntity) -> std::result::Result<Self, Self::Error> {
+        if versioned.schema_version != LIST_SCHEMA_VERSION {
+            return Err(format!(
+                "unsupported schema version {}",
+                versioned.schema_version
+            ));
+        }
+        Ok(match versioned.entity {
+            TaggedEntity::File(file) => ListEntity::File(file),
+            TaggedEntity::Folder(folder) => ListEntity::Folder(folder),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListFile {
     pub href: String,
-    pub last_modified: DateTime<Utc>,
+    #[serde(with = "crate::types::timestamp::rfc3339")]
+    pub last_modified: DateTime,
     pub content_length: i64,
     pub content_type: String,
     pub tag: Option<String>,
+    /// `displayname`, a name for display which may differ from the last segment of the href.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::timestamp::rfc3339_option"
+    )]
+    pub creation_date: Option<DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_language: Option<String>,
+    /// `supported-report-set`, see `ListEntity::supports_report`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supported_reports: Option<Vec<(String, String)>>,
+    /// `supportedlock`, see `ListEntity::supports_lock`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supported_locks: Option<Vec<LockScope>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListFolder {
     pub href: String,
-    pub last_modified: DateTime<Utc>,
+    #[serde(with = "crate::types::timestamp::rfc3339")]
+    pub last_modified: DateTime,
     pub quota_used_bytes: Option<i64>,
     pub quota_available_bytes: Option<i64>,
     pub tag: Option<String>,
+    /// `displayname`, a name for display which may differ from the last segment of the href.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::types::timestamp::rfc3339_option"
+    )]
+    pub creation_date: Option<DateTime>,
+    /// `supported-report-set`, see `ListEntity::supports_report`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supported_reports: Option<Vec<(String, String)>>,
+    /// `supportedlock`, see `ListEntity::supports_lock`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supported_locks: Option<Vec<LockScope>>,
+}
+
+impl ListFile {
+    /// Path of the file for use with `client`, see `Client::path_of`
+    pub fn rel_path(&self, client: &Client) -> String {
+        client.path_of(&self.href)
+    }
+
+    /// Percent-decoded last segment of the href, `a b.txt` for `/dav/a%20b.txt`
+    pub fn name(&self) -> String {
+        href_name(&self.href)
+    }
+}
+
+impl ListFolder {
+    /// Path of the folder for use with `client`, see `Client::path_of`
+    pub fn rel_path(&self, client: &Client) -> String {
+        client.path_of(&self.href)
+    }
+
+    /// Percent-decoded last segment of the href, empty for the root of the server
+    pub fn name(&self) -> String {
+        href_name(&self.href)
+    }
+}
+
+impl ListEntity {
+    /// Path of the resource for use with `client`, see `Client::path_of`
+    pub fn rel_path(&self, client: &Client) -> String {
+        match self {
+            ListEntity::File(file) => file.rel_path(client),
+            ListEntity::Folder(folder) => folder.rel_path(client),
+        }
+    }
+
+    /// Percent-decoded last segment of the href
+    pub fn name(&self) -> String {
+        match self {
+            ListEntity::File(file) => file.name(),
+            ListEntity::Folder(folder) => folder.name(),
+        }
+    }
+
+    /// Whether the `namespace` `name` report is in the `supported-report-set`, `None` when the
+    /// server did not return it
+    ///
+    /// `supported-report-set` is not part of `allprop`, ask for it with
+    /// `PropfindRequest::all_prop_include`.
+    pub fn supports_report(&self, namespace: &str, name: &str) -> Option<bool> {
+        let reports = match self {
+            ListEntity::File(file) => file.supported_reports.as_ref(),
+            ListEntity::Folder(folder) => folder.supported_reports.as_ref(),
+        }?;
+        Some(
+            reports
+                .iter()
+                .any(|(report_namespace, report)| report_namespace == namespace && report == name),
+        )
+    }
+
+    /// Whether the resource can be locked with `scope`, `None` when the server did not return
+    /// `supportedlock`
+    pub fn supports_lock(&self, scope: LockScope) -> Option<bool> {
+        let locks = match self {
+            ListEntity::File(file) => file.supported_locks.as_ref(),
+            ListEntity::Folder(folder) => folder.supported_locks.as_ref(),
+        }?;
+        Some(locks.contains(&scope))
+    }
+}
+
+fn href_name(href: &str) -> String {
+    let name = href
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    percent_encoding::percent_decode_str(name)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// Result of a listing which may have been cancelled before completion.
+#[derive(Debug, Clone)]
+pub struct ListReport {
+    /// Everything listed until the listing finished or was cancelled.
+    pub entities: Vec<ListEntity>,
+    /// Collections which were not listed because the listing stopped early.
+    pub pending: Vec<String>,
+    /// The listing was cancelled or ran out of time.
+    pub cancelled: bool,
 }
 
-fn status_is_ok(status: &str) -> bool {
+/// Listings with the time they were made, by url without trailing slash and depth.
+type ListCacheEntries = HashMap<(String, i64), (Instant, Vec<ListEntity>)>;
+
+/// Depth 0 and 1 listings kept for a while, see `ClientBuilder::set_list_cache_ttl`
+#[derive(Debug, Clone)]
+pub struct ListCache {
+    pub(crate) ttl: Duration,
+    pub(crate) entries: Arc<Mutex<ListCacheEntries>>,
+}
+
+impl ListCache {
+    pub(crate) fn new(ttl: Duration) -> ListCache {
+        ListCache {
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    pub(crate) fn get(&self, url: &str, depth: i64) -> Option<Vec<ListEntity>> {
+        let mut entries = self.entries.lock().unwrap();
+        let key = (url.trim_end_matches('/').to_owned(), depth);
+        match entries.get(&key) {
+            Some((stored, entities)) if stored.elapsed() < self.ttl => Some(entities.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, url: &str, depth: i64, entities: Vec<ListEntity>) {
+        self.entries.lock().unwrap().insert(
+            (url.trim_end_matches('/').to_owned(), depth),
+            (Instant::now(), entities),
+        );
+    }
+
+    /// Forget the listings affected by a change of `url`: its own, its parent's and the ones of
+    /// its descendants.
+    pub(crate) fn invalidate(&self, url: &str) {
+        let url = url.trim_end_matches('/');
+        let parent = url.rsplit_once('/').map(|(parent, _)| parent);
+        let descendants = format!("{}/", url);
+        self.entries.lock().unwrap().retain(|(key, _), _| {
+            key != url && Some(key.as_str()) != parent && !key.starts_with(&descendants)
+        });
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+pub(crate) fn status_is_ok(status: &str) -> bool {
     let code = status.split_whitespace().nth(1);
 
     match code {
@@ -99,14 +849,17 @@ fn status_is_ok(status: &str) -> bool {
 impl TryFrom<ListResponse> for ListEntity {
     type Error = crate::types::Error;
     fn try_from(response: ListResponse) -> Result<Self, Self::Error> {
-        let valid_prop_stat = response
-            .prop_stat
-            .into_iter()
-            .filter(|prop_stat| status_is_ok(&prop_stat.status))
-            .next();
+        let response_status = response.status;
+        let valid_prop_stat = response.prop_stat.into_iter().find(|prop_stat| {
+            match (prop_stat.status.is_empty(), &response_status) {
+                (false, _) => status_is_ok(&prop_stat.status),
+                (true, Some(status)) => status_is_ok(status),
+                (true, None) => true,
+            }
+        });
 
         match valid_prop_stat {
-            Some(ListPropStat { prop, .. }) if prop.resource_type.collection.is_some() => {
+            Some(ListPropStat { prop, .. }) if prop.is_collection() => {
                 Ok(ListEntity::Folder(ListFolder {
                     href: response.href,
                     last_modified: prop.last_modified.ok_or_else(|| {
@@ -117,6 +870,10 @@ impl TryFrom<ListResponse> for ListEntity {
                     quota_used_bytes: prop.quota_used_bytes,
                     quota_available_bytes: prop.quota_available_bytes,
                     tag: prop.tag,
+                    display_name: prop.display_name,
+                    creation_date: prop.creation_date,
+                    supported_reports: prop.supported_reports,
+                    supported_locks: prop.supported_locks,
                 }))
             }
             Some(ListPropStat { prop, .. })
@@ -137,6 +894,11 @@ impl TryFrom<ListResponse> for ListEntity {
                 content_length: prop.content_length.unwrap_or(0),
                 content_type: prop.content_type.unwrap_or("".to_string()),
                 tag: prop.tag,
+                display_name: prop.display_name,
+                creation_date: prop.creation_date,
+                content_language: prop.content_language,
+                supported_reports: prop.supported_reports,
+                supported_locks: prop.supported_locks,
             })),
             None => Err(Error::Decode(DecodeError::FieldNotFound(FieldError {
                 field: "propstat with valid status".to_owned(),
@@ -145,7 +907,7 @@ impl TryFrom<ListResponse> for ListEntity {
     }
 }
 
-fn http_time<'de, D>(d: D) -> Result<Option<DateTime<Utc>>, D::Error>
+pub(crate) fn http_time<'de, D>(d: D) -> Result<Option<DateTime>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -153,14 +915,14 @@ where
 
     match value {
         None => Ok(None),
-        Some(value) => match httpdate::parse_http_date(&value) {
-            Ok(system_time) => Ok(Some(DateTime::<Utc>::from(system_time))),
-            Err(_) => Err(serde::de::Error::custom("parse error")),
+        Some(value) => match parse_http_date(&value) {
+            Some(time) => Ok(Some(time)),
+            None => Err(serde::de::Error::custom("parse error")),
         },
     }
 }
 
-fn empty_number<'de, D>(d: D) -> Result<Option<i64>, D::Error>
+pub(crate) fn empty_number<'de, D>(d: D) -> Result<Option<i64>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -180,6 +942,39 @@ where
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_keeps_unknown_properties() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns" xmlns:nc="http://nextcloud.org/ns">
+            <d:response>
+                <d:href>/remote.php/dav/files/admin/file.txt</d:href>
+                <d:propstat>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                    <d:prop>
+                        <d:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</d:getlastmodified>
+                        <d:resourcetype/>
+                        <oc:fileid>42</oc:fileid>
+                        <oc:share-types><oc:share-type>3</oc:share-type></oc:share-types>
+                        <nc:has-preview/>
+                    </d:prop>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+
+        let parsed = ListMultiStatus::parse(xml).unwrap();
+        let prop = &parsed.responses[0].prop_stat[0].prop;
+        let other = |namespace: &str, name: &str| {
+            prop.other
+                .get(&(namespace.to_owned(), name.to_owned()))
+                .map(String::as_str)
+        };
+        assert_eq!(other("http://owncloud.org/ns", "fileid"), Some("42"));
+        assert_eq!(other("http://owncloud.org/ns", "share-types"), Some("3"));
+        assert_eq!(other("http://nextcloud.org/ns", "has-preview"), Some(""));
+        assert_eq!(other("DAV:", "getlastmodified"), None);
+        assert_eq!(prop.last_modified.unwrap().epoch_seconds(), 1554904800);
+    }
+
     #[test]
     fn parse_single_prop_stat_folder() {
         let xml = r#"<?xml version="1.0" encoding="utf-8"?>
@@ -200,7 +995,7 @@ mod tests {
             </D:response>
         </D:multistatus>"#;
 
-        let parsed: ListMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let parsed = ListMultiStatus::parse(xml).unwrap();
         assert_eq!(parsed.responses.len(), 1);
         let response = parsed.responses[0].clone();
         let list_entity = ListEntity::try_from(response).unwrap();
@@ -226,17 +1021,19 @@ mod tests {
                     <D:status>HTTP/1.1 200 OK</D:status>
                     <D:prop>
                         <D:displayname>file.txt</D:displayname>
+                        <D:creationdate>2019-04-01T08:30:00+02:00</D:creationdate>
                         <D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified>
                         <D:resourcetype/>
                         <D:getetag>"5cafae80b1e3e"</D:getetag>
                         <D:getcontenttype>application/text</D:getcontenttype>
+                        <D:getcontentlanguage>en-US</D:getcontentlanguage>
                         <D:getcontentlength>1234</D:getcontentlength>
                     </D:prop>
                 </D:propstat>
             </D:response>
         </D:multistatus>"#;
 
-        let parsed: ListMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let parsed = ListMultiStatus::parse(xml).unwrap();
         assert_eq!(parsed.responses.len(), 1);
         let response = parsed.responses[0].clone();
         let list_entity = ListEntity::try_from(response).unwrap();
@@ -247,6 +1044,9 @@ mod tests {
                 assert_eq!(file.tag, Some("\"5cafae80b1e3e\"".to_string()));
                 assert_eq!(file.content_length, 1234);
                 assert_eq!(file.content_type, "application/text");
+                assert_eq!(file.display_name.as_deref(), Some("file.txt"));
+                assert_eq!(file.creation_date.unwrap().epoch_seconds(), 1554100200);
+                assert_eq!(file.content_language.as_deref(), Some("en-US"));
             }
             _ => panic!("expected folder"),
         }
@@ -279,7 +1079,7 @@ mod tests {
             </D:response>
         </D:multistatus>"#;
 
-        let parsed: ListMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let parsed = ListMultiStatus::parse(xml).unwrap();
         assert_eq!(parsed.responses.len(), 1);
         let response = parsed.responses[0].clone();
         let list_entity = ListEntity::try_from(response).unwrap();
@@ -320,7 +1120,7 @@ mod tests {
             </D:response>
         </D:multistatus>"#;
 
-        let parsed: ListMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let parsed = ListMultiStatus::parse(xml).unwrap();
         assert_eq!(parsed.responses.len(), 1);
         let response = parsed.responses[0].clone();
         let list_entity = ListEntity::try_from(response).unwrap();
@@ -362,7 +1162,7 @@ mod tests {
             </D:response>
         </D:multistatus>"#;
 
-        let parsed: ListMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let parsed = ListMultiStatus::parse(xml).unwrap();
         assert_eq!(parsed.responses.len(), 1);
         let response = parsed.responses[0].clone();
         let list_entity = ListEntity::try_from(response).unwrap();
@@ -400,13 +1200,40 @@ mod tests {
             </D:response>
         </D:multistatus>"#;
 
-        let parsed: ListMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let parsed = ListMultiStatus::parse(xml).unwrap();
         assert_eq!(parsed.responses.len(), 1);
         let response = parsed.responses[0].clone();
         let list_entity = ListEntity::try_from(response);
         assert!(list_entity.is_err());
     }
 
+    #[test]
+    fn parse_iis_collection_without_propstat_status() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <a:multistatus xmlns:b="urn:uuid:c2f41010-65b3-11d1-a29f-00aa00c14882/" xmlns:a="DAV:">
+            <a:response>
+                <a:href>http://example.com/docs/</a:href>
+                <a:propstat>
+                    <a:prop>
+                        <a:getlastmodified b:dt="dateTime.rfc1123">Wed, 10 Apr 2019 14:00:00 GMT</a:getlastmodified>
+                        <a:iscollection b:dt="boolean">1</a:iscollection>
+                        <a:resourcetype/>
+                    </a:prop>
+                </a:propstat>
+            </a:response>
+        </a:multistatus>"#;
+
+        let parsed = ListMultiStatus::parse(xml).unwrap();
+        let list_entity = ListEntity::try_from(parsed.responses[0].clone()).unwrap();
+        match list_entity {
+            ListEntity::Folder(folder) => {
+                assert_eq!(folder.href, "http://example.com/docs/");
+                assert_eq!(folder.last_modified.timestamp(), 1554904800);
+            }
+            _ => panic!("expected folder"),
+        }
+    }
+
     /// Pretty sure this isn't where the redirect-lifetime response should be
     /// but testing this for consistency with the existing library.
     #[test]
@@ -431,10 +1258,205 @@ mod tests {
             </D:response>
         </D:multistatus>"#;
 
-        let parsed: ListMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let parsed = ListMultiStatus::parse(xml).unwrap();
         assert_eq!(parsed.responses.len(), 1);
         let response = parsed.responses[0].clone();
         let list_entity = ListEntity::try_from(response);
         assert!(list_entity.is_err());
     }
+
+    #[test]
+    fn parse_supported_reports_and_locks() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+            <d:response>
+                <d:href>/dav/calendars/alice/work/</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <d:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</d:getlastmodified>
+                        <d:resourcetype><d:collection/></d:resourcetype>
+                        <d:supported-report-set>
+                            <d:supported-report><d:report><d:sync-collection/></d:report></d:supported-report>
+                            <d:supported-report><d:report><cal:calendar-query/></d:report></d:supported-report>
+                        </d:supported-report-set>
+                        <d:supportedlock>
+                            <d:lockentry><d:lockscope><d:exclusive/></d:lockscope><d:locktype><d:write/></d:locktype></d:lockentry>
+                        </d:supportedlock>
+                        <d:lockdiscovery>
+                            <d:activelock><d:lockscope><d:shared/></d:lockscope></d:activelock>
+                        </d:lockdiscovery>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+            <d:response>
+                <d:href>/dav/a.txt</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <d:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</d:getlastmodified>
+                        <d:resourcetype/>
+                        <d:supportedlock/>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+
+        let parsed = ListMultiStatus::parse(xml).unwrap();
+        let entities: Vec<ListEntity> = parsed
+            .responses
+            .into_iter()
+            .map(|response| ListEntity::try_from(response).unwrap())
+            .collect();
+        let calendar = &entities[0];
+        assert_eq!(
+            calendar.supports_report("DAV:", "sync-collection"),
+            Some(true)
+        );
+        assert_eq!(
+            calendar.supports_report("urn:ietf:params:xml:ns:caldav", "calendar-query"),
+            Some(true)
+        );
+        assert_eq!(
+            calendar.supports_report("DAV:", "version-tree"),
+            Some(false)
+        );
+        assert_eq!(calendar.supports_lock(LockScope::Exclusive), Some(true));
+        assert_eq!(calendar.supports_lock(LockScope::Shared), Some(false));
+        let file = &entities[1];
+        assert_eq!(file.supports_report("DAV:", "sync-collection"), None);
+        assert_eq!(file.supports_lock(LockScope::Exclusive), Some(false));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn list_entity_json_schema() {
+        let entity = ListEntity::File(ListFile {
+            href: "/dav/a.txt".to_owned(),
+            last_modified: DateTime::from_epoch_seconds(1554904800),
+            content_length: 1234,
+            content_type: "text/plain".to_owned(),
+            tag: None,
+            display_name: None,
+            creation_date: None,
+            content_language: None,
+            supported_reports: None,
+            supported_locks: None,
+        });
+        let json = serde_json::to_value(&entity).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "schemaVersion": 1,
+                "type": "file",
+                "href": "/dav/a.txt",
+                "lastModified": "2019-04-10T14:00:00Z",
+                "contentLength": 1234,
+                "contentType": "text/plain",
+                "tag": null
+            })
+        );
+        let parsed: ListEntity = serde_json::from_value(json.clone()).unwrap();
+        assert!(
+            matches!(parsed, ListEntity::File(file) if file.last_modified.epoch_seconds() == 1554904800)
+        );
+
+        let mut future = json;
+        future["schemaVersion"] = 2.into();
+        assert!(serde_json::from_value::<ListEntity>(future).is_err());
+    }
+
+    #[test]
+    fn parse_matches_dav_namespace_not_prefix() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <multistatus xmlns="DAV:" xmlns:x="urn:example">
+            <response>
+                <href>/dav/a%20b.txt</href>
+                <propstat>
+                    <prop>
+                        <getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</getlastmodified>
+                        <resourcetype/>
+                        <x:getcontentlength>not a number</x:getcontentlength>
+                        <getcontentlength>12</getcontentlength>
+                        <getetag>&quot;1&amp;2&quot;</getetag>
+                    </prop>
+                    <status>HTTP/1.1 200 OK</status>
+                </propstat>
+            </response>
+            <lp1:response xmlns:lp1="DAV:">
+                <lp1:href>/dav/sub/</lp1:href>
+                <lp1:propstat>
+                    <lp1:prop>
+                        <lp1:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</lp1:getlastmodified>
+                        <lp1:resourcetype><lp1:collection/></lp1:resourcetype>
+                    </lp1:prop>
+                    <lp1:status>HTTP/1.1 200 OK</lp1:status>
+                </lp1:propstat>
+            </lp1:response>
+        </multistatus>"#;
+
+        let parsed = ListMultiStatus::parse(xml).unwrap();
+        let entities: Vec<ListEntity> = parsed
+            .responses
+            .into_iter()
+            .map(|response| ListEntity::try_from(response).unwrap())
+            .collect();
+        match &entities[0] {
+            ListEntity::File(file) => {
+                assert_eq!(file.href, "/dav/a%20b.txt");
+                assert_eq!(file.name(), "a b.txt");
+                assert_eq!(file.content_length, 12);
+                assert_eq!(file.tag.as_deref(), Some("\"1&2\""));
+            }
+            _ => panic!("expected file"),
+        }
+        assert!(matches!(&entities[1], ListEntity::Folder(folder) if folder.href == "/dav/sub/"));
+    }
+
+    #[test]
+    fn parse_lenient_drops_malformed_values() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <d:multistatus xmlns:d="DAV:">
+            <d:response>
+                <d:href>/dav/contacts/</d:href>
+                <d:propstat>
+                    <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+                <d:propstat>
+                    <d:prop><d:getlastmodified/></d:prop>
+                    <d:status>HTTP/1.1 404 Not Found</d:status>
+                </d:propstat>
+            </d:response>
+            <d:response>
+                <d:href>/dav/a.txt</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <d:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</d:getlastmodified>
+                        <d:getcontentlength>12 bytes</d:getcontentlength>
+                        <d:resourcetype/>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+
+        assert!(ListMultiStatus::parse(xml).is_err());
+        let entities: Vec<ListEntity> = ListMultiStatus::parse_lenient(xml)
+            .unwrap()
+            .responses
+            .into_iter()
+            .map(|response| ListEntity::try_from(response).unwrap())
+            .collect();
+        match &entities[0] {
+            ListEntity::Folder(folder) => {
+                assert_eq!(folder.last_modified, DateTime::unix_epoch())
+            }
+            _ => panic!("expected folder"),
+        }
+        match &entities[1] {
+            ListEntity::File(file) => assert_eq!(file.content_length, 0),
+            _ => panic!("expected file"),
+        }
+    }
 }
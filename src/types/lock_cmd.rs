@@ -0,0 +1,245 @@
+//! Types and serialisation expected for the LOCK command.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::types::{xml_escape, Result};
+use crate::{Client, Depth};
+
+/// A lock token as returned in the `Lock-Token` header or `locktoken` element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockToken(String);
+
+impl LockToken {
+    /// Parse a token, with or without the surrounding angle brackets.
+    pub fn parse(value: &str) -> LockToken {
+        LockToken(
+            value
+                .trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .trim()
+                .to_owned(),
+        )
+    }
+
+    /// The token URI, e.g. `opaquelocktoken:f81d4fae-7dec-11d0-a765-00a0c91e6bf6`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the token uses the `opaquelocktoken` scheme of Apache mod_dav.
+    pub fn is_opaque(&self) -> bool {
+        self.0.starts_with("opaquelocktoken:")
+    }
+
+    /// Value for the `Lock-Token` header of an UNLOCK request.
+    pub fn to_lock_token_header(&self) -> String {
+        format!("<{}>", self.0)
+    }
+
+    /// Untagged list for the `If` header of a write to the locked resource.
+    pub fn to_if_header(&self) -> String {
+        format!("(<{}>)", self.0)
+    }
+}
+
+impl Display for LockToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockScope {
+    #[default]
+    Exclusive,
+    Shared,
+}
+
+/// Parameters of a LOCK request.
+#[derive(Debug, Clone)]
+pub struct LockOptions {
+    pub scope: LockScope,
+    /// Free text identifying the holder of the lock, e.g. a `mailto:` URL.
+    pub owner: Option<String>,
+    /// Requested lifetime of the lock, `None` asks for an infinite lock.
+    pub timeout: Option<Duration>,
+    /// `Depth::Number(0)` or `Depth::Infinity`, only meaningful for collections.
+    pub depth: Depth,
+}
+
+impl Default for LockOptions {
+    fn default() -> Self {
+        LockOptions {
+            scope: LockScope::Exclusive,
+            owner: None,
+            timeout: None,
+            depth: Depth::Infinity,
+        }
+    }
+}
+
+impl LockOptions {
+    pub(crate) fn to_xml(&self) -> String {
+        let scope = match self.scope {
+            LockScope::Exclusive => "exclusive",
+            LockScope::Shared => "shared",
+        };
+        let owner = match &self.owner {
+            Some(owner) => format!("<D:owner>{}</D:owner>", xml_escape(owner)),
+            None => String::new(),
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?><D:lockinfo xmlns:D="DAV:"><D:lockscope><D:{scope}/></D:lockscope><D:locktype><D:write/></D:locktype>{owner}</D:lockinfo>"#
+        )
+    }
+
+    pub(crate) fn timeout_header(&self) -> String {
+        match self.timeout {
+            Some(timeout) => format!("Second-{}", timeout.as_secs()),
+            None => "Infinite".to_owned(),
+        }
+    }
+}
+
+/// A lock reported in `lockdiscovery`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveLock {
+    pub token: LockToken,
+    pub scope: LockScope,
+    /// `0` or `infinity`.
+    pub depth: Option<String>,
+    /// Remaining lifetime granted by the server, `None` for infinite locks.
+    pub timeout: Option<Duration>,
+    /// The resource the lock was taken on.
+    pub root: Option<String>,
+}
+
+/// A lock held by the client, unlocked when released or dropped
+///
+/// Dropping the guard unlocks in a task spawned on the current tokio runtime, call `release` to
+/// wait for the UNLOCK and see its outcome. On wasm the guard must be released explicitly.
+#[derive(Debug)]
+pub struct LockGuard {
+    /// Client sending the lock token in the `If` header of writes.
+    pub(crate) client: Client,
+    pub(crate) path: String,
+    pub(crate) lock: ActiveLock,
+    pub(crate) released: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LockProp {
+    #[serde(rename = "lockdiscovery")]
+    lock_discovery: LockDiscovery,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockDiscovery {
+    #[serde(rename = "activelock", default)]
+    active_locks: Vec<ActiveLockXml>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActiveLockXml {
+    #[serde(rename = "lockscope", default)]
+    lock_scope: LockScopeXml,
+    depth: Option<String>,
+    timeout: Option<String>,
+    #[serde(rename = "locktoken")]
+    lock_token: Option<Href>,
+    #[serde(rename = "lockroot")]
+    lock_root: Option<Href>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct LockScopeXml {
+    shared: Option<()>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Href {
+    href: String,
+}
+
+/// Parse a `Timeout` value, `None` for `Infinite` or unknown values.
+pub(crate) fn parse_timeout(value: &str) -> Option<Duration> {
+    value
+        .split(',')
+        .find_map(|value| value.trim().strip_prefix("Second-")?.parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// The active locks of the `prop` element of a LOCK response.
+pub(crate) fn parse_lock_discovery(xml: &str) -> Result<Vec<ActiveLock>> {
+    let prop: LockProp = serde_xml_rs::from_str(xml)?;
+    Ok(prop
+        .lock_discovery
+        .active_locks
+        .into_iter()
+        .filter_map(|lock| {
+            Some(ActiveLock {
+                token: LockToken::parse(&lock.lock_token?.href),
+                scope: if lock.lock_scope.shared.is_some() {
+                    LockScope::Shared
+                } else {
+                    LockScope::Exclusive
+                },
+                depth: lock.depth.map(|depth| depth.trim().to_owned()),
+                timeout: lock.timeout.as_deref().and_then(parse_timeout),
+                root: lock.lock_root.map(|root| root.href),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_opaque_lock_token() {
+        let token = LockToken::parse("<opaquelocktoken:f81d4fae-7dec-11d0-a765-00a0c91e6bf6>");
+        assert!(token.is_opaque());
+        assert_eq!(
+            token.as_str(),
+            "opaquelocktoken:f81d4fae-7dec-11d0-a765-00a0c91e6bf6"
+        );
+        assert_eq!(
+            token.to_if_header(),
+            "(<opaquelocktoken:f81d4fae-7dec-11d0-a765-00a0c91e6bf6>)"
+        );
+    }
+
+    #[test]
+    fn parse_lock_response() {
+        let locks = parse_lock_discovery(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+            <D:prop xmlns:D="DAV:"><D:lockdiscovery><D:activelock>
+                <D:locktype><D:write/></D:locktype>
+                <D:lockscope><D:exclusive/></D:lockscope>
+                <D:depth>infinity</D:depth>
+                <D:owner><D:href>mailto:alice@example.com</D:href></D:owner>
+                <D:timeout>Second-3600</D:timeout>
+                <D:locktoken><D:href>urn:uuid:e71d4fae-5dec-22d6-fea5-00a0c91e6be4</D:href></D:locktoken>
+                <D:lockroot><D:href>/dav/doc.txt</D:href></D:lockroot>
+            </D:activelock></D:lockdiscovery></D:prop>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            locks,
+            vec![ActiveLock {
+                token: LockToken::parse("urn:uuid:e71d4fae-5dec-22d6-fea5-00a0c91e6be4"),
+                scope: LockScope::Exclusive,
+                depth: Some("infinity".to_owned()),
+                timeout: Some(Duration::from_secs(3600)),
+                root: Some("/dav/doc.txt".to_owned()),
+            }]
+        );
+    }
+}
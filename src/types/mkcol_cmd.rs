@@ -0,0 +1,144 @@
+//! Types and serialisation for MKCALENDAR (RFC 4791) and extended MKCOL (RFC 5689).
+
+use crate::types::list_cmd::{CALDAV_NAMESPACE, CARDDAV_NAMESPACE};
+use crate::types::proppatch_cmd::PropName;
+use crate::types::xml_escape;
+
+/// Namespace of the `calendar-color` property used by Apple and most CalDAV clients.
+const APPLE_ICAL_NAMESPACE: &str = "http://apple.com/ns/ical/";
+
+/// Properties set on a collection when it is created, see `Client::mkcalendar` and
+/// `Client::mkcol_ext`
+///
+/// ```
+/// use reqwest_dav::types::mkcol_cmd::CollectionProps;
+///
+/// let props = CollectionProps::new()
+///     .display_name("Work")
+///     .calendar_components(["VEVENT", "VTODO"])
+///     .calendar_color("#2e7d32");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CollectionProps {
+    /// Properties with their content, already serialised.
+    props: Vec<(PropName, String)>,
+}
+
+impl CollectionProps {
+    pub fn new() -> CollectionProps {
+        CollectionProps::default()
+    }
+
+    /// Set the property to a text value.
+    pub fn set(
+        self,
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+        value: impl AsRef<str>,
+    ) -> Self {
+        self.set_xml(namespace, name, xml_escape(value.as_ref()))
+    }
+
+    /// Set the property to XML content, written as is
+    ///
+    /// The prefixes `D` (`DAV:`), `C` (CalDAV) and `CR` (CardDAV) are declared.
+    pub fn set_xml(
+        mut self,
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+        xml: impl Into<String>,
+    ) -> Self {
+        self.props
+            .push((PropName::new(namespace, name), xml.into()));
+        self
+    }
+
+    pub fn display_name(self, name: impl AsRef<str>) -> Self {
+        self.set("DAV:", "displayname", name)
+    }
+
+    pub fn calendar_description(self, description: impl AsRef<str>) -> Self {
+        self.set(CALDAV_NAMESPACE, "calendar-description", description)
+    }
+
+    /// The components the calendar accepts, like `VEVENT` and `VTODO`.
+    pub fn calendar_components<S: AsRef<str>>(
+        self,
+        components: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let xml: String = components
+            .into_iter()
+            .map(|component| format!(r#"<C:comp name="{}"/>"#, xml_escape(component.as_ref())))
+            .collect();
+        self.set_xml(CALDAV_NAMESPACE, "supported-calendar-component-set", xml)
+    }
+
+    /// Color of the calendar as `#RRGGBB`.
+    pub fn calendar_color(self, color: impl AsRef<str>) -> Self {
+        self.set(APPLE_ICAL_NAMESPACE, "calendar-color", color)
+    }
+
+    /// The resource type of a collection created with `Client::mkcol_ext`, besides
+    /// `DAV:collection`, like `PropName::new("urn:ietf:params:xml:ns:carddav", "addressbook")`.
+    pub fn resource_type(self, types: impl IntoIterator<Item = PropName>) -> Self {
+        let mut xml = "<D:collection/>".to_owned();
+        for kind in types {
+            xml.push_str(&element(&kind, ""));
+        }
+        self.set_xml("DAV:", "resourcetype", xml)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.props.is_empty()
+    }
+
+    /// The request body with `root`, `C:mkcalendar` or `D:mkcol`, as document element.
+    pub(crate) fn to_xml(&self, root: &str) -> String {
+        let mut xml = format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?><{} xmlns:D="DAV:" xmlns:C="{}" xmlns:CR="{}"><D:set><D:prop>"#,
+            root, CALDAV_NAMESPACE, CARDDAV_NAMESPACE
+        );
+        for (prop, content) in &self.props {
+            xml.push_str(&element(prop, content));
+        }
+        xml.push_str(&format!("</D:prop></D:set></{}>", root));
+        xml
+    }
+}
+
+/// The element of a property, with one of the declared prefixes or its own namespace.
+fn element(prop: &PropName, content: &str) -> String {
+    let (prefix, declaration) = match prop.namespace.as_str() {
+        "DAV:" => ("D", String::new()),
+        CALDAV_NAMESPACE => ("C", String::new()),
+        CARDDAV_NAMESPACE => ("CR", String::new()),
+        namespace => ("x", format!(r#" xmlns:x="{}""#, xml_escape(namespace))),
+    };
+    match content.is_empty() {
+        true => format!("<{}:{}{}/>", prefix, prop.name, declaration),
+        false => format!(
+            "<{prefix}:{name}{declaration}>{content}</{prefix}:{name}>",
+            name = prop.name
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CollectionProps;
+    use crate::types::list_cmd::CARDDAV_NAMESPACE;
+    use crate::types::proppatch_cmd::PropName;
+
+    #[test]
+    fn extended_mkcol_body() {
+        let xml = CollectionProps::new()
+            .display_name("Friends & Family")
+            .resource_type([PropName::new(CARDDAV_NAMESPACE, "addressbook")])
+            .set("urn:example", "note", "<none>")
+            .to_xml("D:mkcol");
+        assert!(xml.contains("<D:displayname>Friends &amp; Family</D:displayname>"));
+        assert!(xml.contains("<D:resourcetype><D:collection/><CR:addressbook/></D:resourcetype>"));
+        assert!(xml.contains(r#"<x:note xmlns:x="urn:example">&lt;none&gt;</x:note>"#));
+        assert!(xml.ends_with("</D:prop></D:set></D:mkcol>"));
+    }
+}
@@ -1,26 +1,123 @@
+pub mod about_cmd;
+pub mod acl_cmd;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache_cmd;
+pub mod caldav_cmd;
+pub mod carddav_cmd;
+#[cfg(feature = "checksum")]
+pub mod checksum_cmd;
+pub mod conditional_cmd;
+pub mod copy_cmd;
+pub mod discovery_cmd;
+pub mod download_cmd;
+pub mod etag;
+#[cfg(feature = "fault-injection")]
+pub mod fault_cmd;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod file_cmd;
+pub mod hooks;
 pub mod list_cmd;
+pub mod lock_cmd;
+pub mod mkcol_cmd;
+#[cfg(feature = "nextcloud")]
+pub mod nextcloud_cmd;
+#[cfg(feature = "ocis")]
+pub mod ocis_cmd;
+pub mod options_cmd;
+pub mod principals_cmd;
+pub mod propfind_cmd;
+pub mod proppatch_cmd;
+pub mod put_cmd;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod record_cmd;
+pub mod report_cmd;
+pub mod request_cmd;
+pub mod retry_cmd;
+pub mod search_cmd;
+pub mod server_cmd;
+pub mod stats;
+pub mod status_cmd;
+pub mod sync_cmd;
+pub mod timestamp;
+#[cfg(all(feature = "__tls", not(target_arch = "wasm32")))]
+pub(crate) mod tls_cmd;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transfer_cmd;
+pub mod version_cmd;
+#[cfg(feature = "yandex")]
+pub mod yandex_cmd;
 
 use std::fmt;
-use std::fmt::{Debug, Display, Formatter};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
 
 use reqwest::Response;
 use serde_derive::{Deserialize, Serialize};
 
+use crate::types::status_cmd::{StatusFailure, StatusMultiStatus};
+
+/// Result of the operations of this crate.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(thiserror::Error)]
 pub enum Error {
-    Reqwest(reqwest::Error),
+    #[error("http request failed")]
+    Reqwest(#[source] reqwest::Error),
+    #[error(transparent)]
     ReqwestDecode(ReqwestDecodeError),
+    #[error(transparent)]
     Decode(DecodeError),
+    #[error("tried to make a digest request without a valid context")]
     MissingAuthContext,
+    /// The host given to the client is not a http(s) URL.
+    #[error(transparent)]
+    InvalidHost(InvalidHostError),
+    #[error("io error")]
+    Io(#[source] std::io::Error),
+    /// The audit hook refused the operation.
+    #[error("operation denied by the audit hook")]
+    Denied(hooks::AuditEvent),
+    /// The cancellation token of the client was cancelled.
+    #[error("operation cancelled")]
+    Cancelled,
+    /// A `download_dir` or `upload_dir` was cancelled, the report tells how far it got.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("directory transfer cancelled")]
+    TransferCancelled(Box<transfer_cmd::DirTransferReport>),
+    /// The deadline of the operation passed.
+    #[error("deadline of the operation exceeded")]
+    DeadlineExceeded,
+    /// The server answered 412, the resource does not match the `If-Match`/`If-None-Match` tag.
+    #[error(transparent)]
+    PreconditionFailed(PreconditionFailedError),
+    /// The content does not match the checksum the server gave for it.
+    #[cfg(feature = "checksum")]
+    #[error(transparent)]
+    ChecksumMismatch(ChecksumMismatchError),
 }
 
+#[derive(thiserror::Error)]
 pub enum DecodeError {
-    DigestAuth(digest_auth::Error),
+    #[cfg(feature = "digest")]
+    #[error("invalid digest challenge")]
+    DigestAuth(#[source] digest_auth::Error),
+    #[error("the server sent no www-authenticate header")]
     NoAuthHeaderInResponse,
-    SerdeXml(serde_xml_rs::Error),
+    #[error("invalid xml response")]
+    SerdeXml(#[source] serde_xml_rs::Error),
+    #[error("invalid xml response")]
+    Xml(#[source] quick_xml::Error),
+    #[error("field not supported: {}", .0.field)]
     FieldNotSupported(FieldError),
+    #[error("field not found: {}", .0.field)]
     FieldNotFound(FieldError),
+    #[error(transparent)]
     StatusMismatched(StatusMismatchedError),
+    #[error(transparent)]
     Server(ServerError),
+    #[error(transparent)]
+    MultiStatus(MultiStatusError),
 }
 
 #[derive(Debug)]
@@ -28,25 +125,255 @@ pub struct FieldError {
     pub field: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
+#[error("invalid host {host}: {reason}")]
+pub struct InvalidHostError {
+    pub host: String,
+    pub reason: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("precondition failed for {path}")]
+pub struct PreconditionFailedError {
+    pub path: String,
+    /// Id of the operation, when request ids are enabled.
+    pub request_id: Option<String>,
+    pub request: Option<Box<RequestInfo>>,
+}
+
+#[cfg(feature = "checksum")]
+#[derive(Debug, thiserror::Error)]
+#[error("{algorithm} checksum of {path} is {actual}, expected {expected}")]
+pub struct ChecksumMismatchError {
+    pub path: String,
+    pub algorithm: String,
+    /// Lowercase hex digests.
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "{} answered {response_code}, expected {expected_code}",
+    request_line(.request)
+)]
 pub struct StatusMismatchedError {
     pub response_code: u16,
     pub expected_code: u16,
+    /// Id of the operation, when request ids are enabled.
+    pub request_id: Option<String>,
+    pub request: Option<Box<RequestInfo>>,
+    /// The wait asked for by the `Retry-After` header.
+    pub retry_after: Option<Duration>,
 }
 
-#[derive(Debug)]
+impl StatusMismatchedError {
+    pub(crate) fn new(response: &Response, expected_code: u16) -> StatusMismatchedError {
+        StatusMismatchedError {
+            response_code: response.status().as_u16(),
+            expected_code,
+            request_id: request_id(response),
+            request: Some(RequestInfo::new(response)),
+            retry_after: retry_cmd::retry_after(response),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "{} failed with status {response_code}: {exception}",
+    request_line(.request)
+)]
 pub struct ServerError {
     pub response_code: u16,
     pub exception: String,
     pub message: String,
+    /// Id of the operation, when request ids are enabled.
+    pub request_id: Option<String>,
+    pub request: Option<Box<RequestInfo>>,
+    /// The wait asked for by the `Retry-After` header of a 429 or 503 response.
+    pub retry_after: Option<Duration>,
 }
 
-#[derive(Debug)]
+/// The request an error response belongs to, boxed in the errors to keep `Error` small.
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    /// Not known on wasm.
+    pub method: Option<String>,
+    pub url: String,
+}
+
+impl RequestInfo {
+    pub(crate) fn new(response: &Response) -> Box<RequestInfo> {
+        Box::new(RequestInfo {
+            method: request_method(response),
+            url: response.url().to_string(),
+        })
+    }
+}
+
+/// `GET https://host/path` for error messages, leaving out the query which may hold secrets.
+fn request_line(request: &Option<Box<RequestInfo>>) -> String {
+    let Some(request) = request else {
+        return "request".to_owned();
+    };
+    let url = request.url.split('?').next().unwrap_or_default();
+    match &request.method {
+        Some(method) => format!("{} {}", method, url),
+        None => url.to_owned(),
+    }
+}
+
+/// The request id a response was requested with, stored in the response extensions.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub(crate) struct RequestId(pub(crate) String);
+
+/// The method a response was requested with, stored in the response extensions.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub(crate) struct RequestMethod(pub(crate) reqwest::Method);
+
+/// The request id of a response, not available on wasm where responses carry no extensions.
+pub(crate) fn request_id(response: &Response) -> Option<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return response
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone());
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = response;
+        None
+    }
+}
+
+/// The request method of a response, not available on wasm where responses carry no extensions.
+pub(crate) fn request_method(response: &Response) -> Option<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return response
+        .extensions()
+        .get::<RequestMethod>()
+        .map(|method| method.0.to_string());
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = response;
+        None
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("{} of the affected resources failed", .failures.len())]
+pub struct MultiStatusError {
+    pub failures: Vec<StatusFailure>,
+}
+
+impl MultiStatusError {
+    /// The failures which are not a 424 Failed Dependency caused by another failure.
+    pub fn root_causes(&self) -> Vec<&StatusFailure> {
+        self.failures
+            .iter()
+            .filter(|failure| !failure.is_failed_dependency())
+            .collect()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
 pub enum ReqwestDecodeError {
-    Url(url::ParseError),
-    HeaderToString(reqwest::header::ToStrError),
-    InvalidHeaderValue(reqwest::header::InvalidHeaderValue),
-    InvalidMethod(http::method::InvalidMethod),
+    #[error("invalid url")]
+    Url(#[source] url::ParseError),
+    #[error("header is not visible ascii")]
+    HeaderToString(#[source] reqwest::header::ToStrError),
+    #[error("invalid header value")]
+    InvalidHeaderValue(#[source] reqwest::header::InvalidHeaderValue),
+    #[error("invalid method")]
+    InvalidMethod(#[source] http::method::InvalidMethod),
+}
+
+impl Error {
+    /// The HTTP status of the response the error comes from.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Reqwest(err) => err.status().map(|status| status.as_u16()),
+            Error::Decode(DecodeError::StatusMismatched(err)) => Some(err.response_code),
+            Error::Decode(DecodeError::Server(err)) => Some(err.response_code),
+            Error::PreconditionFailed(_) => Some(412),
+            _ => None,
+        }
+    }
+
+    /// How long the server asked to wait with `Retry-After` before sending the request again
+    ///
+    /// Set for throttled (429) and unavailable (503) responses of servers sending the header,
+    /// in either its delay-seconds or HTTP-date form.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::Decode(DecodeError::StatusMismatched(err)) => err.retry_after,
+            Error::Decode(DecodeError::Server(err)) => err.retry_after,
+            _ => None,
+        }
+    }
+
+    /// The method of the failed request, when known.
+    pub fn method(&self) -> Option<&str> {
+        match self {
+            Error::Decode(DecodeError::StatusMismatched(err)) => err.request.as_ref(),
+            Error::Decode(DecodeError::Server(err)) => err.request.as_ref(),
+            Error::PreconditionFailed(err) => err.request.as_ref(),
+            _ => None,
+        }
+        .and_then(|request| request.method.as_deref())
+    }
+
+    /// The URL of the failed request, when known.
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            Error::Reqwest(err) => err.url().map(|url| url.as_str()),
+            Error::Decode(DecodeError::StatusMismatched(err)) => {
+                err.request.as_ref().map(|request| request.url.as_str())
+            }
+            Error::Decode(DecodeError::Server(err)) => {
+                err.request.as_ref().map(|request| request.url.as_str())
+            }
+            Error::PreconditionFailed(err) => {
+                err.request.as_ref().map(|request| request.url.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// The resource does not exist, 404.
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(404)
+    }
+
+    /// The parent collection is missing or the resource is in a conflicting state, 409.
+    pub fn is_conflict(&self) -> bool {
+        self.status() == Some(409)
+    }
+
+    /// A condition of the request did not hold, 412.
+    pub fn is_precondition_failed(&self) -> bool {
+        self.status() == Some(412)
+    }
+
+    /// The same request may succeed when sent again later
+    ///
+    /// True for connection errors, including reset connections, timeouts and the statuses 429,
+    /// 502, 503 and 504.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Error::Reqwest(err) if err.is_connect() || err.is_timeout() => true,
+            #[cfg(target_arch = "wasm32")]
+            Error::Reqwest(err) if err.is_timeout() => true,
+            Error::Io(err) if err.kind() == std::io::ErrorKind::ConnectionReset => true,
+            _ => self
+                .status()
+                .and_then(|status| reqwest::StatusCode::from_u16(status).ok())
+                .is_some_and(retry_cmd::is_retryable_status),
+        }
+    }
 }
 
 impl Debug for Error {
@@ -68,32 +395,37 @@ impl Debug for Error {
             Error::MissingAuthContext => {
                 builder.field("kind", &"MissingAuthContext");
             }
-        }
-        builder.finish()
-    }
-}
-
-impl Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut builder = f.debug_struct("reqwest_dav::Error");
-        match self {
-            Error::Reqwest(err) => {
-                builder.field("kind", &"Reqwest");
+            Error::InvalidHost(err) => {
+                builder.field("kind", &"InvalidHost");
                 builder.field("source", err);
             }
-            Error::ReqwestDecode(err) => {
-                builder.field("kind", &"ReqwestDecode");
+            Error::Io(err) => {
+                builder.field("kind", &"Io");
                 builder.field("source", err);
             }
-            Error::Decode(err) => {
-                builder.field("kind", &"Decode");
+            Error::Denied(event) => {
+                builder.field("kind", &"Denied");
+                builder.field("event", event);
+            }
+            Error::Cancelled => {
+                builder.field("kind", &"Cancelled");
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Error::TransferCancelled(report) => {
+                builder.field("kind", &"TransferCancelled");
+                builder.field("report", report);
+            }
+            Error::DeadlineExceeded => {
+                builder.field("kind", &"DeadlineExceeded");
+            }
+            Error::PreconditionFailed(err) => {
+                builder.field("kind", &"PreconditionFailed");
                 builder.field("source", err);
             }
-            Error::MissingAuthContext => {
-                builder.field(
-                    "kind",
-                    &"Tried to make a digest request without a valid context.",
-                );
+            #[cfg(feature = "checksum")]
+            Error::ChecksumMismatch(err) => {
+                builder.field("kind", &"ChecksumMismatch");
+                builder.field("source", err);
             }
         }
         builder.finish()
@@ -103,39 +435,32 @@ impl Display for Error {
 impl Debug for DecodeError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
+            #[cfg(feature = "digest")]
             Self::DigestAuth(arg0) => f.debug_tuple("DigestAuth").field(arg0).finish(),
             Self::SerdeXml(arg0) => f.debug_tuple("SerdeXml").field(arg0).finish(),
+            Self::Xml(arg0) => f.debug_tuple("Xml").field(arg0).finish(),
             Self::FieldNotSupported(arg0) => f.debug_tuple("NotSupported").field(arg0).finish(),
             Self::FieldNotFound(arg0) => f.debug_tuple("NotFound").field(arg0).finish(),
             Self::StatusMismatched(arg0) => f.debug_tuple("StatusMismatched").field(arg0).finish(),
             Self::Server(arg0) => f.debug_tuple("Server").field(arg0).finish(),
+            Self::MultiStatus(arg0) => f.debug_tuple("MultiStatus").field(arg0).finish(),
             Self::NoAuthHeaderInResponse => f.debug_tuple("NoAuthHeaderInResponse").finish(),
         }
     }
 }
 
-impl Display for DecodeError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::DigestAuth(arg0) => f.debug_tuple("DigestAuth").field(arg0).finish(),
-            Self::SerdeXml(arg0) => f.debug_tuple("SerdeXml").field(arg0).finish(),
-            Self::FieldNotSupported(arg0) => f.debug_tuple("NotSupported").field(arg0).finish(),
-            Self::FieldNotFound(arg0) => f.debug_tuple("NotFound").field(arg0).finish(),
-            Self::StatusMismatched(arg0) => f.debug_tuple("StatusMismatched").field(arg0).finish(),
-            Self::Server(arg0) => f.debug_tuple("Server").field(arg0).finish(),
-            Self::NoAuthHeaderInResponse => f.debug_tuple("NoAuthHeaderInResponse").finish(),
-        }
-    }
-}
-
-impl std::error::Error for Error {}
-
 impl From<url::ParseError> for Error {
     fn from(error: url::ParseError) -> Self {
         Error::ReqwestDecode(ReqwestDecodeError::Url(error))
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
 impl From<reqwest::Error> for Error {
     fn from(error: reqwest::Error) -> Self {
         Error::Reqwest(error)
@@ -160,12 +485,19 @@ impl From<http::method::InvalidMethod> for Error {
     }
 }
 
+#[cfg(feature = "digest")]
 impl From<digest_auth::Error> for Error {
     fn from(error: digest_auth::Error) -> Self {
         Error::Decode(DecodeError::DigestAuth(error))
     }
 }
 
+impl From<quick_xml::Error> for Error {
+    fn from(error: quick_xml::Error) -> Self {
+        Error::Decode(DecodeError::Xml(error))
+    }
+}
+
 impl From<serde_xml_rs::Error> for Error {
     fn from(error: serde_xml_rs::Error) -> Self {
         Error::Decode(DecodeError::SerdeXml(error))
@@ -178,18 +510,23 @@ struct DavErrorTmp {
     pub message: String,
 }
 
-#[async_trait::async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
 pub trait Dav2xx {
-    async fn dav2xx(self) -> Result<Response, Error>;
+    async fn dav2xx(self) -> Result<Response>;
 }
 
-#[async_trait::async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
 impl Dav2xx for Response {
-    async fn dav2xx(self) -> Result<Response, Error> {
+    async fn dav2xx(self) -> Result<Response> {
         let code = self.status().as_u16();
         if code / 100 == 2 {
             Ok(self)
         } else {
+            let request_id = request_id(&self);
+            let request = Some(RequestInfo::new(&self));
+            let retry_after = retry_cmd::retry_after(&self);
             let text = self.text().await?;
             let tmp: DavErrorTmp = match serde_xml_rs::from_str(&text) {
                 Ok(tmp) => tmp,
@@ -198,6 +535,9 @@ impl Dav2xx for Response {
                         response_code: code,
                         exception: "server exception and parse error".to_owned(),
                         message: text,
+                        request_id,
+                        request,
+                        retry_after,
                     })))
                 }
             };
@@ -205,16 +545,136 @@ impl Dav2xx for Response {
                 response_code: code,
                 exception: tmp.exception,
                 message: tmp.message,
+                request_id,
+                request,
+                retry_after,
             })))
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Escape text for use in an XML element or attribute value.
+pub(crate) fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fail on a 207 response which reports a non 2xx status for any of the affected resources.
+pub(crate) async fn check_multi_status(response: Response) -> Result<()> {
+    if response.status().as_u16() != 207 {
+        return Ok(());
+    }
+    let text = response.text().await?;
+    let multi_status: StatusMultiStatus = serde_xml_rs::from_str(&text)?;
+    let failures = multi_status.failures();
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Decode(DecodeError::MultiStatus(MultiStatusError {
+            failures,
+        })))
+    }
+}
+
+#[derive(Clone)]
 pub enum Auth {
     Anonymous,
     Basic(String, String),
+    #[cfg(feature = "digest")]
     Digest(String, String),
+    /// `Authorization: Bearer` with a fixed token.
+    Bearer(String),
+    /// `Authorization: Bearer` with a token asked from the provider for every request.
+    TokenProvider(Arc<dyn TokenProvider>),
+    /// Basic or Digest, whichever the server asks for in its `WWW-Authenticate` challenge
+    ///
+    /// The server is asked once without credentials, the scheme is then kept by the client and
+    /// the clients derived from it.
+    Auto {
+        username: String,
+        password: String,
+    },
+}
+
+/// The scheme chosen for `Auth::Auto`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AuthScheme {
+    /// The server did not ask for credentials.
+    Anonymous,
+    Basic,
+    #[cfg(feature = "digest")]
+    Digest,
+}
+
+impl Debug for Auth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const REDACTED: &str = "***";
+        match self {
+            Self::Anonymous => f.write_str("Anonymous"),
+            Self::Basic(user, _) => f.debug_tuple("Basic").field(user).field(&REDACTED).finish(),
+            #[cfg(feature = "digest")]
+            Self::Digest(user, _) => f
+                .debug_tuple("Digest")
+                .field(user)
+                .field(&REDACTED)
+                .finish(),
+            Self::Bearer(_) => f.debug_tuple("Bearer").field(&REDACTED).finish(),
+            Self::TokenProvider(_) => f.debug_tuple("TokenProvider").finish(),
+            Self::Auto { username, .. } => f
+                .debug_struct("Auto")
+                .field("username", username)
+                .field("password", &REDACTED)
+                .finish(),
+        }
+    }
+}
+
+/// Source of the tokens of `Auth::TokenProvider`
+///
+/// Called before every request, implementations are expected to cache the token and refresh it
+/// when it is about to expire.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+pub trait TokenProvider: Send + Sync {
+    async fn get_token(&self) -> Result<String>;
+}
+
+/// The high level operations of `Client`, for mocking it in tests or wrapping it
+///
+/// The trait is object safe, bodies are taken as `reqwest::Body` instead of `Into<Body>`.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+pub trait DavClient: Send + Sync {
+    async fn get(&self, path: &str) -> Result<Response>;
+
+    async fn put(&self, path: &str, body: reqwest::Body) -> Result<put_cmd::PutResponse>;
+
+    async fn list(&self, path: &str, depth: Depth) -> Result<Vec<list_cmd::ListEntity>>;
+
+    async fn propfind(
+        &self,
+        path: &str,
+        depth: Depth,
+        request: propfind_cmd::PropfindRequest,
+    ) -> Result<Vec<list_cmd::ListResponse>>;
+
+    async fn delete(&self, path: &str) -> Result<()>;
+
+    async fn mkcol(&self, path: &str) -> Result<()>;
+
+    async fn mv(&self, from: &str, to: &str) -> Result<()>;
+
+    async fn cp(&self, from: &str, to: &str) -> Result<()>;
 }
 
 #[derive(Debug, Clone)]
@@ -222,3 +682,27 @@ pub enum Depth {
     Number(i64),
     Infinity,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Auth;
+
+    #[test]
+    fn auth_debug_hides_secrets() {
+        let auths = [
+            Auth::Basic("user".to_owned(), "secret".to_owned()),
+            #[cfg(feature = "digest")]
+            Auth::Digest("user".to_owned(), "secret".to_owned()),
+            Auth::Bearer("secret".to_owned()),
+            Auth::Auto {
+                username: "user".to_owned(),
+                password: "secret".to_owned(),
+            },
+        ];
+        for auth in auths {
+            let debug = format!("{:?}", auth);
+            assert!(!debug.contains("secret"), "{}", debug);
+            assert!(debug.contains("***"), "{}", debug);
+        }
+    }
+}
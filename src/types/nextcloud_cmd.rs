@@ -0,0 +1,281 @@
+//! Types and serialisation for the Nextcloud specific DAV endpoints.
+
+use crate::types::timestamp::{DateTime, DavDateTime};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::types::xml_escape;
+
+/// Namespace of the ownCloud properties, also used by Nextcloud.
+pub const OC_NAMESPACE: &str = "http://owncloud.org/ns";
+/// Namespace of the Nextcloud properties.
+pub const NC_NAMESPACE: &str = "http://nextcloud.org/ns";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIdMultiStatus {
+    #[serde(rename = "response", default)]
+    pub responses: Vec<FileIdResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIdResponse {
+    pub href: String,
+    #[serde(rename = "propstat", default)]
+    pub prop_stat: Vec<FileIdPropStat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIdPropStat {
+    #[serde(default)]
+    pub status: String,
+    pub prop: FileIdProp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIdProp {
+    #[serde(rename = "fileid")]
+    pub file_id: Option<String>,
+}
+
+/// A previous version of a file kept by the Nextcloud versions app.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileVersion {
+    pub href: String,
+    pub file_id: String,
+    /// Identifier of the version, the unix timestamp it was created at.
+    pub version_id: String,
+    pub last_modified: DateTime,
+    pub content_length: i64,
+}
+
+/// A file or folder in the Nextcloud trash bin, see `Client::list_trash`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub href: String,
+    /// Name of the item in the trash bin, the original name with a `.d<timestamp>` suffix.
+    pub id: String,
+    /// The name the item had before it was deleted.
+    pub name: String,
+    /// Path of the item before it was deleted, relative to the user's files root.
+    pub original_location: String,
+    pub deleted_at: Option<DateTime>,
+    pub content_length: Option<i64>,
+    pub is_folder: bool,
+}
+
+/// A file to send with `bulk_upload`.
+#[derive(Debug, Clone)]
+pub struct BulkFile {
+    /// Use absolute path to the webdav server file location
+    pub path: String,
+    pub content: Vec<u8>,
+    pub mtime: Option<DateTime>,
+}
+
+/// Outcome of a single file of a bulk upload.
+#[derive(Debug, Clone)]
+pub struct BulkUploadResult {
+    pub path: String,
+    pub etag: Option<String>,
+    /// Error reported by the server for this file, `None` on success.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct BulkResponseEntry {
+    #[serde(default)]
+    pub error: bool,
+    pub etag: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Requested dimensions of a server generated preview.
+#[derive(Debug, Clone)]
+pub struct PreviewOptions {
+    pub width: u32,
+    pub height: u32,
+    /// Keep the aspect ratio instead of cropping to exactly `width` x `height`.
+    pub keep_aspect: bool,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> Self {
+        PreviewOptions {
+            width: 256,
+            height: 256,
+            keep_aspect: true,
+        }
+    }
+}
+
+/// Settings of `Client::put_chunked`
+#[derive(Debug, Clone)]
+pub struct ChunkOptions {
+    /// Bytes per chunk, Nextcloud asks for 5 MiB to 5 GiB except for the last chunk.
+    pub chunk_size: usize,
+    /// Name of the upload folder, generated when not set
+    ///
+    /// An interrupted upload is resumed by passing the same id again, chunks already on the
+    /// server with the expected size are not sent twice.
+    pub upload_id: Option<String>,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        ChunkOptions {
+            chunk_size: 10 * 1024 * 1024,
+            upload_id: None,
+        }
+    }
+}
+
+/// Filters and pagination of a Nextcloud SEARCH request.
+///
+/// All filters are combined with `and`, names and mime types use SQL `LIKE` wildcards (`%`).
+#[derive(Debug, Clone, Default)]
+pub struct NextcloudSearch {
+    name: Option<String>,
+    mime_type: Option<String>,
+    min_size: Option<i64>,
+    max_size: Option<i64>,
+    modified_after: Option<DateTime>,
+    modified_before: Option<DateTime>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+impl NextcloudSearch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name_like(mut self, pattern: &str) -> Self {
+        self.name = Some(pattern.to_owned());
+        self
+    }
+
+    pub fn mime_type_like(mut self, pattern: &str) -> Self {
+        self.mime_type = Some(pattern.to_owned());
+        self
+    }
+
+    pub fn min_size(mut self, bytes: i64) -> Self {
+        self.min_size = Some(bytes);
+        self
+    }
+
+    pub fn max_size(mut self, bytes: i64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    pub fn modified_after(mut self, time: DateTime) -> Self {
+        self.modified_after = Some(time);
+        self
+    }
+
+    pub fn modified_before(mut self, time: DateTime) -> Self {
+        self.modified_before = Some(time);
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    fn conditions(&self) -> Vec<String> {
+        let compare = |operator: &str, prop: &str, literal: String| {
+            format!(
+                "<d:{op}><d:prop><{prop}/></d:prop><d:literal>{literal}</d:literal></d:{op}>",
+                op = operator,
+                prop = prop,
+                literal = xml_escape(&literal)
+            )
+        };
+        let time = |time: &DateTime| time.to_rfc3339_seconds();
+        let mut conditions = vec![];
+        if let Some(name) = &self.name {
+            conditions.push(compare("like", "d:displayname", name.clone()));
+        }
+        if let Some(mime_type) = &self.mime_type {
+            conditions.push(compare("like", "d:getcontenttype", mime_type.clone()));
+        }
+        if let Some(size) = self.min_size {
+            conditions.push(compare("gte", "oc:size", size.to_string()));
+        }
+        if let Some(size) = self.max_size {
+            conditions.push(compare("lte", "oc:size", size.to_string()));
+        }
+        if let Some(after) = &self.modified_after {
+            conditions.push(compare("gt", "d:getlastmodified", time(after)));
+        }
+        if let Some(before) = &self.modified_before {
+            conditions.push(compare("lt", "d:getlastmodified", time(before)));
+        }
+        conditions
+    }
+
+    /// The `searchrequest` body for a search below `scope`, an href relative to the dav root.
+    pub fn to_xml(&self, scope: &str, props: &str) -> String {
+        let mut conditions = self.conditions();
+        let condition = match conditions.len() {
+            0 => "<d:like><d:prop><d:displayname/></d:prop><d:literal>%</d:literal></d:like>"
+                .to_owned(),
+            1 => conditions.remove(0),
+            _ => format!("<d:and>{}</d:and>", conditions.join("")),
+        };
+        let limit = match (self.limit, self.offset) {
+            (None, None) => String::new(),
+            (limit, offset) => format!(
+                "<d:limit>{}{}</d:limit>",
+                limit
+                    .map(|limit| format!("<d:nresults>{}</d:nresults>", limit))
+                    .unwrap_or_default(),
+                offset
+                    .map(|offset| format!("<ns:firstresult>{}</ns:firstresult>", offset))
+                    .unwrap_or_default()
+            ),
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<d:searchrequest xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns" xmlns:ns="https://github.com/icewind1991/SearchDAV/ns">
+    <d:basicsearch>
+        <d:select><d:prop>{props}</d:prop></d:select>
+        <d:from><d:scope><d:href>{scope}</d:href><d:depth>infinity</d:depth></d:scope></d:from>
+        <d:where>{condition}</d:where>
+        <d:orderby/>
+        {limit}
+    </d:basicsearch>
+</d:searchrequest>"#,
+            props = props,
+            scope = xml_escape(scope),
+            condition = condition,
+            limit = limit
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_body_combines_filters() {
+        let xml = NextcloudSearch::new()
+            .mime_type_like("application/pdf")
+            .min_size(1024)
+            .limit(10)
+            .offset(20)
+            .to_xml("/files/alice/Documents", "<d:getetag/>");
+        assert!(xml.contains("<d:and><d:like><d:prop><d:getcontenttype/></d:prop><d:literal>application/pdf</d:literal></d:like><d:gte><d:prop><oc:size/></d:prop><d:literal>1024</d:literal></d:gte></d:and>"));
+        assert!(xml.contains("<d:href>/files/alice/Documents</d:href>"));
+        assert!(xml.contains(
+            "<d:limit><d:nresults>10</d:nresults><ns:firstresult>20</ns:firstresult></d:limit>"
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+//! Types and serialisation for the ownCloud Infinite Scale Graph drives API.
+
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriveCollection {
+    #[serde(default)]
+    pub value: Vec<Drive>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Drive {
+    pub id: String,
+    pub name: String,
+    pub drive_type: String,
+    pub root: DriveRoot,
+    pub quota: Option<SpaceQuota>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveRoot {
+    pub web_dav_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceQuota {
+    pub total: Option<i64>,
+    pub used: Option<i64>,
+    pub remaining: Option<i64>,
+}
+
+/// The kind of an oCIS space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpaceType {
+    Personal,
+    Project,
+    Share,
+    Other(String),
+}
+
+/// A storage space of an ownCloud Infinite Scale server.
+#[derive(Debug, Clone)]
+pub struct Space {
+    pub id: String,
+    pub name: String,
+    pub space_type: SpaceType,
+    /// Root of the space, usable as client host.
+    pub web_dav_url: String,
+    pub quota: Option<SpaceQuota>,
+}
+
+impl From<Drive> for Space {
+    fn from(drive: Drive) -> Self {
+        Space {
+            id: drive.id,
+            name: drive.name,
+            space_type: match drive.drive_type.as_str() {
+                "personal" => SpaceType::Personal,
+                "project" => SpaceType::Project,
+                "virtual" | "mountpoint" => SpaceType::Share,
+                other => SpaceType::Other(other.to_owned()),
+            },
+            web_dav_url: drive.root.web_dav_url,
+            quota: drive.quota,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_drives() {
+        let json = r#"{"value":[{"id":"1284d238-aa92$4c42","name":"Alice","driveType":"personal","root":{"webDavUrl":"https://ocis.example.com/dav/spaces/1284d238-aa92$4c42"},"quota":{"total":1000,"used":10,"remaining":990,"state":"normal"}},{"id":"p1","name":"Marketing","driveType":"project","root":{"webDavUrl":"https://ocis.example.com/dav/spaces/p1"}}]}"#;
+        let drives: DriveCollection = serde_json::from_str(json).unwrap();
+        let spaces: Vec<Space> = drives.value.into_iter().map(Space::from).collect();
+        assert_eq!(spaces[0].space_type, SpaceType::Personal);
+        assert_eq!(spaces[0].quota.as_ref().unwrap().remaining, Some(990));
+        assert_eq!(spaces[1].space_type, SpaceType::Project);
+        assert_eq!(
+            spaces[1].web_dav_url,
+            "https://ocis.example.com/dav/spaces/p1"
+        );
+    }
+}
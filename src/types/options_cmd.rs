@@ -0,0 +1,108 @@
+//! Capabilities advertised in the response to OPTIONS.
+
+use reqwest::header::{HeaderMap, ALLOW};
+
+/// The `DAV` and `Allow` headers of an OPTIONS response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DavCapabilities {
+    /// Tokens of the `DAV` header, e.g. `1`, `2`, `calendar-access`, `<http://apache.org/dav/propset/fs/1>`.
+    pub compliance: Vec<String>,
+    /// Methods of the `Allow` header, upper case.
+    pub allowed_methods: Vec<String>,
+}
+
+impl DavCapabilities {
+    pub fn from_headers(headers: &HeaderMap) -> DavCapabilities {
+        let tokens = |name| -> Vec<String> {
+            headers
+                .get_all(name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .map(|token| token.trim().to_owned())
+                .filter(|token| !token.is_empty())
+                .collect()
+        };
+        DavCapabilities {
+            compliance: tokens("dav"),
+            allowed_methods: tokens(ALLOW.as_str())
+                .into_iter()
+                .map(|method| method.to_ascii_uppercase())
+                .collect(),
+        }
+    }
+
+    /// Whether the `DAV` header lists `token`, compared case-insensitively.
+    pub fn supports(&self, token: &str) -> bool {
+        self.compliance
+            .iter()
+            .any(|compliance| compliance.eq_ignore_ascii_case(token))
+    }
+
+    /// Whether the `Allow` header lists `method`.
+    pub fn allows(&self, method: &str) -> bool {
+        self.allowed_methods
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(method))
+    }
+
+    /// Compliance class 1, basic WebDAV.
+    pub fn is_class_1(&self) -> bool {
+        self.supports("1")
+    }
+
+    /// Compliance class 2, locking.
+    pub fn is_class_2(&self) -> bool {
+        self.supports("2")
+    }
+
+    /// Compliance class 3, RFC 4918 revisions.
+    pub fn is_class_3(&self) -> bool {
+        self.supports("3")
+    }
+
+    /// WebDAV ACL (RFC 3744).
+    pub fn access_control(&self) -> bool {
+        self.supports("access-control")
+    }
+
+    /// CalDAV (RFC 4791).
+    pub fn calendar_access(&self) -> bool {
+        self.supports("calendar-access")
+    }
+
+    /// CardDAV (RFC 6352).
+    pub fn addressbook(&self) -> bool {
+        self.supports("addressbook")
+    }
+
+    /// DeltaV (RFC 3253) versioning.
+    pub fn versioning(&self) -> bool {
+        self.supports("version-control") || self.allows("VERSION-CONTROL")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dav_and_allow_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append("dav", "1, 2, 3, access-control".parse().unwrap());
+        headers.append(
+            "dav",
+            "calendar-access, <http://apache.org/dav/propset/fs/1>"
+                .parse()
+                .unwrap(),
+        );
+        headers.append("allow", "OPTIONS, GET, PROPFIND, lock".parse().unwrap());
+        let capabilities = DavCapabilities::from_headers(&headers);
+        assert!(capabilities.is_class_2() && capabilities.is_class_3());
+        assert!(capabilities.access_control() && capabilities.calendar_access());
+        assert!(!capabilities.addressbook() && !capabilities.versioning());
+        assert!(capabilities.supports("<http://apache.org/dav/propset/fs/1>"));
+        assert!(capabilities.allows("LOCK"));
+        assert!(!capabilities.allows("PUT"));
+    }
+}
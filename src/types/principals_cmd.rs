@@ -0,0 +1,203 @@
+//! Types of the principals of RFC 3744 and of their search.
+
+use quick_xml::events::Event;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+use crate::types::list_cmd::CALDAV_NAMESPACE;
+use crate::types::proppatch_cmd::PropName;
+use crate::types::{xml_escape, Result};
+
+pub(crate) const SABREDAV_NAMESPACE: &str = "http://sabredav.org/ns";
+
+/// The properties asked for principals, see `Principal`.
+pub(crate) const PRINCIPAL_PROPS: [(&str, &str); 3] = [
+    ("DAV:", "displayname"),
+    (SABREDAV_NAMESPACE, "email-address"),
+    (CALDAV_NAMESPACE, "calendar-user-address-set"),
+];
+
+/// A user or group known to the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub href: String,
+    pub display_name: Option<String>,
+    /// From the SabreDAV `email-address`, or the `mailto:` address of the CalDAV
+    /// `calendar-user-address-set`.
+    pub email: Option<String>,
+}
+
+/// A `principal-property-search`, see `Client::principal_search`
+///
+/// ```
+/// use reqwest_dav::types::principals_cmd::PrincipalQuery;
+///
+/// let query = PrincipalQuery::new("doe").property("urn:ietf:params:xml:ns:caldav", "calendar-user-type");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrincipalQuery {
+    /// Text the properties have to contain, matched case-insensitively by most servers.
+    pub text: String,
+    /// Properties searched, a principal matches when any of them does.
+    pub properties: Vec<PropName>,
+}
+
+impl PrincipalQuery {
+    /// Principals whose display name or SabreDAV email address contains `text`
+    pub fn new(text: impl Into<String>) -> Self {
+        PrincipalQuery {
+            text: text.into(),
+            properties: vec![
+                PropName::new("DAV:", "displayname"),
+                PropName::new(SABREDAV_NAMESPACE, "email-address"),
+            ],
+        }
+    }
+
+    /// Search another property too
+    pub fn property(mut self, namespace: &str, name: &str) -> Self {
+        self.properties.push(PropName::new(namespace, name));
+        self
+    }
+
+    pub(crate) fn to_xml(&self) -> String {
+        let searches = self
+            .properties
+            .iter()
+            .map(|prop| {
+                format!(
+                    "<D:property-search><D:prop>{}</D:prop><D:match>{}</D:match></D:property-search>",
+                    prop.to_empty_element(),
+                    xml_escape(&self.text)
+                )
+            })
+            .collect::<String>();
+        let props = PRINCIPAL_PROPS
+            .iter()
+            .map(|(namespace, name)| PropName::new(*namespace, *name).to_empty_element())
+            .collect::<String>();
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?><D:principal-property-search xmlns:D="DAV:" test="anyof">{}<D:prop>{}</D:prop><D:apply-to-principal-collection-set/></D:principal-property-search>"#,
+            searches, props
+        )
+    }
+}
+
+/// The principals of a multistatus holding the `PRINCIPAL_PROPS`, in order.
+pub(crate) fn parse_principals(xml: &str) -> Result<Vec<Principal>> {
+    let mut reader = NsReader::from_str(xml);
+    let mut principals = vec![];
+    // (namespace, local name) of the open elements
+    let mut stack: Vec<(Vec<u8>, Vec<u8>)> = vec![];
+    let mut text = String::new();
+    let mut current: Option<Principal> = None;
+    let mut address: Option<String> = None;
+    loop {
+        let (resolved, event) = reader.read_resolved_event()?;
+        let namespace = match &resolved {
+            ResolveResult::Bound(Namespace(uri)) => uri.to_vec(),
+            _ => vec![],
+        };
+        match event {
+            Event::Start(element) => {
+                let name = element.local_name().as_ref().to_vec();
+                if (namespace.as_slice(), name.as_slice()) == (b"DAV:", b"response") {
+                    current = Some(Principal {
+                        href: String::new(),
+                        display_name: None,
+                        email: None,
+                    });
+                    address = None;
+                }
+                stack.push((namespace, name));
+                text.clear();
+            }
+            Event::End(_) => {
+                let Some((namespace, name)) = stack.pop() else {
+                    continue;
+                };
+                let value = std::mem::take(&mut text).trim().to_owned();
+                let parent = stack.last().map(|(_, name)| name.as_slice());
+                let Some(principal) = &mut current else {
+                    continue;
+                };
+                match (namespace.as_slice(), name.as_slice()) {
+                    (b"DAV:", b"href") if parent == Some(b"response") => principal.href = value,
+                    (b"DAV:", b"href") if parent == Some(b"calendar-user-address-set") => {
+                        if let Some(email) = value.strip_prefix("mailto:") {
+                            address.get_or_insert_with(|| email.to_owned());
+                        }
+                    }
+                    (b"DAV:", b"displayname") if !value.is_empty() => {
+                        principal.display_name = Some(value)
+                    }
+                    (b"http://sabredav.org/ns", b"email-address") if !value.is_empty() => {
+                        principal.email = Some(value)
+                    }
+                    (b"DAV:", b"response") => {
+                        if let Some(mut principal) = current.take() {
+                            principal.email = principal.email.or(address.take());
+                            principals.push(principal);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(value) => text.push_str(&value.unescape()?),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(principals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn principals_of_multistatus() {
+        let xml = r#"<?xml version="1.0"?>
+        <d:multistatus xmlns:d="DAV:" xmlns:s="http://sabredav.org/ns" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+            <d:response>
+                <d:href>/principals/alice/</d:href>
+                <d:propstat>
+                    <d:prop>
+                        <d:displayname>Alice &amp; Co</d:displayname>
+                        <cal:calendar-user-address-set>
+                            <d:href>/principals/alice/</d:href>
+                            <d:href>mailto:alice@example.com</d:href>
+                        </cal:calendar-user-address-set>
+                    </d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+                <d:propstat>
+                    <d:prop><s:email-address/></d:prop>
+                    <d:status>HTTP/1.1 404 Not Found</d:status>
+                </d:propstat>
+            </d:response>
+            <d:response>
+                <d:href>/principals/bob/</d:href>
+                <d:propstat>
+                    <d:prop><s:email-address>bob@example.com</s:email-address></d:prop>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+        assert_eq!(
+            parse_principals(xml).unwrap(),
+            vec![
+                Principal {
+                    href: "/principals/alice/".to_owned(),
+                    display_name: Some("Alice & Co".to_owned()),
+                    email: Some("alice@example.com".to_owned()),
+                },
+                Principal {
+                    href: "/principals/bob/".to_owned(),
+                    display_name: None,
+                    email: Some("bob@example.com".to_owned()),
+                },
+            ]
+        );
+    }
+}
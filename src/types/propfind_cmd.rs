@@ -0,0 +1,84 @@
+//! Types of PROPFIND requests.
+
+use crate::types::proppatch_cmd::PropName;
+use crate::types::xml_escape;
+
+/// The properties a PROPFIND asks for, see `Client::list_with`
+#[derive(Debug, Clone, Default)]
+pub enum PropfindRequest {
+    /// All the live and dead properties, what `Client::list` asks for.
+    #[default]
+    AllProp,
+    /// All the properties and the given ones, which `allprop` may leave out like
+    /// `supported-report-set`.
+    AllPropInclude(Vec<PropName>),
+    /// Only the names of the properties, without their values.
+    PropName,
+    /// The given properties.
+    Props(Vec<PropName>),
+}
+
+impl PropfindRequest {
+    /// Ask for the given `(namespace, name)` properties.
+    pub fn props<N, L>(props: impl IntoIterator<Item = (N, L)>) -> PropfindRequest
+    where
+        N: Into<String>,
+        L: Into<String>,
+    {
+        PropfindRequest::Props(
+            props
+                .into_iter()
+                .map(|(namespace, name)| PropName::new(namespace, name))
+                .collect(),
+        )
+    }
+
+    /// Ask for all properties and the given `(namespace, name)` ones.
+    pub fn all_prop_include<N, L>(props: impl IntoIterator<Item = (N, L)>) -> PropfindRequest
+    where
+        N: Into<String>,
+        L: Into<String>,
+    {
+        PropfindRequest::AllPropInclude(
+            props
+                .into_iter()
+                .map(|(namespace, name)| PropName::new(namespace, name))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn to_xml(&self) -> String {
+        let mut xml =
+            r#"<?xml version="1.0" encoding="utf-8" ?><D:propfind xmlns:D="DAV:">"#.to_owned();
+        match self {
+            PropfindRequest::AllProp => xml.push_str("<D:allprop/>"),
+            PropfindRequest::AllPropInclude(props) => {
+                xml.push_str("<D:allprop/><D:include>");
+                push_props(&mut xml, props);
+                xml.push_str("</D:include>");
+            }
+            PropfindRequest::PropName => xml.push_str("<D:propname/>"),
+            PropfindRequest::Props(props) => {
+                xml.push_str("<D:prop>");
+                push_props(&mut xml, props);
+                xml.push_str("</D:prop>");
+            }
+        }
+        xml.push_str("</D:propfind>");
+        xml
+    }
+}
+
+fn push_props(xml: &mut String, props: &[PropName]) {
+    for prop in props {
+        if prop.namespace == "DAV:" {
+            xml.push_str(&format!("<D:{}/>", prop.name));
+        } else {
+            xml.push_str(&format!(
+                r#"<x:{} xmlns:x="{}"/>"#,
+                prop.name,
+                xml_escape(&prop.namespace)
+            ));
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Types and serialisation for the PROPPATCH command.
+
+use std::collections::BTreeMap;
+
+use serde::de::IgnoredAny;
+use serde_derive::Deserialize;
+
+use crate::types::status_cmd::status_code;
+use crate::types::xml_escape;
+
+/// A property, identified by its namespace and local name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropName {
+    pub namespace: String,
+    pub name: String,
+}
+
+impl PropName {
+    pub fn new(namespace: impl Into<String>, name: impl Into<String>) -> PropName {
+        PropName {
+            namespace: namespace.into(),
+            name: name.into(),
+        }
+    }
+
+    /// The empty element of the property, declaring its namespace unless it is `DAV:`.
+    pub(crate) fn to_empty_element(&self) -> String {
+        match self.namespace.as_str() {
+            "DAV:" => format!("<D:{}/>", self.name),
+            namespace => format!(r#"<x:{} xmlns:x="{}"/>"#, self.name, xml_escape(namespace)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum PropOperation {
+    Set(PropName, String),
+    Remove(PropName),
+}
+
+/// Properties to set and remove in one PROPPATCH, applied by the server in order
+///
+/// ```
+/// use reqwest_dav::types::proppatch_cmd::PropPatch;
+///
+/// let patch = PropPatch::new()
+///     .set("http://example.com/ns", "author", "Alice")
+///     .remove("http://example.com/ns", "draft");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PropPatch {
+    operations: Vec<PropOperation>,
+}
+
+impl PropPatch {
+    pub fn new() -> PropPatch {
+        PropPatch::default()
+    }
+
+    /// Set the property to a text value.
+    pub fn set(
+        mut self,
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.operations.push(PropOperation::Set(
+            PropName::new(namespace, name),
+            value.into(),
+        ));
+        self
+    }
+
+    pub fn remove(mut self, namespace: impl Into<String>, name: impl Into<String>) -> Self {
+        self.operations
+            .push(PropOperation::Remove(PropName::new(namespace, name)));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    pub(crate) fn properties(&self) -> impl Iterator<Item = &PropName> {
+        self.operations.iter().map(|operation| match operation {
+            PropOperation::Set(name, _) | PropOperation::Remove(name) => name,
+        })
+    }
+
+    /// The `propertyupdate` body, declaring one prefix per namespace.
+    pub(crate) fn to_xml(&self) -> String {
+        let mut namespaces: Vec<&str> = vec![];
+        for property in self.properties() {
+            if property.namespace != "DAV:" && !namespaces.contains(&property.namespace.as_str()) {
+                namespaces.push(&property.namespace);
+            }
+        }
+        let prefix = |property: &PropName| match namespaces
+            .iter()
+            .position(|namespace| *namespace == property.namespace)
+        {
+            Some(index) => format!("ns{}", index),
+            None => "D".to_owned(),
+        };
+        let mut xml =
+            r#"<?xml version="1.0" encoding="utf-8" ?><D:propertyupdate xmlns:D="DAV:""#.to_owned();
+        for (index, namespace) in namespaces.iter().enumerate() {
+            xml.push_str(&format!(
+                r#" xmlns:ns{}="{}""#,
+                index,
+                xml_escape(namespace)
+            ));
+        }
+        xml.push('>');
+        for operation in &self.operations {
+            match operation {
+                PropOperation::Set(property, value) => xml.push_str(&format!(
+                    "<D:set><D:prop><{prefix}:{name}>{value}</{prefix}:{name}></D:prop></D:set>",
+                    prefix = prefix(property),
+                    name = property.name,
+                    value = xml_escape(value),
+                )),
+                PropOperation::Remove(property) => xml.push_str(&format!(
+                    "<D:remove><D:prop><{}:{}/></D:prop></D:remove>",
+                    prefix(property),
+                    property.name,
+                )),
+            }
+        }
+        xml.push_str("</D:propertyupdate>");
+        xml
+    }
+}
+
+/// What the server did with one property of a PROPPATCH.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropOutcome {
+    pub property: PropName,
+    /// `200` when applied, `424` when skipped because another property failed.
+    pub status: u16,
+    pub description: Option<String>,
+}
+
+impl PropOutcome {
+    pub fn is_ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PropPatchMultiStatus {
+    #[serde(rename = "response", default)]
+    responses: Vec<PropPatchResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PropPatchResponse {
+    #[serde(rename = "propstat", default)]
+    prop_stats: Vec<PropPatchPropStat>,
+    #[serde(rename = "responsedescription")]
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PropPatchPropStat {
+    /// Local names only, the namespaces are lost when parsing.
+    #[serde(default)]
+    prop: BTreeMap<String, IgnoredAny>,
+    #[serde(default)]
+    status: String,
+    #[serde(rename = "responsedescription")]
+    description: Option<String>,
+}
+
+impl PropPatchMultiStatus {
+    /// The outcome of each property of `patch`, matched by local name.
+    pub(crate) fn outcomes(&self, patch: &PropPatch) -> Vec<PropOutcome> {
+        patch
+            .properties()
+            .map(|property| {
+                let found = self.responses.iter().find_map(|response| {
+                    response
+                        .prop_stats
+                        .iter()
+                        .find(|prop_stat| prop_stat.prop.contains_key(&property.name))
+                        .map(|prop_stat| (response, prop_stat))
+                });
+                match found {
+                    Some((response, prop_stat)) => PropOutcome {
+                        property: property.clone(),
+                        status: status_code(&prop_stat.status),
+                        description: prop_stat
+                            .description
+                            .clone()
+                            .or_else(|| response.description.clone())
+                            .map(|description| description.trim().to_owned()),
+                    },
+                    // Servers may leave out properties they applied.
+                    None => PropOutcome {
+                        property: property.clone(),
+                        status: 200,
+                        description: None,
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_update_body() {
+        let patch = PropPatch::new()
+            .set("http://example.com/ns", "author", "Alice & Bob")
+            .set("DAV:", "displayname", "Report")
+            .remove("http://example.com/ns", "draft");
+        assert_eq!(
+            patch.to_xml(),
+            concat!(
+                r#"<?xml version="1.0" encoding="utf-8" ?><D:propertyupdate xmlns:D="DAV:" xmlns:ns0="http://example.com/ns">"#,
+                "<D:set><D:prop><ns0:author>Alice &amp; Bob</ns0:author></D:prop></D:set>",
+                "<D:set><D:prop><D:displayname>Report</D:displayname></D:prop></D:set>",
+                "<D:remove><D:prop><ns0:draft/></D:prop></D:remove>",
+                "</D:propertyupdate>"
+            )
+        );
+    }
+}
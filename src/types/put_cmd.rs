@@ -0,0 +1,54 @@
+//! Types of uploads.
+
+use reqwest::Response;
+
+#[cfg(feature = "checksum")]
+use crate::types::checksum_cmd::ChecksumAlgorithm;
+use crate::types::request_cmd::RequestOptions;
+use crate::types::timestamp::DateTime;
+
+/// Settings of `Client::put_with`
+#[derive(Debug, Clone, Default)]
+pub struct PutOptions {
+    /// Create missing parent collections before uploading, see `Client::mkcol_recursive`.
+    pub ensure_parent: bool,
+    /// Modification time to keep, sent in `X-OC-Mtime` to Nextcloud and ownCloud and set with
+    /// `Client::set_mtime` on servers which do not accept it.
+    pub mtime: Option<DateTime>,
+    /// Send the checksum of the body in `OC-Checksum`, and in `Content-MD5` for MD5, so the
+    /// server can reject a corrupted upload. Only for bodies held in memory.
+    #[cfg(feature = "checksum")]
+    pub checksum: Option<ChecksumAlgorithm>,
+    /// Headers, query parameters and timeout of the PUT.
+    pub request: RequestOptions,
+}
+
+/// What the server told about an upload, returned by `Client::put`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PutResponse {
+    /// The tag of the new version, from `ETag` or the `OC-ETag` of Nextcloud and ownCloud.
+    pub etag: Option<String>,
+    /// The `OC-FileId` of Nextcloud and ownCloud.
+    pub file_id: Option<String>,
+    pub status: u16,
+    /// The server took `PutOptions::mtime`.
+    pub mtime_set: bool,
+}
+
+impl PutResponse {
+    pub(crate) fn from_response(response: &Response) -> PutResponse {
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        PutResponse {
+            etag: header("etag").or_else(|| header("oc-etag")),
+            file_id: header("oc-fileid"),
+            status: response.status().as_u16(),
+            mtime_set: header("x-oc-mtime").as_deref() == Some("accepted"),
+        }
+    }
+}
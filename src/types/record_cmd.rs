@@ -0,0 +1,87 @@
+//! Recorded traffic for replaying captured server behaviour in tests.
+
+#[cfg(feature = "json")]
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde_derive::{Deserialize, Serialize};
+
+/// One request and the response the server gave to it.
+///
+/// Credentials are scrubbed from the url and the bodies, and the `Authorization`,
+/// `Cookie` and `Set-Cookie` headers are never recorded. Response bodies which are not UTF-8
+/// are stored in base64, flagged by `binary`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Interaction {
+    pub method: String,
+    /// Path and query of the request.
+    pub url: String,
+    #[serde(default)]
+    pub request_body: Option<String>,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub binary: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordMode {
+    /// Send requests to the server and keep what was exchanged.
+    Record,
+    /// Answer requests from the recorded interactions without touching the network.
+    Replay,
+}
+
+/// Interceptor recording or replaying the traffic of a client, see `ClientBuilder::set_recorder`
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    pub(crate) mode: RecordMode,
+    pub(crate) interactions: Arc<Mutex<Vec<Interaction>>>,
+    /// Index of the interactions already replayed.
+    pub(crate) replayed: Arc<Mutex<Vec<bool>>>,
+}
+
+impl Recorder {
+    pub fn record() -> Recorder {
+        Recorder::new(RecordMode::Record, vec![])
+    }
+
+    /// Replay `interactions`, each one answers a single request with the same method and url.
+    pub fn replay(interactions: Vec<Interaction>) -> Recorder {
+        Recorder::new(RecordMode::Replay, interactions)
+    }
+
+    fn new(mode: RecordMode, interactions: Vec<Interaction>) -> Recorder {
+        Recorder {
+            mode,
+            replayed: Arc::new(Mutex::new(vec![false; interactions.len()])),
+            interactions: Arc::new(Mutex::new(interactions)),
+        }
+    }
+
+    /// Replay the interactions of a fixture file written by `save`.
+    #[cfg(feature = "json")]
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Recorder> {
+        let content = std::fs::read_to_string(path)?;
+        let interactions = serde_json::from_str(&content)?;
+        Ok(Recorder::replay(interactions))
+    }
+
+    /// Write the interactions to a JSON fixture file.
+    #[cfg(feature = "json")]
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let content = serde_json::to_string_pretty(&self.interactions())?;
+        std::fs::write(path, content)
+    }
+
+    pub fn mode(&self) -> RecordMode {
+        self.mode
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().clone()
+    }
+}
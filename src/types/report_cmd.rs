@@ -0,0 +1,281 @@
+//! Types of REPORT requests not modelled by the crate and of the expand-property REPORT.
+
+use std::collections::HashMap;
+
+use quick_xml::errors::IllFormedError;
+use quick_xml::events::Event;
+use quick_xml::name::{LocalName, Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+use crate::types::list_cmd::{qualified_name, status_is_ok};
+use crate::types::proppatch_cmd::PropName;
+use crate::types::{xml_escape, DecodeError, Depth, Error, FieldError, Result};
+
+/// A REPORT with a body written by the caller, see `Client::report`
+///
+/// ```
+/// use reqwest_dav::types::report_cmd::Report;
+/// use reqwest_dav::Depth;
+///
+/// let report = Report::new(
+///     r#"<?xml version="1.0" encoding="utf-8" ?>
+/// <D:principal-property-search xmlns:D="DAV:">
+///   <D:property-search><D:prop><D:displayname/></D:prop><D:match>doE</D:match></D:property-search>
+///   <D:prop><D:displayname/></D:prop>
+/// </D:principal-property-search>"#,
+/// )
+/// .depth(Depth::Number(0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// The XML request body, its root element names the report.
+    pub body: String,
+    pub depth: Depth,
+}
+
+impl Report {
+    /// A report sent with `Depth: 0`
+    pub fn new(body: impl Into<String>) -> Self {
+        Report {
+            body: body.into(),
+            depth: Depth::Number(0),
+        }
+    }
+
+    pub fn depth(mut self, depth: Depth) -> Self {
+        self.depth = depth;
+        self
+    }
+}
+
+/// A property returned by `Client::expand_property`, with the properties of the resources its
+/// hrefs link to
+///
+/// ```
+/// use reqwest_dav::types::report_cmd::ExpandProperty;
+///
+/// // the display names of the members of a group
+/// let spec = ExpandProperty::new("DAV:", "group-member-set")
+///     .expand(ExpandProperty::new("DAV:", "displayname"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandProperty {
+    pub prop: PropName,
+    /// Properties of the linked resources, the hrefs are returned as they are when empty.
+    pub children: Vec<ExpandProperty>,
+}
+
+impl ExpandProperty {
+    pub fn new(namespace: impl Into<String>, name: impl Into<String>) -> Self {
+        ExpandProperty {
+            prop: PropName::new(namespace, name),
+            children: vec![],
+        }
+    }
+
+    /// Return `child` of the resources the property links to
+    pub fn expand(mut self, child: ExpandProperty) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    fn to_xml(&self) -> String {
+        let attributes = format!(
+            r#"name="{}" namespace="{}""#,
+            xml_escape(&self.prop.name),
+            xml_escape(&self.prop.namespace)
+        );
+        match self.children.is_empty() {
+            true => format!("<D:property {}/>", attributes),
+            false => format!(
+                "<D:property {}>{}</D:property>",
+                attributes,
+                self.children
+                    .iter()
+                    .map(ExpandProperty::to_xml)
+                    .collect::<String>()
+            ),
+        }
+    }
+}
+
+pub(crate) fn expand_property_body(spec: &[ExpandProperty]) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8" ?><D:expand-property xmlns:D="DAV:">{}</D:expand-property>"#,
+        spec.iter().map(ExpandProperty::to_xml).collect::<String>()
+    )
+}
+
+/// A resource of an expand-property response
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpandedResponse {
+    pub href: String,
+    /// The properties the server returned with a 2xx status, keyed by namespace and local name.
+    pub props: HashMap<(String, String), ExpandedProperty>,
+}
+
+impl ExpandedResponse {
+    pub fn prop(&self, namespace: &str, name: &str) -> Option<&ExpandedProperty> {
+        self.props.get(&(namespace.to_owned(), name.to_owned()))
+    }
+}
+
+/// The value of a property of an `ExpandedResponse`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpandedProperty {
+    /// Text of the property outside of the expanded resources, trimmed.
+    pub text: String,
+    /// Hrefs of the property which were not expanded.
+    pub hrefs: Vec<String>,
+    /// The resources the property links to, with the properties asked for them.
+    pub responses: Vec<ExpandedResponse>,
+}
+
+/// The responses of an expand-property multistatus, with the linked resources nested in them.
+pub(crate) fn parse_expanded(xml: &str) -> Result<Vec<ExpandedResponse>> {
+    let mut reader = NsReader::from_str(xml);
+    let mut responses = vec![];
+    let mut found_root = false;
+    loop {
+        let (namespace, event) = reader.read_resolved_event()?;
+        match event {
+            Event::Start(element) => match dav_local_name(&namespace, element.local_name()) {
+                Some("multistatus") => found_root = true,
+                Some("response") if found_root => responses.push(parse_response(&mut reader)?),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !found_root {
+        return Err(Error::Decode(DecodeError::FieldNotFound(FieldError {
+            field: "multistatus".to_owned(),
+        })));
+    }
+    Ok(responses)
+}
+
+fn dav_local_name<'a>(namespace: &ResolveResult, local_name: LocalName<'a>) -> Option<&'a str> {
+    match namespace {
+        ResolveResult::Bound(Namespace(b"DAV:")) => {
+            std::str::from_utf8(local_name.into_inner()).ok()
+        }
+        _ => None,
+    }
+}
+
+/// A `response`, after its start tag.
+fn parse_response(reader: &mut NsReader<&[u8]>) -> Result<ExpandedResponse> {
+    let mut response = ExpandedResponse::default();
+    loop {
+        let (namespace, event) = reader.read_resolved_event()?;
+        match event {
+            Event::Start(element) => match dav_local_name(&namespace, element.local_name()) {
+                Some("href") => response.href = read_text(reader)?,
+                Some("propstat") => {
+                    let (props, status) = parse_prop_stat(reader)?;
+                    if status.is_empty() || status_is_ok(&status) {
+                        response.props.extend(props);
+                    }
+                }
+                _ => {
+                    reader.read_to_end(element.name())?;
+                }
+            },
+            Event::End(_) => return Ok(response),
+            Event::Eof => return Err(unexpected_end()),
+            _ => {}
+        }
+    }
+}
+
+type Props = HashMap<(String, String), ExpandedProperty>;
+
+/// A `propstat`, after its start tag.
+fn parse_prop_stat(reader: &mut NsReader<&[u8]>) -> Result<(Props, String)> {
+    let mut props = HashMap::new();
+    let mut status = String::new();
+    loop {
+        let (namespace, event) = reader.read_resolved_event()?;
+        match event {
+            Event::Start(element) => match dav_local_name(&namespace, element.local_name()) {
+                Some("prop") => props = parse_prop(reader)?,
+                Some("status") => status = read_text(reader)?,
+                _ => {
+                    reader.read_to_end(element.name())?;
+                }
+            },
+            Event::End(_) => return Ok((props, status)),
+            Event::Eof => return Err(unexpected_end()),
+            _ => {}
+        }
+    }
+}
+
+/// A `prop`, after its start tag.
+fn parse_prop(reader: &mut NsReader<&[u8]>) -> Result<Props> {
+    let mut props = HashMap::new();
+    loop {
+        let (namespace, event) = reader.read_resolved_event()?;
+        match event {
+            Event::Start(element) => {
+                let key = qualified_name(&namespace, element.local_name().as_ref());
+                props.insert(key, parse_property(reader)?);
+            }
+            Event::Empty(element) => {
+                let key = qualified_name(&namespace, element.local_name().as_ref());
+                props.insert(key, ExpandedProperty::default());
+            }
+            Event::End(_) => return Ok(props),
+            Event::Eof => return Err(unexpected_end()),
+            _ => {}
+        }
+    }
+}
+
+/// The value of a property, after its start tag.
+fn parse_property(reader: &mut NsReader<&[u8]>) -> Result<ExpandedProperty> {
+    let mut property = ExpandedProperty::default();
+    let mut text = String::new();
+    // elements open inside the property
+    let mut depth = 0;
+    loop {
+        let (namespace, event) = reader.read_resolved_event()?;
+        match event {
+            Event::Start(element) => match dav_local_name(&namespace, element.local_name()) {
+                Some("response") => property.responses.push(parse_response(reader)?),
+                Some("href") => property.hrefs.push(read_text(reader)?),
+                _ => depth += 1,
+            },
+            Event::End(_) if depth == 0 => {
+                property.text = text.trim().to_owned();
+                return Ok(property);
+            }
+            Event::End(_) => depth -= 1,
+            Event::Text(value) => text.push_str(&value.unescape()?),
+            Event::CData(data) => text.push_str(&String::from_utf8_lossy(&data)),
+            Event::Eof => return Err(unexpected_end()),
+            _ => {}
+        }
+    }
+}
+
+/// The trimmed text of an element without children, after its start tag.
+fn read_text(reader: &mut NsReader<&[u8]>) -> Result<String> {
+    let mut text = String::new();
+    loop {
+        match reader.read_event()? {
+            Event::Text(value) => text.push_str(&value.unescape()?),
+            Event::CData(data) => text.push_str(&String::from_utf8_lossy(&data)),
+            Event::End(_) => return Ok(text.trim().to_owned()),
+            Event::Eof => return Err(unexpected_end()),
+            _ => {}
+        }
+    }
+}
+
+/// The document ended inside the multistatus.
+fn unexpected_end() -> Error {
+    quick_xml::Error::from(IllFormedError::MissingEndTag("multistatus".to_owned())).into()
+}
@@ -0,0 +1,63 @@
+//! Settings applied to a single request.
+
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::RequestBuilder;
+use url::Url;
+
+/// Extra headers, query parameters and a timeout for one request, see `Client::get_with`
+///
+/// ```
+/// use std::time::Duration;
+/// use reqwest_dav::types::request_cmd::RequestOptions;
+///
+/// let options = RequestOptions::new()
+///     .header("x-trace".parse().unwrap(), "abc".parse().unwrap())
+///     .query("version", "3")
+///     .timeout(Duration::from_secs(10));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Sent on top of the headers of the request, replacing those of the same name.
+    pub headers: HeaderMap,
+    /// Appended to the query string of the url.
+    pub query: Vec<(String, String)>,
+    /// Time the request may take, from sending it until the body has been read.
+    pub timeout: Option<Duration>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn query(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Add the query parameters to `url`, before the request is signed.
+    pub(crate) fn apply_to_url(&self, url: &mut Url) {
+        if !self.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.query);
+        }
+    }
+
+    pub(crate) fn apply(&self, mut builder: RequestBuilder) -> RequestBuilder {
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        builder.headers(self.headers.clone())
+    }
+}
@@ -0,0 +1,93 @@
+//! Types of the automatic retries.
+
+use std::time::Duration;
+
+use reqwest::{Method, Response, StatusCode};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// When and how often failed requests are sent again, see `ClientBuilder::set_retry_policy`
+///
+/// Connection errors, timeouts and 429, 502, 503 and 504 responses are retried for the
+/// idempotent methods GET, HEAD, OPTIONS, PROPFIND, DELETE and MKCOL, and for PUT when
+/// `retry_put` is set and the body is in memory. Streamed bodies can not be sent twice.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts in total, the first one included.
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for every further one.
+    pub initial_backoff: Duration,
+    /// Upper bound of the wait. A longer `Retry-After` ends the retries, the caller can wait
+    /// for it with `Error::retry_after`.
+    pub max_backoff: Duration,
+    /// Wait a random time between half and all of the backoff.
+    pub jitter: bool,
+    /// Wait as long as the `Retry-After` header of the response asks.
+    pub honor_retry_after: bool,
+    /// Retry PUT too, only when the body can be replayed because it is held in memory.
+    pub retry_put: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            honor_retry_after: true,
+            retry_put: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether requests with `method` are retried.
+    pub fn applies_to(&self, method: &Method) -> bool {
+        match method.as_str() {
+            "GET" | "HEAD" | "OPTIONS" | "PROPFIND" | "DELETE" | "MKCOL" => true,
+            "PUT" => self.retry_put,
+            _ => false,
+        }
+    }
+
+    /// The wait before retry number `retry`, counted from 1
+    ///
+    /// `retry_after` is the wait asked for by the server, which replaces the backoff when
+    /// `honor_retry_after` is set.
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after.filter(|_| self.honor_retry_after) {
+            return retry_after.min(self.max_backoff);
+        }
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        if self.jitter {
+            // uniform in [0.5, 1.0)
+            let random = (uuid::Uuid::new_v4().as_u128() as u64) as f64 / u64::MAX as f64;
+            backoff.mul_f64(0.5 + random / 2.0)
+        } else {
+            backoff
+        }
+    }
+}
+
+/// Statuses telling that the same request may succeed later.
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 502 | 503 | 504)
+}
+
+/// The wait asked for by the `Retry-After` header, in seconds or as a date.
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get("retry-after")?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value)
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Some(date.saturating_sub(now))
+}
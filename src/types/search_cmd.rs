@@ -0,0 +1,282 @@
+//! Types of RFC 5323 DASL `basicsearch` requests.
+
+use std::ops::Not;
+
+use crate::types::proppatch_cmd::PropName;
+use crate::types::xml_escape;
+use crate::Depth;
+
+/// A `where` clause of a `basicsearch`
+///
+/// ```
+/// use reqwest_dav::types::search_cmd::SearchCondition;
+///
+/// let condition = SearchCondition::and([
+///     SearchCondition::like("DAV:", "displayname", "%.pdf"),
+///     !SearchCondition::is_collection(),
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchCondition {
+    And(Vec<SearchCondition>),
+    Or(Vec<SearchCondition>),
+    Not(Box<SearchCondition>),
+    /// A comparison of a property with a literal, the operator is `eq`, `lt`, `lte`, `gt`,
+    /// `gte` or `like`.
+    Compare {
+        operator: &'static str,
+        prop: PropName,
+        literal: String,
+    },
+    /// The resource has the property.
+    IsDefined(PropName),
+    /// The resource is a collection.
+    IsCollection,
+}
+
+impl SearchCondition {
+    pub fn and(conditions: impl IntoIterator<Item = SearchCondition>) -> Self {
+        SearchCondition::And(conditions.into_iter().collect())
+    }
+
+    pub fn or(conditions: impl IntoIterator<Item = SearchCondition>) -> Self {
+        SearchCondition::Or(conditions.into_iter().collect())
+    }
+
+    pub fn eq(namespace: &str, name: &str, literal: impl Into<String>) -> Self {
+        Self::compare("eq", namespace, name, literal)
+    }
+
+    pub fn lt(namespace: &str, name: &str, literal: impl Into<String>) -> Self {
+        Self::compare("lt", namespace, name, literal)
+    }
+
+    pub fn lte(namespace: &str, name: &str, literal: impl Into<String>) -> Self {
+        Self::compare("lte", namespace, name, literal)
+    }
+
+    pub fn gt(namespace: &str, name: &str, literal: impl Into<String>) -> Self {
+        Self::compare("gt", namespace, name, literal)
+    }
+
+    pub fn gte(namespace: &str, name: &str, literal: impl Into<String>) -> Self {
+        Self::compare("gte", namespace, name, literal)
+    }
+
+    /// SQL style pattern match, `%` matches any text and `_` a single character.
+    pub fn like(namespace: &str, name: &str, pattern: impl Into<String>) -> Self {
+        Self::compare("like", namespace, name, pattern)
+    }
+
+    pub fn is_defined(namespace: &str, name: &str) -> Self {
+        SearchCondition::IsDefined(PropName::new(namespace, name))
+    }
+
+    pub fn is_collection() -> Self {
+        SearchCondition::IsCollection
+    }
+
+    fn compare(
+        operator: &'static str,
+        namespace: &str,
+        name: &str,
+        literal: impl Into<String>,
+    ) -> Self {
+        SearchCondition::Compare {
+            operator,
+            prop: PropName::new(namespace, name),
+            literal: literal.into(),
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        let all = |conditions: &[SearchCondition]| {
+            conditions
+                .iter()
+                .map(SearchCondition::to_xml)
+                .collect::<String>()
+        };
+        match self {
+            SearchCondition::And(conditions) => format!("<D:and>{}</D:and>", all(conditions)),
+            SearchCondition::Or(conditions) => format!("<D:or>{}</D:or>", all(conditions)),
+            SearchCondition::Not(condition) => format!("<D:not>{}</D:not>", condition.to_xml()),
+            SearchCondition::Compare {
+                operator,
+                prop,
+                literal,
+            } => format!(
+                "<D:{op}><D:prop>{prop}</D:prop><D:literal>{literal}</D:literal></D:{op}>",
+                op = operator,
+                prop = prop.to_empty_element(),
+                literal = xml_escape(literal)
+            ),
+            SearchCondition::IsDefined(prop) => format!(
+                "<D:is-defined><D:prop>{}</D:prop></D:is-defined>",
+                prop.to_empty_element()
+            ),
+            SearchCondition::IsCollection => "<D:is-collection/>".to_owned(),
+        }
+    }
+}
+
+impl Not for SearchCondition {
+    type Output = SearchCondition;
+
+    fn not(self) -> SearchCondition {
+        SearchCondition::Not(Box::new(self))
+    }
+}
+
+/// A DASL `basicsearch`, see `Client::search`
+///
+/// ```
+/// use reqwest_dav::types::search_cmd::{SearchCondition, SearchQuery};
+///
+/// let query = SearchQuery::new()
+///     .filter(SearchCondition::like("DAV:", "getcontenttype", "image/%"))
+///     .order_by("DAV:", "getlastmodified", false)
+///     .limit(50);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    /// Properties returned for the matches, all of them when empty.
+    pub select: Vec<PropName>,
+    pub condition: Option<SearchCondition>,
+    /// Properties to sort by, ascending when the flag is set.
+    pub order_by: Vec<(PropName, bool)>,
+    pub limit: Option<u32>,
+    /// How far below the scope to search, infinity by default.
+    pub depth: Depth,
+}
+
+impl Default for SearchQuery {
+    fn default() -> Self {
+        SearchQuery {
+            select: vec![],
+            condition: None,
+            order_by: vec![],
+            limit: None,
+            depth: Depth::Infinity,
+        }
+    }
+}
+
+impl SearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return only the given `(namespace, name)` properties
+    ///
+    /// The matches are turned into `ListEntity` values, which needs at least `getlastmodified`
+    /// and `resourcetype`.
+    pub fn select<N, L>(mut self, props: impl IntoIterator<Item = (N, L)>) -> Self
+    where
+        N: Into<String>,
+        L: Into<String>,
+    {
+        self.select = props
+            .into_iter()
+            .map(|(namespace, name)| PropName::new(namespace, name))
+            .collect();
+        self
+    }
+
+    pub fn filter(mut self, condition: SearchCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Sort by a property, after the properties given before
+    pub fn order_by(mut self, namespace: &str, name: &str, ascending: bool) -> Self {
+        self.order_by
+            .push((PropName::new(namespace, name), ascending));
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn depth(mut self, depth: Depth) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// The `searchrequest` body for a search below `scope`, an href.
+    pub(crate) fn to_xml(&self, scope: &str) -> String {
+        let select = match self.select.is_empty() {
+            true => "<D:allprop/>".to_owned(),
+            false => format!(
+                "<D:prop>{}</D:prop>",
+                self.select
+                    .iter()
+                    .map(PropName::to_empty_element)
+                    .collect::<String>()
+            ),
+        };
+        let depth = match self.depth {
+            Depth::Number(depth) => depth.to_string(),
+            Depth::Infinity => "infinity".to_owned(),
+        };
+        let condition = match &self.condition {
+            Some(condition) => format!("<D:where>{}</D:where>", condition.to_xml()),
+            None => String::new(),
+        };
+        let order_by = match self.order_by.is_empty() {
+            true => String::new(),
+            false => format!(
+                "<D:orderby>{}</D:orderby>",
+                self.order_by
+                    .iter()
+                    .map(|(prop, ascending)| format!(
+                        "<D:order><D:prop>{}</D:prop>{}</D:order>",
+                        prop.to_empty_element(),
+                        if *ascending {
+                            "<D:ascending/>"
+                        } else {
+                            "<D:descending/>"
+                        }
+                    ))
+                    .collect::<String>()
+            ),
+        };
+        let limit = match self.limit {
+            Some(limit) => format!("<D:limit><D:nresults>{}</D:nresults></D:limit>", limit),
+            None => String::new(),
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?><D:searchrequest xmlns:D="DAV:"><D:basicsearch><D:select>{select}</D:select><D:from><D:scope><D:href>{scope}</D:href><D:depth>{depth}</D:depth></D:scope></D:from>{condition}{order_by}{limit}</D:basicsearch></D:searchrequest>"#,
+            scope = xml_escape(scope),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basicsearch_body() {
+        let xml = SearchQuery::new()
+            .select([("DAV:", "getetag"), ("http://owncloud.org/ns", "size")])
+            .filter(SearchCondition::and([
+                SearchCondition::like("DAV:", "displayname", "%.pdf"),
+                !SearchCondition::is_collection(),
+            ]))
+            .order_by("DAV:", "getlastmodified", false)
+            .limit(10)
+            .depth(Depth::Number(1))
+            .to_xml("/dav/docs");
+        assert!(xml.contains(
+            r#"<D:select><D:prop><D:getetag/><x:size xmlns:x="http://owncloud.org/ns"/></D:prop></D:select>"#
+        ));
+        assert!(xml.contains("<D:scope><D:href>/dav/docs</D:href><D:depth>1</D:depth></D:scope>"));
+        assert!(xml.contains("<D:where><D:and><D:like><D:prop><D:displayname/></D:prop><D:literal>%.pdf</D:literal></D:like><D:not><D:is-collection/></D:not></D:and></D:where>"));
+        assert!(xml.contains(
+            "<D:orderby><D:order><D:prop><D:getlastmodified/></D:prop><D:descending/></D:order></D:orderby>"
+        ));
+        assert!(xml.contains("<D:limit><D:nresults>10</D:nresults></D:limit>"));
+    }
+}
@@ -0,0 +1,268 @@
+//! Types describing the remote server implementation.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use reqwest::header::HeaderMap;
+use url::Url;
+
+use crate::types::options_cmd::DavCapabilities;
+
+/// Known WebDAV server implementations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerKind {
+    Nextcloud,
+    OwnCloud,
+    SabreDav,
+    ApacheModDav,
+    Iis,
+    Synology,
+    Jianguoyun,
+    Box,
+    FourShared,
+    Unknown,
+}
+
+/// Result of fingerprinting the server behind a client.
+#[derive(Debug, Clone)]
+pub struct ServerInfo {
+    pub kind: ServerKind,
+    pub version: Option<String>,
+    /// Raw value of the `Server` response header, if any.
+    pub server_header: Option<String>,
+}
+
+/// Behaviour switches for servers that deviate from RFC 4918.
+#[derive(Debug, Clone, Default)]
+pub struct ServerQuirks {
+    /// Send `Translate: f` on GET and PUT so IIS serves the raw file instead of
+    /// handing it to a script handler (ASP, ASP.NET) first.
+    pub translate_f: bool,
+    /// The server refuses `Depth: infinity`, recursive listings are crawled with depth 1 requests.
+    pub finite_depth: bool,
+    /// Percent-encode every reserved character of the `Destination` header.
+    pub encode_destination: bool,
+    /// Send the `Destination` header as an absolute path instead of a full URI, for servers
+    /// behind proxies that rewrite the host.
+    pub path_destination: bool,
+    /// The server hands out weak etags (Apache mod_dav) which have to be sent as strong ones
+    /// in `If-Match`, and which may change on metadata updates.
+    pub weak_etags: bool,
+    /// Send PROPFIND without a body, for appliances that reject the `allprop` request body.
+    pub empty_propfind_body: bool,
+    /// Accept multistatus bodies with missing or malformed properties: dates and numbers which
+    /// do not parse are dropped, resources without `getlastmodified` (Nextcloud CardDAV
+    /// collections) are listed as modified at the Unix epoch.
+    pub lenient_parsing: bool,
+}
+
+impl ServerQuirks {
+    /// The quirks known to be needed by the given server implementation.
+    pub fn for_server(kind: &ServerKind) -> ServerQuirks {
+        match kind {
+            ServerKind::Iis => ServerQuirks {
+                translate_f: true,
+                ..Default::default()
+            },
+            ServerKind::ApacheModDav => ServerQuirks {
+                weak_etags: true,
+                ..Default::default()
+            },
+            ServerKind::Box | ServerKind::FourShared => ServerQuirks {
+                finite_depth: true,
+                encode_destination: true,
+                ..Default::default()
+            },
+            ServerKind::Nextcloud | ServerKind::OwnCloud | ServerKind::Jianguoyun => ServerQuirks {
+                lenient_parsing: true,
+                ..Default::default()
+            },
+            _ => ServerQuirks::default(),
+        }
+    }
+
+    /// The quirks for a fingerprinted server and the capabilities it advertised in OPTIONS
+    ///
+    /// Starts from `for_server`. Servers advertising the Apache `fs` property set get weak
+    /// etags whatever their `Server` header says, since mod_dav is often behind a proxy.
+    pub fn detect(info: &ServerInfo, capabilities: &DavCapabilities) -> ServerQuirks {
+        let mut quirks = ServerQuirks::for_server(&info.kind);
+        if capabilities.supports("<http://apache.org/dav/propset/fs/1>") {
+            quirks.weak_etags = true;
+        }
+        quirks
+    }
+}
+
+impl Display for ServerKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ServerKind::Nextcloud => "Nextcloud",
+            ServerKind::OwnCloud => "ownCloud",
+            ServerKind::SabreDav => "SabreDAV",
+            ServerKind::ApacheModDav => "Apache mod_dav",
+            ServerKind::Iis => "IIS",
+            ServerKind::Synology => "Synology",
+            ServerKind::Jianguoyun => "Jianguoyun",
+            ServerKind::Box => "Box",
+            ServerKind::FourShared => "4shared",
+            ServerKind::Unknown => "Unknown",
+        };
+        f.write_str(name)
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Extract the version from a `Product/1.2.3 (comment)` style token.
+fn product_version(header: &str, product: &str) -> Option<String> {
+    header
+        .split_whitespace()
+        .find_map(|token| token.strip_prefix(product)?.strip_prefix('/'))
+        .map(|version| version.to_owned())
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+fn on_domain(host: &str, domain: &str) -> bool {
+    host.strip_suffix(domain)
+        .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('.'))
+}
+
+impl ServerInfo {
+    /// Identify the server from the host, the response headers and the body of a PROPFIND.
+    pub fn detect(host: &str, headers: &HeaderMap, body: &str) -> ServerInfo {
+        let server_header = header_str(headers, "server").map(|value| value.to_owned());
+        let server = server_header.as_deref().unwrap_or("");
+        let sabre_version = header_str(headers, "x-sabre-version").map(|value| value.to_owned());
+        let host = Url::parse(host)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase))
+            .unwrap_or_default();
+
+        let (kind, version) = if on_domain(&host, "jianguoyun.com") {
+            (ServerKind::Jianguoyun, None)
+        } else if on_domain(&host, "box.com") {
+            (ServerKind::Box, None)
+        } else if on_domain(&host, "4shared.com") {
+            (ServerKind::FourShared, None)
+        } else if body.contains("http://nextcloud.org/ns") {
+            (ServerKind::Nextcloud, None)
+        } else if body.contains("http://owncloud.org/ns") {
+            (ServerKind::OwnCloud, None)
+        } else if sabre_version.is_some() || body.contains("http://sabredav.org/ns") {
+            (ServerKind::SabreDav, sabre_version)
+        } else if server.contains("Microsoft-IIS") {
+            (ServerKind::Iis, product_version(server, "Microsoft-IIS"))
+        } else if server.to_lowercase().contains("synology")
+            || on_domain(&host, "synology.me")
+            || on_domain(&host, "quickconnect.to")
+        {
+            (ServerKind::Synology, None)
+        } else if server.starts_with("Apache") {
+            (ServerKind::ApacheModDav, product_version(server, "Apache"))
+        } else {
+            (ServerKind::Unknown, None)
+        };
+
+        ServerInfo {
+            kind,
+            version,
+            server_header,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    #[test]
+    fn detect_nextcloud_from_namespaces() {
+        let body = r#"<d:multistatus xmlns:d="DAV:" xmlns:s="http://sabredav.org/ns" xmlns:oc="http://owncloud.org/ns" xmlns:nc="http://nextcloud.org/ns"/>"#;
+        let info = ServerInfo::detect(
+            "https://cloud.example.com/remote.php/dav/files/admin",
+            &headers(&[("server", "nginx")]),
+            body,
+        );
+        assert_eq!(info.kind, ServerKind::Nextcloud);
+        assert_eq!(info.server_header.as_deref(), Some("nginx"));
+    }
+
+    #[test]
+    fn detect_iis_and_apache_versions() {
+        let info = ServerInfo::detect(
+            "http://example.com",
+            &headers(&[("server", "Microsoft-IIS/10.0")]),
+            "",
+        );
+        assert_eq!(info.kind, ServerKind::Iis);
+        assert_eq!(info.version.as_deref(), Some("10.0"));
+
+        let info = ServerInfo::detect(
+            "http://example.com",
+            &headers(&[("server", "Apache/2.4.41 (Ubuntu)")]),
+            "",
+        );
+        assert_eq!(info.kind, ServerKind::ApacheModDav);
+        assert_eq!(info.version.as_deref(), Some("2.4.41"));
+    }
+
+    #[test]
+    fn iis_quirks_send_translate_header() {
+        assert!(ServerQuirks::for_server(&ServerKind::Iis).translate_f);
+        assert!(!ServerQuirks::for_server(&ServerKind::Nextcloud).translate_f);
+    }
+
+    #[test]
+    fn box_quirks_limit_depth() {
+        let info = ServerInfo::detect("https://dav.box.com/dav", &HeaderMap::new(), "");
+        assert_eq!(info.kind, ServerKind::Box);
+        for host in [
+            "https://dropbox.com/dav",
+            "https://mybox.company.net/box.com",
+        ] {
+            let info = ServerInfo::detect(host, &HeaderMap::new(), "");
+            assert_eq!(info.kind, ServerKind::Unknown, "{}", host);
+        }
+        let quirks = ServerQuirks::for_server(&info.kind);
+        assert!(quirks.finite_depth);
+        assert!(quirks.encode_destination);
+    }
+
+    #[test]
+    fn detect_quirks_from_options() {
+        let info = ServerInfo::detect(
+            "https://example.com/dav",
+            &headers(&[("server", "nginx")]),
+            "",
+        );
+        let capabilities = DavCapabilities::from_headers(&headers(&[(
+            "dav",
+            "1,2,<http://apache.org/dav/propset/fs/1>",
+        )]));
+        let quirks = ServerQuirks::detect(&info, &capabilities);
+        assert!(quirks.weak_etags);
+        assert!(!quirks.lenient_parsing);
+
+        let info = ServerInfo::detect("https://dav.jianguoyun.com/dav/", &HeaderMap::new(), "");
+        let quirks = ServerQuirks::detect(&info, &DavCapabilities::default());
+        assert!(quirks.lenient_parsing && !quirks.weak_etags);
+    }
+
+    #[test]
+    fn detect_jianguoyun_from_host() {
+        let info = ServerInfo::detect("https://dav.jianguoyun.com/dav/", &HeaderMap::new(), "");
+        assert_eq!(info.kind, ServerKind::Jianguoyun);
+    }
+}
@@ -0,0 +1,73 @@
+//! Cumulative transfer statistics of a client.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Snapshot of the counters returned by `Client::stats`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    pub requests: u64,
+    /// Requests which failed to complete or were answered with a 4xx or 5xx status.
+    pub failures: u64,
+    pub bytes_sent: u64,
+    /// Sum of the `Content-Length` of the responses.
+    pub bytes_received: u64,
+    pub total_latency: Duration,
+}
+
+impl TransferStats {
+    pub fn average_latency(&self) -> Option<Duration> {
+        match self.requests {
+            0 => None,
+            requests => Some(self.total_latency / requests as u32),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StatsCounters {
+    requests: AtomicU64,
+    failures: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+impl StatsCounters {
+    pub(crate) fn record(
+        &self,
+        status: Option<u16>,
+        bytes_sent: Option<u64>,
+        bytes_received: Option<u64>,
+        elapsed: Duration,
+    ) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if status.map(|status| status >= 400).unwrap_or(true) {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+        }
+        self.bytes_sent
+            .fetch_add(bytes_sent.unwrap_or(0), Ordering::Relaxed);
+        self.bytes_received
+            .fetch_add(bytes_received.unwrap_or(0), Ordering::Relaxed);
+        self.latency_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> TransferStats {
+        TransferStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            total_latency: Duration::from_micros(self.latency_micros.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.requests.store(0, Ordering::Relaxed);
+        self.failures.store(0, Ordering::Relaxed);
+        self.bytes_sent.store(0, Ordering::Relaxed);
+        self.bytes_received.store(0, Ordering::Relaxed);
+        self.latency_micros.store(0, Ordering::Relaxed);
+    }
+}
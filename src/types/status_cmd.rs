@@ -0,0 +1,90 @@
+//! Types and serialisation for multistatus responses reporting only a status per resource,
+//! as returned by DELETE, MOVE and COPY on collections.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::types::list_cmd::status_is_ok;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusMultiStatus {
+    #[serde(rename = "response", default)]
+    pub responses: Vec<StatusResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusResponse {
+    #[serde(rename = "href", default)]
+    pub hrefs: Vec<String>,
+    #[serde(default)]
+    pub status: String,
+    #[serde(rename = "responsedescription")]
+    pub description: Option<String>,
+}
+
+/// A resource that could not be processed as part of a multistatus response.
+#[derive(Debug, Clone)]
+pub struct StatusFailure {
+    pub href: String,
+    pub status: u16,
+    pub description: Option<String>,
+}
+
+impl StatusFailure {
+    /// 424 Failed Dependency: the resource was skipped because another one failed.
+    pub fn is_failed_dependency(&self) -> bool {
+        self.status == 424
+    }
+}
+
+/// Numeric code of a `HTTP/1.1 200 OK` style status line, 0 when malformed.
+pub(crate) fn status_code(status: &str) -> u16 {
+    status
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0)
+}
+
+impl StatusMultiStatus {
+    /// All resources whose status is not 2xx.
+    pub fn failures(&self) -> Vec<StatusFailure> {
+        self.responses
+            .iter()
+            .filter(|response| !status_is_ok(&response.status))
+            .flat_map(|response| {
+                response.hrefs.iter().map(|href| StatusFailure {
+                    href: href.clone(),
+                    status: status_code(&response.status),
+                    description: response.description.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_failed_dependency() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <D:multistatus xmlns:D="DAV:">
+            <D:response>
+                <D:href>http://www.example.com/container/resource3</D:href>
+                <D:status>HTTP/1.1 423 Locked</D:status>
+            </D:response>
+            <D:response>
+                <D:href>http://www.example.com/container/</D:href>
+                <D:status>HTTP/1.1 424 Failed Dependency</D:status>
+            </D:response>
+        </D:multistatus>"#;
+
+        let parsed: StatusMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let failures = parsed.failures();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].status, 423);
+        assert!(!failures[0].is_failed_dependency());
+        assert!(failures[1].is_failed_dependency());
+    }
+}
@@ -0,0 +1,96 @@
+//! Types and serialisation for the RFC 6578 sync-collection REPORT.
+
+use crate::types::list_cmd::{status_is_ok, ListEntity, ListMultiStatus};
+use crate::types::status_cmd::status_code;
+use crate::types::{xml_escape, DecodeError, Error, FieldError, Result};
+
+/// Changes of a collection since a previous sync token.
+#[derive(Debug, Clone)]
+pub struct SyncChanges {
+    /// Members added or modified.
+    pub changed: Vec<ListEntity>,
+    /// Hrefs of the members removed.
+    pub deleted: Vec<String>,
+    /// Token to pass to the next call.
+    pub sync_token: String,
+    /// The server returned only part of the changes, call again with `sync_token` for the rest.
+    pub truncated: bool,
+}
+
+pub(crate) fn sync_collection_body(sync_token: Option<&str>, limit: Option<u32>) -> String {
+    let limit = match limit {
+        Some(limit) => format!("<D:limit><D:nresults>{}</D:nresults></D:limit>", limit),
+        None => String::new(),
+    };
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8" ?><D:sync-collection xmlns:D="DAV:"><D:sync-token>{}</D:sync-token><D:sync-level>1</D:sync-level>{}<D:prop><D:getlastmodified/><D:getetag/><D:getcontentlength/><D:getcontenttype/><D:resourcetype/></D:prop></D:sync-collection>"#,
+        xml_escape(sync_token.unwrap_or_default()),
+        limit
+    )
+}
+
+impl TryFrom<ListMultiStatus> for SyncChanges {
+    type Error = Error;
+
+    fn try_from(multi_status: ListMultiStatus) -> Result<Self> {
+        let sync_token = multi_status.sync_token.ok_or_else(|| {
+            Error::Decode(DecodeError::FieldNotFound(FieldError {
+                field: "sync-token".to_owned(),
+            }))
+        })?;
+        let mut changes = SyncChanges {
+            changed: vec![],
+            deleted: vec![],
+            sync_token,
+            truncated: false,
+        };
+        for response in multi_status.responses {
+            match response.status.as_deref() {
+                Some(status) if status_code(status) == 404 => changes.deleted.push(response.href),
+                // 507 Insufficient Storage on the collection itself marks a truncated result
+                Some(status) if status_code(status) == 507 => changes.truncated = true,
+                Some(status) if !status_is_ok(status) && response.prop_stat.is_empty() => {}
+                _ => changes.changed.push(ListEntity::try_from(response)?),
+            }
+        }
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sync_collection_response() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8" ?>
+        <D:multistatus xmlns:D="DAV:">
+            <D:response>
+                <D:href>/dav/cal/new.ics</D:href>
+                <D:propstat>
+                    <D:prop>
+                        <D:getlastmodified>Wed, 10 Apr 2019 14:00:00 GMT</D:getlastmodified>
+                        <D:getetag>"00001-abcd1"</D:getetag>
+                        <D:resourcetype/>
+                    </D:prop>
+                    <D:status>HTTP/1.1 200 OK</D:status>
+                </D:propstat>
+            </D:response>
+            <D:response>
+                <D:href>/dav/cal/old.ics</D:href>
+                <D:status>HTTP/1.1 404 Not Found</D:status>
+            </D:response>
+            <D:response>
+                <D:href>/dav/cal/</D:href>
+                <D:status>HTTP/1.1 507 Insufficient Storage</D:status>
+            </D:response>
+            <D:sync-token>http://example.com/ns/sync/1234</D:sync-token>
+        </D:multistatus>"#;
+
+        let changes = SyncChanges::try_from(ListMultiStatus::parse(xml).unwrap()).unwrap();
+        assert_eq!(changes.changed.len(), 1);
+        assert_eq!(changes.deleted, vec!["/dav/cal/old.ics".to_owned()]);
+        assert_eq!(changes.sync_token, "http://example.com/ns/sync/1234");
+        assert!(changes.truncated);
+    }
+}
@@ -0,0 +1,227 @@
+//! The date type used by the crate.
+//!
+//! `DateTime` is `chrono::DateTime<Utc>` with the `chrono` feature and `time::OffsetDateTime`
+//! with only the `time` feature, so projects standardized on one of the crates do not need the
+//! other. `chrono` takes precedence when both are enabled, then `DavDateTime` converts from and
+//! to `time::OffsetDateTime`. The conversions the crate relies on are provided by the sealed
+//! `DavDateTime` trait.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+compile_error!("either the `chrono` or the `time` feature has to be enabled");
+
+#[cfg(feature = "chrono")]
+pub type DateTime = chrono::DateTime<chrono::Utc>;
+
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub type DateTime = time::OffsetDateTime;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Conversions between the date type of the crate and `SystemTime`.
+pub trait DavDateTime: sealed::Sealed + Sized {
+    fn from_system_time(time: SystemTime) -> Self;
+
+    fn to_system_time(&self) -> SystemTime;
+
+    fn now() -> Self {
+        Self::from_system_time(SystemTime::now())
+    }
+
+    fn unix_epoch() -> Self {
+        Self::from_system_time(UNIX_EPOCH)
+    }
+
+    /// Seconds since the unix epoch, negative before it.
+    fn epoch_seconds(&self) -> i64 {
+        match self.to_system_time().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+        }
+    }
+
+    fn from_epoch_seconds(seconds: i64) -> Self {
+        let offset = Duration::from_secs(seconds.unsigned_abs());
+        Self::from_system_time(if seconds < 0 {
+            UNIX_EPOCH - offset
+        } else {
+            UNIX_EPOCH + offset
+        })
+    }
+
+    /// RFC 3339 in UTC with second precision, `2019-04-10T14:00:00Z`.
+    fn to_rfc3339_seconds(&self) -> String;
+
+    #[cfg(feature = "time")]
+    fn from_offset_date_time(time: time::OffsetDateTime) -> Self {
+        Self::from_system_time(time.into())
+    }
+
+    #[cfg(feature = "time")]
+    fn to_offset_date_time(&self) -> time::OffsetDateTime {
+        self.to_system_time().into()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl sealed::Sealed for chrono::DateTime<chrono::Utc> {}
+
+#[cfg(feature = "chrono")]
+impl DavDateTime for chrono::DateTime<chrono::Utc> {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+
+    fn to_system_time(&self) -> SystemTime {
+        (*self).into()
+    }
+
+    fn to_rfc3339_seconds(&self) -> String {
+        self.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    }
+}
+
+#[cfg(feature = "time")]
+impl sealed::Sealed for time::OffsetDateTime {}
+
+#[cfg(feature = "time")]
+impl DavDateTime for time::OffsetDateTime {
+    fn from_system_time(time: SystemTime) -> Self {
+        time.into()
+    }
+
+    fn to_system_time(&self) -> SystemTime {
+        (*self).into()
+    }
+
+    fn to_rfc3339_seconds(&self) -> String {
+        let utc = self.to_offset(time::UtcOffset::UTC);
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            utc.year(),
+            u8::from(utc.month()),
+            utc.day(),
+            utc.hour(),
+            utc.minute(),
+            utc.second()
+        )
+    }
+}
+
+/// Serde representation of dates as RFC 3339 strings, independent of the date crate.
+pub mod rfc3339 {
+    use super::{DateTime, DavDateTime};
+
+    pub fn serialize<S>(time: &DateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&time.to_rfc3339_seconds())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: String = serde::Deserialize::deserialize(deserializer)?;
+        parse(&value).ok_or_else(|| serde::de::Error::custom("invalid RFC 3339 date"))
+    }
+
+    #[cfg(feature = "chrono")]
+    pub(crate) fn parse(value: &str) -> Option<DateTime> {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|time| time.with_timezone(&chrono::Utc))
+    }
+
+    #[cfg(all(feature = "time", not(feature = "chrono")))]
+    pub(crate) fn parse(value: &str) -> Option<DateTime> {
+        time::OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339).ok()
+    }
+}
+
+/// Serde representation of optional dates as RFC 3339 strings.
+pub mod rfc3339_option {
+    use super::{rfc3339, DateTime, DavDateTime};
+
+    pub fn serialize<S>(time: &Option<DateTime>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match time {
+            Some(time) => serializer.serialize_some(&time.to_rfc3339_seconds()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+        value
+            .map(|value| {
+                rfc3339::parse(&value)
+                    .ok_or_else(|| serde::de::Error::custom("invalid RFC 3339 date"))
+            })
+            .transpose()
+    }
+}
+
+/// Parse a `getlastmodified` value
+///
+/// Besides the HTTP date formats (RFC 1123, RFC 850 and asctime) servers send RFC 3339 dates,
+/// ISO 8601 ones with a space for the `T` or without an offset, taken as UTC, and seconds since
+/// the Unix epoch.
+pub(crate) fn parse_http_date(value: &str) -> Option<DateTime> {
+    let value = value.trim();
+    if let Ok(time) = httpdate::parse_http_date(value) {
+        return Some(DateTime::from_system_time(time));
+    }
+    if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return value.parse().ok().map(DateTime::from_epoch_seconds);
+    }
+    let mut value = value.to_owned();
+    if value.as_bytes().get(10) == Some(&b' ') {
+        value.replace_range(10..11, "T");
+    }
+    rfc3339::parse(&value).or_else(|| rfc3339::parse(&format!("{}Z", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_epoch_seconds() {
+        let time = DateTime::from_epoch_seconds(1554904800);
+        assert_eq!(time.epoch_seconds(), 1554904800);
+        assert_eq!(time.to_rfc3339_seconds(), "2019-04-10T14:00:00Z");
+        #[cfg(feature = "time")]
+        assert_eq!(
+            DateTime::from_offset_date_time(time.to_offset_date_time()),
+            time
+        );
+    }
+
+    #[test]
+    fn parses_http_date_variants() {
+        for value in [
+            "Wed, 10 Apr 2019 14:00:00 GMT",
+            "Wednesday, 10-Apr-19 14:00:00 GMT",
+            "Wed Apr 10 14:00:00 2019",
+            "2019-04-10T16:00:00+02:00",
+            "2019-04-10 14:00:00Z",
+            "2019-04-10T14:00:00",
+            " 1554904800 ",
+        ] {
+            let time = parse_http_date(value).unwrap();
+            assert_eq!(time.epoch_seconds(), 1554904800, "{}", value);
+        }
+        assert!(parse_http_date("yesterday").is_none());
+        assert!(parse_http_date("").is_none());
+    }
+}
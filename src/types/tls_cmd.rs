@@ -0,0 +1,37 @@
+//! TLS settings of the agent built by `ClientBuilder`.
+
+use reqwest::Certificate;
+#[cfg(feature = "__identity")]
+use reqwest::Identity;
+
+/// Certificates and verification settings, see `ClientBuilder::add_root_certificate_pem`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TlsSettings {
+    pub(crate) accept_invalid_certs: bool,
+    pub(crate) root_certificates: Vec<Certificate>,
+    #[cfg(feature = "__identity")]
+    pub(crate) identity: Option<Identity>,
+}
+
+impl TlsSettings {
+    /// Whether anything differs from the defaults of reqwest.
+    pub(crate) fn is_customized(&self) -> bool {
+        #[cfg(feature = "__identity")]
+        if self.identity.is_some() {
+            return true;
+        }
+        self.accept_invalid_certs || !self.root_certificates.is_empty()
+    }
+
+    pub(crate) fn apply(self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        builder = builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        for certificate in self.root_certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        #[cfg(feature = "__identity")]
+        if let Some(identity) = self.identity {
+            builder = builder.identity(identity);
+        }
+        builder
+    }
+}
@@ -0,0 +1,140 @@
+//! Types of the background transfer queue and of directory transfers.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::types::Error;
+
+/// Work for the transfer queue.
+#[derive(Debug, Clone)]
+pub enum TransferJob {
+    Upload { path: String, data: Vec<u8> },
+    Delete { path: String },
+    Mkcol { path: String },
+    Copy { from: String, to: String },
+    Move { from: String, to: String },
+}
+
+impl TransferJob {
+    /// The resource the job works on, the source for copies and moves.
+    pub fn path(&self) -> &str {
+        match self {
+            TransferJob::Upload { path, .. }
+            | TransferJob::Delete { path }
+            | TransferJob::Mkcol { path } => path,
+            TransferJob::Copy { from, .. } | TransferJob::Move { from, .. } => from,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TransferFailure {
+    pub path: String,
+    pub error: Error,
+}
+
+/// What became of the jobs of a queue, returned by `TransferQueue::shutdown`
+#[derive(Debug, Default)]
+pub struct TransferReport {
+    pub completed: Vec<String>,
+    pub failed: Vec<TransferFailure>,
+    /// Jobs stopped by the shutdown, either in flight or not started yet.
+    pub cancelled: Vec<String>,
+}
+
+/// Runs transfer jobs in the background with limited concurrency, see `Client::transfer_queue`
+#[derive(Debug)]
+pub struct TransferQueue {
+    pub(crate) sender: UnboundedSender<TransferJob>,
+    pub(crate) worker: JoinHandle<()>,
+    pub(crate) cancellation: CancellationToken,
+    pub(crate) report: Arc<Mutex<TransferReport>>,
+}
+
+/// Settings of `Client::download_dir`
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Files downloaded at the same time.
+    pub concurrency: usize,
+    /// Replace local files which already exist, otherwise they are skipped.
+    pub overwrite: bool,
+    /// Stop the transfer once cancelled, see `Error::TransferCancelled`.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        DownloadOptions {
+            concurrency: 4,
+            overwrite: true,
+            cancellation: None,
+        }
+    }
+}
+
+/// Settings of `Client::upload_dir`
+#[derive(Debug, Clone)]
+pub struct UploadOptions {
+    /// Files uploaded at the same time.
+    pub concurrency: usize,
+    /// Skip files whose remote copy has the same size and is not older than the local one.
+    pub skip_unchanged: bool,
+    /// Give the remote files the modification time of the local ones, see `PutOptions::mtime`.
+    pub preserve_mtime: bool,
+    /// Stop the transfer once cancelled, see `Error::TransferCancelled`.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        UploadOptions {
+            concurrency: 4,
+            skip_unchanged: false,
+            preserve_mtime: false,
+            cancellation: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EntryOutcome {
+    Transferred {
+        bytes: u64,
+    },
+    /// Left alone as asked by the options.
+    Skipped,
+    /// Not transferred, or interrupted, because the transfer was cancelled.
+    Cancelled,
+    Failed(Error),
+}
+
+/// What became of one file of a directory transfer.
+#[derive(Debug)]
+pub struct DirEntryResult {
+    /// Path on the server.
+    pub remote: String,
+    pub local: PathBuf,
+    pub outcome: EntryOutcome,
+}
+
+/// Outcome of every file of `Client::download_dir` or `Client::upload_dir`
+#[derive(Debug, Default)]
+pub struct DirTransferReport {
+    pub entries: Vec<DirEntryResult>,
+}
+
+impl DirTransferReport {
+    pub fn failures(&self) -> impl Iterator<Item = &DirEntryResult> {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry.outcome, EntryOutcome::Failed(_)))
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
@@ -0,0 +1,91 @@
+//! Types of DeltaV (RFC 3253) versioning.
+
+use crate::types::list_cmd::{status_is_ok, ListMultiStatus};
+use crate::types::timestamp::{rfc3339, DateTime};
+
+/// Body of the version-tree REPORT.
+pub(crate) const VERSION_TREE_BODY: &str = r#"<?xml version="1.0" encoding="utf-8" ?><D:version-tree xmlns:D="DAV:"><D:prop><D:version-name/><D:creator-displayname/><D:creationdate/><D:getcontentlength/><D:getetag/></D:prop></D:version-tree>"#;
+
+/// A version of a version-controlled resource, see `Client::version_tree`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DavVersion {
+    /// The version resource, pass it to `Client::get_version` for the content.
+    pub href: String,
+    /// Label of the version given by the server, like `V3` or a revision number.
+    pub version_name: Option<String>,
+    pub creator: Option<String>,
+    pub created: Option<DateTime>,
+    pub content_length: Option<i64>,
+    pub tag: Option<String>,
+}
+
+impl From<ListMultiStatus> for Vec<DavVersion> {
+    fn from(multi_status: ListMultiStatus) -> Self {
+        multi_status
+            .responses
+            .into_iter()
+            .map(|response| {
+                let mut props = response
+                    .prop_stat
+                    .into_iter()
+                    .filter(|prop_stat| {
+                        prop_stat.status.is_empty() || status_is_ok(&prop_stat.status)
+                    })
+                    .flat_map(|prop_stat| prop_stat.prop.other)
+                    .filter(|((namespace, _), _)| namespace == "DAV:")
+                    .map(|((_, name), value)| (name, value))
+                    .collect::<std::collections::HashMap<_, _>>();
+                DavVersion {
+                    href: response.href,
+                    version_name: props.remove("version-name"),
+                    creator: props.remove("creator-displayname"),
+                    created: props
+                        .remove("creationdate")
+                        .and_then(|value| rfc3339::parse(&value)),
+                    content_length: props
+                        .remove("getcontentlength")
+                        .and_then(|value| value.parse().ok()),
+                    tag: props.remove("getetag"),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DavVersion;
+    use crate::types::list_cmd::ListMultiStatus;
+    use crate::types::timestamp::DavDateTime;
+
+    #[test]
+    fn parse_version_tree() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <D:multistatus xmlns:D="DAV:">
+            <D:response>
+                <D:href>/repo/!svn/ver/2/a.txt</D:href>
+                <D:propstat>
+                    <D:prop>
+                        <D:version-name>2</D:version-name>
+                        <D:creator-displayname>alice</D:creator-displayname>
+                        <D:creationdate>2019-04-10T14:00:00Z</D:creationdate>
+                        <D:getcontentlength>12</D:getcontentlength>
+                    </D:prop>
+                    <D:status>HTTP/1.1 200 OK</D:status>
+                </D:propstat>
+                <D:propstat>
+                    <D:prop><D:getetag/></D:prop>
+                    <D:status>HTTP/1.1 404 Not Found</D:status>
+                </D:propstat>
+            </D:response>
+        </D:multistatus>"#;
+        let versions: Vec<DavVersion> = ListMultiStatus::parse_raw(xml).unwrap().into();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].href, "/repo/!svn/ver/2/a.txt");
+        assert_eq!(versions[0].version_name.as_deref(), Some("2"));
+        assert_eq!(versions[0].creator.as_deref(), Some("alice"));
+        assert_eq!(versions[0].created.unwrap().epoch_seconds(), 1554904800);
+        assert_eq!(versions[0].content_length, Some(12));
+        assert_eq!(versions[0].tag, None);
+    }
+}
@@ -0,0 +1,136 @@
+//! Types and serialisation for the Yandex Disk property extensions.
+
+use crate::types::list_cmd::status_is_ok;
+use serde_derive::{Deserialize, Serialize};
+
+/// Namespace of the Yandex Disk custom properties.
+pub const YANDEX_NAMESPACE: &str = "urn:yandex:disk:meta";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YandexMultiStatus {
+    #[serde(rename = "response", default)]
+    pub responses: Vec<YandexResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YandexResponse {
+    pub href: String,
+    #[serde(rename = "propstat", default)]
+    pub prop_stat: Vec<YandexPropStat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YandexPropStat {
+    #[serde(default)]
+    pub status: String,
+    pub prop: YandexProp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct YandexProp {
+    pub public_url: Option<String>,
+    pub media_type: Option<String>,
+}
+
+/// Yandex specific properties of a resource.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct YandexProps {
+    pub href: String,
+    /// Public link, present once the resource has been published.
+    pub public_url: Option<String>,
+    pub media_type: Option<String>,
+}
+
+/// Preview sizes accepted by the Yandex preview endpoint.
+#[derive(Debug, Clone)]
+pub enum YandexPreviewSize {
+    S,
+    M,
+    L,
+    Xl,
+    Xxl,
+    Xxxl,
+    /// Explicit `<width>x<height>`, either side may be left out.
+    Pixels(Option<u32>, Option<u32>),
+}
+
+impl YandexPreviewSize {
+    pub(crate) fn as_query(&self) -> String {
+        match self {
+            YandexPreviewSize::S => "S".to_owned(),
+            YandexPreviewSize::M => "M".to_owned(),
+            YandexPreviewSize::L => "L".to_owned(),
+            YandexPreviewSize::Xl => "XL".to_owned(),
+            YandexPreviewSize::Xxl => "XXL".to_owned(),
+            YandexPreviewSize::Xxxl => "XXXL".to_owned(),
+            YandexPreviewSize::Pixels(width, height) => format!(
+                "{}x{}",
+                width.map(|w| w.to_string()).unwrap_or_default(),
+                height.map(|h| h.to_string()).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+impl From<YandexResponse> for YandexProps {
+    fn from(response: YandexResponse) -> Self {
+        let prop = response
+            .prop_stat
+            .into_iter()
+            .filter(|prop_stat| status_is_ok(&prop_stat.status))
+            .fold(YandexProp::default(), |acc, prop_stat| YandexProp {
+                public_url: acc.public_url.or(prop_stat.prop.public_url),
+                media_type: acc.media_type.or(prop_stat.prop.media_type),
+            });
+        YandexProps {
+            href: response.href,
+            public_url: prop.public_url.filter(|url| !url.is_empty()),
+            media_type: prop.media_type.filter(|media_type| !media_type.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_public_url() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <d:multistatus xmlns:d="DAV:">
+            <d:response>
+                <d:href>/photos/cat.jpg</d:href>
+                <d:propstat>
+                    <d:status>HTTP/1.1 200 OK</d:status>
+                    <d:prop>
+                        <public_url xmlns="urn:yandex:disk:meta">https://yadi.sk/i/abcdef</public_url>
+                    </d:prop>
+                </d:propstat>
+                <d:propstat>
+                    <d:status>HTTP/1.1 404 Not Found</d:status>
+                    <d:prop>
+                        <media_type xmlns="urn:yandex:disk:meta"/>
+                    </d:prop>
+                </d:propstat>
+            </d:response>
+        </d:multistatus>"#;
+
+        let parsed: YandexMultiStatus = serde_xml_rs::from_str(xml).unwrap();
+        let props = YandexProps::from(parsed.responses[0].clone());
+        assert_eq!(props.href, "/photos/cat.jpg");
+        assert_eq!(
+            props.public_url.as_deref(),
+            Some("https://yadi.sk/i/abcdef")
+        );
+        assert_eq!(props.media_type, None);
+    }
+
+    #[test]
+    fn preview_size_query() {
+        assert_eq!(YandexPreviewSize::Xl.as_query(), "XL");
+        assert_eq!(
+            YandexPreviewSize::Pixels(Some(128), None).as_query(),
+            "128x"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! DeltaV (RFC 3253) versioning of resources.
+
+use reqwest::{Method, Response};
+
+use crate::types::list_cmd::ListMultiStatus;
+use crate::types::version_cmd::{DavVersion, VERSION_TREE_BODY};
+use crate::types::{Dav2xx, Result};
+use crate::{Client, Depth};
+
+impl Client {
+    pub async fn version_control_raw(&self, path: &str) -> Result<Response> {
+        let builder = self
+            .start_request(Method::from_bytes(b"VERSION-CONTROL")?, path)
+            .await?;
+        self.execute(builder).await
+    }
+
+    /// Put a resource under version control, its later changes create new versions
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn version_control(&self, path: &str) -> Result<()> {
+        self.version_control_raw(path).await?.dav2xx().await?;
+        Ok(())
+    }
+
+    pub async fn version_tree_raw(&self, path: &str) -> Result<Response> {
+        self.report_raw(path, Depth::Number(0), VERSION_TREE_BODY)
+            .await
+    }
+
+    /// List the versions of a version-controlled resource with the version-tree REPORT
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn version_tree(&self, path: &str) -> Result<Vec<DavVersion>> {
+        let response = self.version_tree_raw(path).await?.dav2xx().await?;
+        Ok(ListMultiStatus::parse_raw(&response.text().await?)?.into())
+    }
+
+    /// Get the content of a version returned by `version_tree`
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn get_version(&self, version: &DavVersion) -> Result<Response> {
+        self.get_raw(&self.path_of(&version.href))
+            .await?
+            .dav2xx()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{body_string_contains, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::ClientBuilder;
+
+    #[tokio::test]
+    async fn version_tree_and_retrieval() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("VERSION-CONTROL"))
+            .and(path("/dav/a.txt"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("REPORT"))
+            .and(path("/dav/a.txt"))
+            .and(header("depth", "0"))
+            .and(body_string_contains("<D:version-tree"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/versions/a.txt/1</D:href><D:propstat><D:prop><D:version-name>V1</D:version-name></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response></D:multistatus>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dav/versions/a.txt/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("first"))
+            .mount(&mock_server)
+            .await;
+
+        let client = ClientBuilder::new()
+            .set_host(format!("{}/dav", mock_server.uri()))
+            .build()
+            .unwrap();
+        client.version_control("/a.txt").await.unwrap();
+        let versions = client.version_tree("/a.txt").await.unwrap();
+        assert_eq!(versions[0].version_name.as_deref(), Some("V1"));
+        let response = client.get_version(&versions[0]).await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "first");
+        mock_server.verify().await;
+    }
+}
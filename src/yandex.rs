@@ -0,0 +1,87 @@
+//! Yandex Disk extensions: custom properties, previews and hashed uploads.
+
+use md5::{Digest, Md5};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Method, Response};
+use sha2::Sha256;
+
+use crate::types::yandex_cmd::{YandexMultiStatus, YandexPreviewSize, YandexProps};
+use crate::types::{Dav2xx, Result};
+use crate::Client;
+
+impl Client {
+    /// Get the Yandex custom properties (public link, media type) of a resource
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn yandex_props(&self, path: &str) -> Result<YandexProps> {
+        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
+            <D:propfind xmlns:D="DAV:">
+                <D:prop>
+                    <public_url xmlns="urn:yandex:disk:meta"/>
+                    <media_type xmlns="urn:yandex:disk:meta"/>
+                </D:prop>
+            </D:propfind>
+        "#;
+        let builder = self
+            .start_request(Method::from_bytes(b"PROPFIND")?, path)
+            .await?
+            .header("depth", "0")
+            .body(body);
+        let text = self.execute(builder).await?.dav2xx().await?.text().await?;
+        let multi_status: YandexMultiStatus = serde_xml_rs::from_str(&text)?;
+        Ok(multi_status
+            .responses
+            .into_iter()
+            .next()
+            .map(YandexProps::from)
+            .unwrap_or(YandexProps {
+                href: path.to_owned(),
+                public_url: None,
+                media_type: None,
+            }))
+    }
+
+    /// Get the image preview Yandex generates for a file
+    ///
+    /// Use absolute path to the webdav server file location
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn yandex_preview(&self, path: &str, size: YandexPreviewSize) -> Result<Response> {
+        let builder = self
+            .start_request(Method::GET, path)
+            .await?
+            .query(&[("preview", ""), ("size", &size.as_query())]);
+        self.execute(builder).await?.dav2xx().await
+    }
+
+    /// Upload a file with the size and hash headers Yandex uses to deduplicate uploads
+    ///
+    /// When the server already stores a file with the same hashes, the content is not transferred again.
+    ///
+    /// Use absolute path to the webdav server folder location
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, body))
+    )]
+    pub async fn yandex_put(&self, path: &str, body: Vec<u8>) -> Result<()> {
+        let md5 = hex::encode(Md5::digest(&body));
+        let sha256 = hex::encode(Sha256::digest(&body));
+        let builder = self
+            .start_request(Method::PUT, path)
+            .await?
+            .headers({
+                let mut map = HeaderMap::new();
+                map.insert(
+                    "content-type",
+                    HeaderValue::from_str("application/octet-stream")?,
+                );
+                map.insert("content-length", HeaderValue::from(body.len()));
+                map.insert("etag", HeaderValue::from_str(&md5)?);
+                map.insert("sha256", HeaderValue::from_str(&sha256)?);
+                map
+            })
+            .body(body);
+        self.execute(builder).await?.dav2xx().await?;
+        Ok(())
+    }
+}